@@ -0,0 +1,455 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::sync::Arc;
+
+use crate::types::{BlockId, BlockRef, ObjectId, OBJ_TYPE_FILE, OBJ_TYPE_ZNODE};
+use crate::block::{MultiRecipe, ZBlock};
+use crate::codec::{ZIndexRef, ZPayload, ZRegion, ZSpan, encode_z_region, encode_z_span, decode_z_span, decode_z_index};
+use crate::store::blockstore::{BlockStore, StoreError, StoreResult, hash_multi, hash_z};
+use crate::store::typed::TypedStore;
+use crate::store::decode::BlockBody;
+use crate::config::QuarxConfig;
+use crate::import::importer::ImportStats;
+use crate::reader::ObjectReader;
+use crate::analysis::tar::TarAnalyzer;
+use crate::analysis::elf::ElfAnalyzer;
+use crate::analysis::json::JsonAnalyzer;
+
+/// Проход анализатора по содержимому одного объекта.
+///
+/// Данные подаются по порядку, кусками произвольной длины (обычно L0-чанки);
+/// смещение куска — сумма длин предыдущих. Параллельный импорт ведёт
+/// проходы в отдельном потоке.
+pub trait ZScan: Send {
+    fn feed(&mut self, data: &[u8]);
+
+    /// Конец данных: найденные зоны (offset/len в байтах объекта).
+    /// Пусто — формат не распознан. `src` — произвольный доступ к уже
+    /// записанному содержимому (для форматов с оглавлением в конце).
+    fn finish(self: Box<Self>, src: &dyn ZSource) -> StoreResult<Vec<ZRegion>>;
+
+    /// Оценка памяти, занятой проходом (байт), для `import_mem_limit`.
+    /// Проходы с растущим состоянием (списки зон и т.п.) должны её давать.
+    fn mem_usage(&self) -> usize {
+        0
+    }
+}
+
+/// Чтение содержимого объекта по смещению (в `ZScan::finish`).
+pub trait ZSource {
+    fn len(&self) -> u64;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Заполнить `buf` байтами с `offset`; за концом данных — ошибка.
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> StoreResult<()>;
+}
+
+/// Структурный анализатор: размечает содержимое Z-блоками своего `z_type`.
+pub trait ZAnalyzer: Send + Sync {
+    fn z_type(&self) -> u32;
+    fn name(&self) -> &str;
+    /// Новый проход для очередного объекта.
+    fn scan(&self) -> Box<dyn ZScan>;
+
+    /// Ключ зоны для поиска (`zblocks_by_key`), обычно `z_key` от имени.
+    /// None у всех зон объекта — индекс по ключу не пишется.
+    fn region_key(&self, _r: &ZRegion) -> Option<u64> {
+        None
+    }
+}
+
+/// Ключ зоны по имени: первые 8 байт blake3.
+pub fn z_key(name: &[u8]) -> u64 {
+    let h = blake3::hash(name);
+    u64::from_be_bytes(h.as_bytes()[..8].try_into().expect("8 bytes"))
+}
+
+/// Реестр анализаторов: `z_type` -> анализатор.
+///
+/// Клонируется дёшево (анализаторы под Arc). Импорт прогоняет через
+/// содержимое каждого файла все зарегистрированные анализаторы
+/// в порядке возрастания `z_type`.
+#[derive(Clone, Default)]
+pub struct ZRegistry {
+    map: BTreeMap<u32, Arc<dyn ZAnalyzer>>,
+}
+
+impl ZRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Встроенные анализаторы, включённые в конфиге (`analysis_z_*`).
+    pub fn from_config(cfg: &QuarxConfig) -> Self {
+        let mut reg = Self::new();
+        if cfg.analysis_z_tar {
+            reg.register(Arc::new(TarAnalyzer));
+        }
+        if cfg.analysis_z_elf {
+            reg.register(Arc::new(ElfAnalyzer));
+        }
+        if cfg.analysis_z_json {
+            reg.register(Arc::new(JsonAnalyzer));
+        }
+        reg
+    }
+
+    /// Зарегистрировать анализатор; вернёт прежний с тем же `z_type`.
+    pub fn register(&mut self, a: Arc<dyn ZAnalyzer>) -> Option<Arc<dyn ZAnalyzer>> {
+        self.map.insert(a.z_type(), a)
+    }
+
+    pub fn get(&self, z_type: u32) -> Option<&Arc<dyn ZAnalyzer>> {
+        self.map.get(&z_type)
+    }
+
+    pub fn z_types(&self) -> impl Iterator<Item = u32> + '_ {
+        self.map.keys().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+}
+
+impl std::fmt::Debug for ZRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map()
+            .entries(self.map.iter().map(|(t, a)| (t, a.name())))
+            .finish()
+    }
+}
+
+/// Сколько байт meta Z-блоков копится до записи в store (без бюджета).
+const Z_BATCH: u64 = 1024 * 1024;
+
+/// Проходы всех анализаторов реестра по одному объекту.
+///
+/// Список L0 объекта здесь не хранится: в `finish` содержимое и L0 зон
+/// берутся из уже записанного дерева данных.
+pub(crate) struct ZScanSet {
+    scans: Vec<(Arc<dyn ZAnalyzer>, Box<dyn ZScan>)>,
+    pos: u64,
+    /// Бюджет памяти проходов (None — без лимита).
+    budget: Option<u64>,
+    /// Проходов, снятых по бюджету.
+    dropped: u64,
+}
+
+impl ZScanSet {
+    pub(crate) fn new(reg: &ZRegistry, budget: Option<u64>) -> Self {
+        Self {
+            scans: reg.map.values().map(|a| (a.clone(), a.scan())).collect(),
+            pos: 0,
+            budget,
+            dropped: 0,
+        }
+    }
+
+    /// Есть ли ещё проходы (иначе `push` ничего не делает).
+    pub(crate) fn is_active(&self) -> bool {
+        !self.scans.is_empty()
+    }
+
+    pub(crate) fn push(&mut self, data: &[u8]) {
+        if self.scans.is_empty() {
+            return;
+        }
+        for (_, s) in &mut self.scans {
+            s.feed(data);
+        }
+        self.pos += data.len() as u64;
+
+        // сверх бюджета снимаем самые прожорливые проходы: объект
+        // останется без их зон, но память импорта ограничена
+        let Some(budget) = self.budget else {
+            return;
+        };
+        loop {
+            let usage = self.scans.iter().map(|(_, s)| s.mem_usage() as u64);
+            if usage.sum::<u64>() <= budget {
+                break;
+            }
+            let (i, _) = self
+                .scans
+                .iter()
+                .enumerate()
+                .max_by_key(|(_, (_, s))| s.mem_usage())
+                .expect("scans not empty over budget");
+            self.scans.remove(i);
+            self.dropped += 1;
+        }
+    }
+
+    /// Записать Z-блоки найденных зон и их индексы (dedup по hash),
+    /// вернуть ссылки на индексы — по одной на `z_type` с зонами.
+    ///
+    /// `root` — корень уже записанного дерева данных объекта. Z-блоки
+    /// пишутся пачками по мере разметки: в памяти — зоны одного прохода
+    /// и meta не больше остатка бюджета (или `Z_BATCH`).
+    /// `first_l0`/`last_l0` — крайние L0 зоны в порядке объекта;
+    /// в meta — `ZRegion` (точные границы) и `ZSpan` (все L0 зоны).
+    pub(crate) fn finish<S: BlockStore + ?Sized>(
+        self,
+        store: &mut S,
+        root: BlockId,
+        stats: &mut ImportStats,
+    ) -> StoreResult<Vec<ZIndexRef>> {
+        stats.analyzers_dropped += self.dropped;
+        if self.scans.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // z_type -> (Z-блок, ключ) в порядке зон
+        let mut by_type: BTreeMap<u32, Vec<(BlockId, Option<u64>)>> = BTreeMap::new();
+        let mut scans = self.scans.into_iter();
+        while let Some((a, s)) = scans.next() {
+            let regions = {
+                let mut rd = ObjectReader::open(&*store, root)?;
+                let src = TreeSource { len: rd.len()?, rd: RefCell::new(rd) };
+                if src.len() != self.pos {
+                    return Err(StoreError::Corrupt(format!(
+                        "data tree {} has {} bytes, analyzers saw {}",
+                        root, src.len(), self.pos
+                    )));
+                }
+                s.finish(&src)?
+            };
+            for r in &regions {
+                let end = r.offset.checked_add(r.len).filter(|&e| r.len > 0 && e <= self.pos);
+                if end.is_none() {
+                    return Err(StoreError::Corrupt(format!(
+                        "analyzer {} returned region {}+{} outside of {} bytes",
+                        a.name(), r.offset, r.len, self.pos
+                    )));
+                }
+            }
+
+            // meta зон (ZSpan растёт с длиной зоны) копится пачками в пределах
+            // бюджета: остаток после ещё не законченных проходов и самих зон
+            let held: u64 = scans.as_slice().iter().map(|(_, s)| s.mem_usage() as u64).sum::<u64>()
+                + regions.iter().map(|r| (size_of::<ZRegion>() + r.data.len()) as u64).sum::<u64>();
+            let limit = self.budget.map_or(Z_BATCH, |b| b.saturating_sub(held).min(Z_BATCH));
+
+            let mut next = 0;
+            while next < regions.len() {
+                let mut batch = Vec::new();
+                let mut bytes = 0;
+                {
+                    let mut rd = ObjectReader::open(&*store, root)?;
+                    // хотя бы одна зона за пачку, иначе импорт не продвинется
+                    while next < regions.len() && (batch.is_empty() || bytes < limit) {
+                        let r = &regions[next];
+                        let (base, l0s) = rd.l0_span(r.offset, r.len)?;
+                        let (first_l0, last_l0) = (l0s[0], l0s[l0s.len() - 1]);
+                        let mut meta = encode_z_region(r);
+                        meta.extend_from_slice(&encode_z_span(&ZSpan { base, l0s }));
+                        bytes += (size_of::<ZPayload>() + meta.len()) as u64;
+                        batch.push((a.region_key(r), ZPayload { first_l0, last_l0, z_type: a.z_type(), meta }));
+                        next += 1;
+                    }
+                }
+                for (key, z) in batch {
+                    let id = match store.find_hash(&hash_z(&z))? {
+                        Some(id) => id,
+                        None => {
+                            stats.blocks_written += 1;
+                            store.put_z(&z)?
+                        }
+                    };
+                    stats.zblocks += 1;
+                    by_type.entry(z.z_type).or_default().push((id, key));
+                }
+            }
+        }
+
+        let mut out = Vec::with_capacity(by_type.len());
+        for (z_type, mut zids) in by_type {
+            let ends = (1..=zids.len() as u64).collect();
+            let index = put_index(store, zids.iter().map(|&(id, _)| id).collect(), ends, stats)?;
+            let by_key = if zids.iter().any(|(_, k)| k.is_some()) {
+                // зоны без ключа — в начале, под ключом 0
+                zids.sort_by_key(|&(_, k)| k.unwrap_or(0));
+                let (ids, keys) = zids.into_iter().map(|(id, k)| (id, k.unwrap_or(0))).unzip();
+                Some(put_index(store, ids, keys, stats)?)
+            } else {
+                None
+            };
+            out.push(ZIndexRef { z_type, index, by_key });
+        }
+        Ok(out)
+    }
+}
+
+/// Записать Indexed-узел индекса Z-блоков (dedup по hash).
+fn put_index<S: BlockStore + ?Sized>(
+    store: &mut S,
+    blocks: Vec<BlockId>,
+    ends: Vec<u64>,
+    stats: &mut ImportStats,
+) -> StoreResult<BlockId> {
+    let recipe = MultiRecipe::Indexed { blocks: blocks.into(), ends };
+    Ok(match store.find_hash(&hash_multi(&recipe)?)? {
+        Some(id) => id,
+        None => {
+            stats.blocks_written += 1;
+            store.put_multi(&recipe)?
+        }
+    })
+}
+
+/// ZSource поверх записанного дерева данных объекта.
+struct TreeSource<'a, S: BlockStore + ?Sized> {
+    rd:  RefCell<ObjectReader<'a, S>>,
+    len: u64,
+}
+
+impl<S: BlockStore + ?Sized> ZSource for TreeSource<'_, S> {
+    fn len(&self) -> u64 {
+        self.len
+    }
+
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> StoreResult<()> {
+        let end = offset.checked_add(buf.len() as u64).filter(|&e| e <= self.len());
+        if end.is_none() {
+            return Err(StoreError::Corrupt(format!(
+                "read {}+{} past end of {} bytes",
+                offset, buf.len(), self.len()
+            )));
+        }
+        let mut rd = self.rd.borrow_mut();
+        rd.seek(SeekFrom::Start(offset))?;
+        rd.read_exact(buf)?;
+        Ok(())
+    }
+}
+
+/// Object файла: сам `obj` или файл его Z-node; None — не файл.
+fn file_object<S: BlockStore + ?Sized>(store: &S, obj: ObjectId) -> StoreResult<Option<Vec<u8>>> {
+    let mut o = store.get_object(obj)?;
+    if o.obj_type == OBJ_TYPE_ZNODE {
+        let BlockRef::Object(file) = o.root else {
+            return Err(StoreError::Corrupt(format!("Z-node {} root is not Object", obj)));
+        };
+        o = store.get_object(file)?;
+    }
+    Ok((o.obj_type == OBJ_TYPE_FILE).then_some(o.meta))
+}
+
+/// Индекс Z-блоков `z_type` файла: (ids в порядке индекса, `ends`).
+fn read_index<S: BlockStore + ?Sized>(store: &S, id: BlockId) -> StoreResult<(Vec<BlockId>, Vec<u64>)> {
+    match store.get_multi(id)?.recipe {
+        MultiRecipe::Indexed { blocks, ends } => Ok((blocks.into_vec(), ends)),
+        _ => Err(StoreError::Corrupt(format!("Z index {} is not Indexed", id))),
+    }
+}
+
+/// Z-блоки, покрывающие объект `obj` (файл или его Z-node), в порядке
+/// записи; `z_type` — только данного типа (читаются только его Z-блоки).
+pub fn zblocks_of<S: BlockStore + ?Sized>(store: &S, obj: ObjectId, z_type: Option<u32>) -> StoreResult<Vec<ZBlock>> {
+    let Some(meta) = file_object(store, obj)? else {
+        return Ok(Vec::new());
+    };
+    let mut out = Vec::new();
+    for r in decode_z_index(&meta).unwrap_or_default() {
+        if z_type.is_none_or(|t| t == r.z_type) {
+            for id in read_index(store, r.index)?.0 {
+                out.push(store.get_z(id)?);
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// i-я зона `z_type` объекта `obj`: читаются индекс и один Z-блок.
+pub fn zblock_at<S: BlockStore + ?Sized>(store: &S, obj: ObjectId, z_type: u32, i: u64) -> StoreResult<Option<ZBlock>> {
+    let Some(meta) = file_object(store, obj)? else {
+        return Ok(None);
+    };
+    let Some(r) = decode_z_index(&meta).unwrap_or_default().into_iter().find(|r| r.z_type == z_type) else {
+        return Ok(None);
+    };
+    let ids = read_index(store, r.index)?.0;
+    let Some(&id) = usize::try_from(i).ok().and_then(|i| ids.get(i)) else {
+        return Ok(None);
+    };
+    Ok(Some(store.get_z(id)?))
+}
+
+/// Зоны `z_type` объекта `obj` с ключом `key` (`z_key`) в порядке зон.
+///
+/// Это кандидаты: ключ — префикс hash, имя зоны сверяет вызывающий.
+/// Без индекса по ключу (анализатор ключей не даёт) — пусто.
+pub fn zblocks_by_key<S: BlockStore + ?Sized>(store: &S, obj: ObjectId, z_type: u32, key: u64) -> StoreResult<Vec<ZBlock>> {
+    let Some(meta) = file_object(store, obj)? else {
+        return Ok(Vec::new());
+    };
+    match decode_z_index(&meta).unwrap_or_default().into_iter().find(|r| r.z_type == z_type) {
+        Some(ZIndexRef { by_key: Some(idx), .. }) => {
+            let (ids, keys) = read_index(store, idx)?;
+            let from = keys.partition_point(|&k| k < key);
+            let to = keys.partition_point(|&k| k <= key);
+            ids.get(from..to).unwrap_or_default().iter().map(|&id| store.get_z(id)).collect()
+        }
+        _ => Ok(Vec::new()),
+    }
+}
+
+/// Скопировать байты объекта `[offset, offset + len)` из L0 зоны `z` в `out`.
+///
+/// Читаются только L0 из `ZSpan` зоны, пересекающие диапазон; дерево
+/// данных объекта не нужно. Диапазон должен лежать внутри span.
+pub fn copy_zblock_range<S: BlockStore + ?Sized, W: Write>(
+    store: &S,
+    z: &ZBlock,
+    offset: u64,
+    len: u64,
+    out: &mut W,
+) -> StoreResult<u64> {
+    let span = decode_z_span(&z.meta)
+        .ok_or(StoreError::Corrupt(format!("Z block {} has no L0 span", z.id)))?;
+    let outside = || StoreError::Corrupt(format!("range {}+{} is outside of Z block {} span", offset, len, z.id));
+    if offset < span.base {
+        return Err(outside());
+    }
+    let end = offset.checked_add(len).ok_or_else(outside)?;
+    let mut pos = span.base;
+    let mut copied = 0;
+    for id in span.l0s {
+        if pos >= end {
+            break;
+        }
+        // длина — из заголовка, данные читаем только у L0, попавших в диапазон
+        let h = store.get_header(id)?;
+        let size = h
+            .l0_raw_len()
+            .ok_or(StoreError::Corrupt(format!("Z block {} span has non-L0 block {}", z.id, id)))?;
+        let next = pos.checked_add(size).ok_or_else(outside)?;
+        if next > offset {
+            let (_, _, body) = store.get_typed(id)?;
+            let BlockBody::L0(raw) = body else {
+                return Err(StoreError::Corrupt(format!("block {} is not L0", id)));
+            };
+            let from = offset.saturating_sub(pos) as usize;
+            let to = (end - pos).min(size) as usize;
+            let part = raw
+                .get(from..to)
+                .ok_or(StoreError::Corrupt(format!("L0 {} is shorter than its header", id)))?;
+            out.write_all(part)?;
+            copied += (to - from) as u64;
+        }
+        pos = next;
+    }
+    if copied != len {
+        return Err(outside());
+    }
+    Ok(copied)
+}
@@ -0,0 +1,290 @@
+use std::io::Write;
+
+use crate::types::{BlockId, ObjectId, Z_TYPE_ELF};
+use crate::block::ZBlock;
+use crate::codec::{ZRegion, decode_z_region, tlv, tlv_iter, u32_decode, u32_encode, u64_decode, u64_encode};
+use crate::store::blockstore::{BlockStore, StoreError, StoreResult};
+use crate::store::typed::TypedStore;
+use crate::analysis::analyzer::{ZAnalyzer, ZScan, ZSource, zblocks_of, copy_zblock_range};
+
+/// Секция без данных в файле (.bss и т.п.).
+const SHT_NOBITS: u32 = 8;
+/// Больше этого .shstrtab не читаем: имена секций останутся пустыми.
+const SHSTRTAB_LIMIT: u64 = 16 * 1024 * 1024;
+
+/// Секция ELF, восстановленная по её Z-блоку.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ElfSection {
+    /// Номер в таблице секций.
+    pub index:   u32,
+    pub name:    String,
+    pub sh_type: u32,
+    /// sh_flags (SHF_WRITE = 1, SHF_ALLOC = 2, SHF_EXECINSTR = 4, ...).
+    pub flags:   u64,
+    pub addr:    u64,
+    /// Смещение и размер данных секции в объекте.
+    pub offset:  u64,
+    pub size:    u64,
+    /// Z-блок секции.
+    pub zblock:  BlockId,
+}
+
+/// Анализатор ELF32/ELF64 (LE/BE): Z-блок на каждую секцию с данными в файле.
+///
+/// Таблица секций обычно в конце файла, поэтому разбор идёт в `finish`
+/// через `ZSource`; при проходе запоминается только ELF-заголовок.
+/// Битый или усечённый файл — просто без зон.
+pub struct ElfAnalyzer;
+
+impl ZAnalyzer for ElfAnalyzer {
+    fn z_type(&self) -> u32 {
+        Z_TYPE_ELF
+    }
+
+    fn name(&self) -> &str {
+        "elf"
+    }
+
+    fn scan(&self) -> Box<dyn ZScan> {
+        Box::<ElfScan>::default()
+    }
+}
+
+// Данные секции (ZRegion::data) <-> TLV
+//
+//   0x80: name
+//   0x81: index:u32
+//   0x82: sh_type:u32
+//   0x83: flags:u64
+//   0x84: addr:u64
+
+fn encode_section(s: &ElfSection) -> Vec<u8> {
+    let mut v = Vec::new();
+    v.extend_from_slice(&tlv(0x80, s.name.as_bytes()));
+    v.extend_from_slice(&tlv(0x81, &u32_encode(s.index)));
+    v.extend_from_slice(&tlv(0x82, &u32_encode(s.sh_type)));
+    v.extend_from_slice(&tlv(0x83, &u64_encode(s.flags)));
+    v.extend_from_slice(&tlv(0x84, &u64_encode(s.addr)));
+    v
+}
+
+fn decode_section(z: &ZBlock) -> Option<ElfSection> {
+    let region = decode_z_region(&z.meta)?;
+    let mut name = String::new();
+    let mut index = None;
+    let mut sh_type = None;
+    let mut flags = 0;
+    let mut addr = 0;
+    for (tag, val) in tlv_iter(&region.data).ok()? {
+        match tag {
+            0x80 => name = String::from_utf8(val).ok()?,
+            0x81 => index = Some(u32_decode(&val).ok()?),
+            0x82 => sh_type = Some(u32_decode(&val).ok()?),
+            0x83 => flags = u64_decode(&val).ok()?,
+            0x84 => addr = u64_decode(&val).ok()?,
+            _ => {}
+        }
+    }
+    Some(ElfSection {
+        index: index?,
+        name,
+        sh_type: sh_type?,
+        flags,
+        addr,
+        offset: region.offset,
+        size: region.len,
+        zblock: z.id,
+    })
+}
+
+/// Секции ELF-файла `obj` (файл или его Z-node) в порядке таблицы.
+/// Не ELF — пустой список.
+pub fn elf_sections<S: BlockStore + ?Sized>(store: &S, obj: ObjectId) -> StoreResult<Vec<ElfSection>> {
+    zblocks_of(store, obj, Some(Z_TYPE_ELF))?
+        .iter()
+        .map(|z| decode_section(z).ok_or(StoreError::Corrupt(format!("ELF Z block {} has bad meta", z.id))))
+        .collect()
+}
+
+/// Секция по имени (первая из одноимённых).
+pub fn elf_section<S: BlockStore + ?Sized>(store: &S, obj: ObjectId, name: &str) -> StoreResult<Option<ElfSection>> {
+    Ok(elf_sections(store, obj)?.into_iter().find(|s| s.name == name))
+}
+
+/// Записать данные секции в `out`: читаются только L0 её Z-блока.
+pub fn elf_extract<S: BlockStore + ?Sized, W: Write>(store: &S, s: &ElfSection, out: &mut W) -> StoreResult<u64> {
+    let z = store.get_z(s.zblock)?;
+    copy_zblock_range(store, &z, s.offset, s.size, out)
+}
+
+#[derive(Default)]
+struct ElfScan {
+    head: Vec<u8>,
+}
+
+impl ZScan for ElfScan {
+    fn feed(&mut self, data: &[u8]) {
+        let n = (64 - self.head.len()).min(data.len());
+        self.head.extend_from_slice(&data[..n]);
+    }
+
+    fn finish(self: Box<Self>, src: &dyn ZSource) -> StoreResult<Vec<ZRegion>> {
+        let Some(elf) = Elf::parse(&self.head) else {
+            return Ok(Vec::new());
+        };
+        elf.sections(src)
+    }
+}
+
+/// Поля ELF-заголовка, нужные для таблицы секций.
+struct Elf {
+    is64:      bool,
+    be:        bool,
+    shoff:     u64,
+    shentsize: u64,
+    shnum:     u64,
+    shstrndx:  u64,
+}
+
+/// Заголовок секции (общие поля ELF32/ELF64).
+struct Shdr {
+    name:    u32,
+    sh_type: u32,
+    flags:   u64,
+    addr:    u64,
+    offset:  u64,
+    size:    u64,
+    link:    u32,
+}
+
+impl Elf {
+    fn parse(h: &[u8]) -> Option<Self> {
+        if h.len() < 52 || &h[..4] != b"\x7fELF" {
+            return None;
+        }
+        let is64 = match h[4] {
+            1 => false,
+            2 => true,
+            _ => return None,
+        };
+        let be = match h[5] {
+            1 => false,
+            2 => true,
+            _ => return None,
+        };
+        let e = Elf { is64, be, shoff: 0, shentsize: 0, shnum: 0, shstrndx: 0 };
+        let (shoff, rest) = if is64 {
+            if h.len() < 64 {
+                return None;
+            }
+            (e.u64(&h[0x28..]), 0x3a)
+        } else {
+            (e.u32(&h[0x20..]) as u64, 0x2e)
+        };
+        Some(Elf {
+            shoff,
+            shentsize: e.u16(&h[rest..]) as u64,
+            shnum:     e.u16(&h[rest + 2..]) as u64,
+            shstrndx:  e.u16(&h[rest + 4..]) as u64,
+            ..e
+        })
+    }
+
+    fn u16(&self, b: &[u8]) -> u16 {
+        let v = [b[0], b[1]];
+        if self.be { u16::from_be_bytes(v) } else { u16::from_le_bytes(v) }
+    }
+
+    fn u32(&self, b: &[u8]) -> u32 {
+        let v = [b[0], b[1], b[2], b[3]];
+        if self.be { u32::from_be_bytes(v) } else { u32::from_le_bytes(v) }
+    }
+
+    fn u64(&self, b: &[u8]) -> u64 {
+        let v = [b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]];
+        if self.be { u64::from_be_bytes(v) } else { u64::from_le_bytes(v) }
+    }
+
+    fn shdr(&self, b: &[u8]) -> Shdr {
+        if self.is64 {
+            Shdr {
+                name:    self.u32(&b[0..]),
+                sh_type: self.u32(&b[4..]),
+                flags:   self.u64(&b[8..]),
+                addr:    self.u64(&b[16..]),
+                offset:  self.u64(&b[24..]),
+                size:    self.u64(&b[32..]),
+                link:    self.u32(&b[40..]),
+            }
+        } else {
+            Shdr {
+                name:    self.u32(&b[0..]),
+                sh_type: self.u32(&b[4..]),
+                flags:   self.u32(&b[8..]) as u64,
+                addr:    self.u32(&b[12..]) as u64,
+                offset:  self.u32(&b[16..]) as u64,
+                size:    self.u32(&b[20..]) as u64,
+                link:    self.u32(&b[24..]),
+            }
+        }
+    }
+
+    fn sections(&self, src: &dyn ZSource) -> StoreResult<Vec<ZRegion>> {
+        let len = src.len();
+        let min_ent = if self.is64 { 64 } else { 40 };
+        if self.shoff == 0 || self.shentsize < min_ent || self.shoff.saturating_add(self.shentsize) > len {
+            return Ok(Vec::new());
+        }
+        let mut ent = vec![0u8; self.shentsize as usize];
+
+        // shnum/shstrndx за пределами 16 бит лежат в секции 0
+        src.read_at(self.shoff, &mut ent)?;
+        let sh0 = self.shdr(&ent);
+        let shnum = if self.shnum == 0 { sh0.size } else { self.shnum };
+        let shstrndx = if self.shstrndx == 0xffff { sh0.link as u64 } else { self.shstrndx };
+        let table = shnum.checked_mul(self.shentsize).and_then(|t| t.checked_add(self.shoff));
+        if table.is_none_or(|end| end > len) {
+            return Ok(Vec::new());
+        }
+
+        let mut shdrs = Vec::with_capacity(shnum as usize);
+        let mut table = vec![0u8; (shnum * self.shentsize) as usize];
+        src.read_at(self.shoff, &mut table)?;
+        for e in table.chunks(self.shentsize as usize) {
+            shdrs.push(self.shdr(e));
+        }
+
+        let in_file = |s: &Shdr| s.sh_type != SHT_NOBITS && s.size > 0 && s.offset.saturating_add(s.size) <= len;
+        let mut names = Vec::new();
+        if let Some(st) = shdrs.get(shstrndx as usize).filter(|s| in_file(s) && s.size <= SHSTRTAB_LIMIT) {
+            names = vec![0u8; st.size as usize];
+            src.read_at(st.offset, &mut names)?;
+        }
+
+        let mut out = Vec::new();
+        for (i, s) in shdrs.iter().enumerate() {
+            if i == 0 || !in_file(s) {
+                continue;
+            }
+            let name = names
+                .get(s.name as usize..)
+                .map(|b| {
+                    let end = b.iter().position(|&c| c == 0).unwrap_or(b.len());
+                    String::from_utf8_lossy(&b[..end]).into_owned()
+                })
+                .unwrap_or_default();
+            let sec = ElfSection {
+                index: i as u32,
+                name,
+                sh_type: s.sh_type,
+                flags: s.flags,
+                addr: s.addr,
+                offset: s.offset,
+                size: s.size,
+                zblock: 0,
+            };
+            out.push(ZRegion { offset: s.offset, len: s.size, data: encode_section(&sec) });
+        }
+        Ok(out)
+    }
+}
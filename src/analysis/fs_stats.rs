@@ -0,0 +1,68 @@
+use crate::types::{BlockRef, ObjectId, OBJ_TYPE_DIR};
+use crate::codec::{DirEntryKind, decode_dir_meta};
+use crate::config::QuarxConfig;
+use crate::store::blockstore::{BlockStore, StoreError, StoreResult};
+use crate::store::decode::BlockBody;
+use crate::reader::ObjectReader;
+use crate::analysis::znode::znode_meta_of;
+
+/// Сводка по дереву каталогов (или одному файлу).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FsStats {
+    pub files:    u64,
+    pub dirs:     u64,
+    pub symlinks: u64,
+    /// Сумма размеров файлов, которые удалось оценить.
+    pub bytes:    u64,
+    /// Файлов, размер которых взят из Z-node (дёшево).
+    pub znode_files:    u64,
+    /// Файлов без Z-node, размер которых получен чтением payload.
+    pub fallback_files: u64,
+    /// Файлов без Z-node при выключенном fallback: в `bytes` не вошли.
+    pub unsized_files:  u64,
+}
+
+/// Посчитать fs-stats от `root` (каталог OBJ_TYPE_DIR или файл).
+///
+/// Размер файла берётся из его Z-node. Без Z-node — только при
+/// `analysis_fs_stats_fallback`: тогда читаются рецепты/заголовки
+/// дерева данных (`ObjectReader`), иначе файл учитывается как unsized.
+pub fn fs_stats<S: BlockStore + ?Sized>(store: &S, root: ObjectId, cfg: &QuarxConfig) -> StoreResult<FsStats> {
+    let mut st = FsStats::default();
+    walk(store, root, cfg, &mut st)?;
+    Ok(st)
+}
+
+fn walk<S: BlockStore + ?Sized>(store: &S, id: ObjectId, cfg: &QuarxConfig, st: &mut FsStats) -> StoreResult<()> {
+    let body = store.get_typed(id)?.2;
+    let mut znode = None;
+    if let BlockBody::Object(o) = &body {
+        if o.obj_type == OBJ_TYPE_DIR {
+            let entries = decode_dir_meta(&o.meta)
+                .ok_or(StoreError::Corrupt(format!("directory object {} has bad entries", id)))?;
+            st.dirs += 1;
+            for e in entries {
+                match (e.kind, e.child) {
+                    (DirEntryKind::Symlink, _) => st.symlinks += 1,
+                    (_, BlockRef::L0(c) | BlockRef::Multi(c) | BlockRef::Z(c) | BlockRef::Object(c)) => {
+                        walk(store, c, cfg, st)?
+                    }
+                }
+            }
+            return Ok(());
+        }
+        znode = znode_meta_of(id, o)?;
+    }
+
+    st.files += 1;
+    if let Some(m) = znode {
+        st.bytes += m.size_bytes;
+        st.znode_files += 1;
+    } else if cfg.analysis_fs_stats_fallback {
+        st.bytes += ObjectReader::open(store, id)?.len()?;
+        st.fallback_files += 1;
+    } else {
+        st.unsized_files += 1;
+    }
+    Ok(())
+}
@@ -0,0 +1,365 @@
+use std::io::Write;
+
+use crate::types::{BlockId, ObjectId, Z_TYPE_JSON};
+use crate::block::ZBlock;
+use crate::codec::{ZRegion, decode_z_region, tlv, tlv_iter, u64_decode, u64_encode};
+use crate::store::blockstore::{BlockStore, StoreError, StoreResult};
+use crate::store::typed::TypedStore;
+use crate::analysis::analyzer::{ZAnalyzer, ZScan, ZSource, zblocks_of, zblock_at, zblocks_by_key, z_key, copy_zblock_range};
+
+/// Длиннее ключи обрезаются (в meta и при поиске).
+const KEY_LIMIT: usize = 4096;
+
+/// Где лежит зона JSON.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JsonPath {
+    /// Член верхнего объекта документа.
+    Key(String),
+    /// Элемент верхнего массива документа или запись NDJSON.
+    Index(u64),
+}
+
+/// Зона JSON, восстановленная по её Z-блоку.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonSpan {
+    pub path:   JsonPath,
+    /// true — запись NDJSON, false — член/элемент одного документа.
+    pub record: bool,
+    /// Для Key — от открывающей кавычки ключа до конца значения;
+    /// для Index — само значение (без перевода строки / запятой).
+    pub offset: u64,
+    pub len:    u64,
+    /// Z-блок зоны.
+    pub zblock: BlockId,
+}
+
+/// Анализатор JSON/NDJSON.
+///
+/// Одно значение верхнего уровня (объект или массив) — документ: зона на
+/// каждый член/элемент верхнего уровня. Несколько значений подряд — NDJSON:
+/// зона на каждую запись. Скаляры верхнего уровня и прочий текст — без зон;
+/// разбор останавливается на первом мусоре между значениями.
+pub struct JsonAnalyzer;
+
+impl ZAnalyzer for JsonAnalyzer {
+    fn z_type(&self) -> u32 {
+        Z_TYPE_JSON
+    }
+
+    fn name(&self) -> &str {
+        "json"
+    }
+
+    fn scan(&self) -> Box<dyn ZScan> {
+        Box::<JsonScan>::default()
+    }
+
+    fn region_key(&self, r: &ZRegion) -> Option<u64> {
+        match decode_path(&r.data)?.0 {
+            JsonPath::Key(k) => Some(z_key(k.as_bytes())),
+            JsonPath::Index(_) => None,
+        }
+    }
+}
+
+// Данные зоны (ZRegion::data) <-> TLV
+//
+//   0x90: key (UTF-8, без кавычек и escape)   | одно из двух
+//   0x91: index:u64                           |
+//   0x92: record:u8 (1 — запись NDJSON)
+
+fn encode_span(path: &JsonPath, record: bool) -> Vec<u8> {
+    let mut v = Vec::new();
+    match path {
+        JsonPath::Key(k) => v.extend_from_slice(&tlv(0x90, k.as_bytes())),
+        JsonPath::Index(i) => v.extend_from_slice(&tlv(0x91, &u64_encode(*i))),
+    }
+    v.extend_from_slice(&tlv(0x92, &[record as u8]));
+    v
+}
+
+fn decode_path(data: &[u8]) -> Option<(JsonPath, bool)> {
+    let mut path = None;
+    let mut record = false;
+    for (tag, val) in tlv_iter(data).ok()? {
+        match tag {
+            0x90 => path = Some(JsonPath::Key(String::from_utf8(val).ok()?)),
+            0x91 => path = Some(JsonPath::Index(u64_decode(&val).ok()?)),
+            0x92 => record = val.first() == Some(&1),
+            _ => {}
+        }
+    }
+    Some((path?, record))
+}
+
+fn decode_span(z: &ZBlock) -> Option<JsonSpan> {
+    let region = decode_z_region(&z.meta)?;
+    let (path, record) = decode_path(&region.data)?;
+    Some(JsonSpan {
+        path,
+        record,
+        offset: region.offset,
+        len: region.len,
+        zblock: z.id,
+    })
+}
+
+/// Зоны JSON объекта `obj` (файл или его Z-node) в порядке текста.
+pub fn json_spans<S: BlockStore + ?Sized>(store: &S, obj: ObjectId) -> StoreResult<Vec<JsonSpan>> {
+    zblocks_of(store, obj, Some(Z_TYPE_JSON))?.iter().map(span_of).collect()
+}
+
+fn span_of(z: &ZBlock) -> StoreResult<JsonSpan> {
+    decode_span(z).ok_or(StoreError::Corrupt(format!("JSON Z block {} has bad meta", z.id)))
+}
+
+/// Член верхнего объекта документа по ключу (последний из одноимённых, как у парсеров).
+///
+/// Читаются индекс по ключу и Z-блоки с тем же `z_key`, не все зоны.
+pub fn json_key<S: BlockStore + ?Sized>(store: &S, obj: ObjectId, key: &str) -> StoreResult<Option<JsonSpan>> {
+    let want = JsonPath::Key(key.to_owned());
+    for z in zblocks_by_key(store, obj, Z_TYPE_JSON, z_key(key.as_bytes()))?.iter().rev() {
+        let s = span_of(z)?;
+        if s.path == want {
+            return Ok(Some(s));
+        }
+    }
+    Ok(None)
+}
+
+/// Запись NDJSON или элемент верхнего массива по номеру.
+///
+/// Элементы нумеруются подряд, поэтому номер — позиция зоны в индексе:
+/// читаются индекс и один Z-блок.
+pub fn json_record<S: BlockStore + ?Sized>(store: &S, obj: ObjectId, index: u64) -> StoreResult<Option<JsonSpan>> {
+    let Some(z) = zblock_at(store, obj, Z_TYPE_JSON, index)? else {
+        return Ok(None);
+    };
+    let s = span_of(&z)?;
+    Ok((s.path == JsonPath::Index(index)).then_some(s))
+}
+
+/// Записать байты зоны в `out`: читаются только L0 её Z-блока.
+pub fn json_extract<S: BlockStore + ?Sized, W: Write>(store: &S, s: &JsonSpan, out: &mut W) -> StoreResult<u64> {
+    let z = store.get_z(s.zblock)?;
+    copy_zblock_range(store, &z, s.offset, s.len, out)
+}
+
+/// Член/элемент верхнего уровня текущего значения.
+struct Member {
+    path:  JsonPath,
+    start: u64,
+    end:   u64,
+}
+
+#[derive(Default)]
+struct JsonScan {
+    pos:   u64,
+    depth: u32,
+    in_str: bool,
+    esc:   bool,
+    /// Текущее значение верхнего уровня — объект ('{') или массив.
+    object: bool,
+    /// Начало текущего значения верхнего уровня.
+    value_start: u64,
+    /// Конец последнего не-пробельного байта внутри значения.
+    last_end: u64,
+
+    /// Объект: ждём ключ (после '{' или ',').
+    want_key: bool,
+    /// Идёт чтение ключа члена.
+    in_key: bool,
+    key:    Vec<u8>,
+    /// Начало текущего члена/элемента.
+    member: Option<(JsonPath, u64)>,
+    next_index: u64,
+    /// Члены/элементы первого значения (режим документа).
+    members: Vec<Member>,
+    /// Байт ключей в `members` (для `mem_usage`).
+    key_bytes: usize,
+
+    /// Законченные значения верхнего уровня (записи NDJSON).
+    records: Vec<(u64, u64)>,
+    /// Разбор остановлен (мусор).
+    done: bool,
+}
+
+impl ZScan for JsonScan {
+    fn feed(&mut self, data: &[u8]) {
+        for &b in data {
+            if self.done {
+                return;
+            }
+            self.byte(b);
+            self.pos += 1;
+        }
+    }
+
+    fn finish(self: Box<Self>, _src: &dyn ZSource) -> StoreResult<Vec<ZRegion>> {
+        let region = |path: &JsonPath, record, start: u64, end: u64| ZRegion {
+            offset: start,
+            len:    end - start,
+            data:   encode_span(path, record),
+        };
+        Ok(match self.records.len() {
+            0 => Vec::new(),
+            1 => self.members.iter().map(|m| region(&m.path, false, m.start, m.end)).collect(),
+            _ => self
+                .records
+                .iter()
+                .enumerate()
+                .map(|(i, &(s, e))| region(&JsonPath::Index(i as u64), true, s, e))
+                .collect(),
+        })
+    }
+
+    fn mem_usage(&self) -> usize {
+        self.key.capacity()
+            + self.members.capacity() * std::mem::size_of::<Member>()
+            + self.key_bytes
+            + self.records.capacity() * std::mem::size_of::<(u64, u64)>()
+    }
+}
+
+impl JsonScan {
+    fn byte(&mut self, b: u8) {
+        let ws = matches!(b, b' ' | b'\t' | b'\n' | b'\r');
+
+        if self.depth == 0 {
+            match b {
+                _ if ws => {}
+                b'{' | b'[' => {
+                    self.depth = 1;
+                    self.object = b == b'{';
+                    self.want_key = self.object;
+                    self.value_start = self.pos;
+                    self.next_index = 0;
+                }
+                _ => self.done = true,
+            }
+            return;
+        }
+
+        if self.in_str {
+            self.last_end = self.pos + 1;
+            if self.esc {
+                self.esc = false;
+            } else if b == b'\\' {
+                self.esc = true;
+            } else if b == b'"' {
+                self.in_str = false;
+                if self.in_key {
+                    self.in_key = false;
+                    let key = unescape(&self.key);
+                    let start = self.member.take().map_or(self.pos, |(_, s)| s);
+                    self.member = Some((JsonPath::Key(key), start));
+                    return;
+                }
+            }
+            if self.in_key && self.key.len() < KEY_LIMIT {
+                self.key.push(b);
+            }
+            return;
+        }
+        if ws {
+            return;
+        }
+
+        let top = self.depth == 1;
+        // начало элемента верхнего массива
+        if top && !self.object && self.member.is_none() && !matches!(b, b',' | b']') {
+            self.member = Some((JsonPath::Index(self.next_index), self.pos));
+            self.next_index += 1;
+        }
+
+        match b {
+            b'"' => {
+                self.in_str = true;
+                if top && self.object && self.want_key {
+                    self.want_key = false;
+                    self.in_key = true;
+                    self.key.clear();
+                    self.member = Some((JsonPath::Key(String::new()), self.pos));
+                }
+            }
+            b'{' | b'[' => self.depth += 1,
+            b'}' | b']' if top => {
+                self.close_member();
+                self.depth = 0;
+                self.records.push((self.value_start, self.pos + 1));
+                return;
+            }
+            b'}' | b']' => self.depth -= 1,
+            b',' if top => {
+                self.close_member();
+                self.want_key = self.object;
+                return;
+            }
+            _ => {}
+        }
+        self.last_end = self.pos + 1;
+    }
+
+    /// Закрыть член/элемент на последнем не-пробельном байте.
+    fn close_member(&mut self) {
+        let Some((path, start)) = self.member.take() else {
+            return;
+        };
+        // члены нужны только для первого значения (документ)
+        if self.records.is_empty() {
+            if let JsonPath::Key(k) = &path {
+                self.key_bytes += k.len();
+            }
+            self.members.push(Member { path, start, end: self.last_end });
+        }
+    }
+}
+
+/// Ключ JSON без escape; битые последовательности — как есть.
+fn unescape(raw: &[u8]) -> String {
+    let s = String::from_utf8_lossy(raw);
+    if !s.contains('\\') {
+        return s.into_owned();
+    }
+    let mut out = String::with_capacity(s.len());
+    let mut it = s.chars();
+    while let Some(c) = it.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match it.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('r') => out.push('\r'),
+            Some('b') => out.push('\u{8}'),
+            Some('f') => out.push('\u{c}'),
+            Some('u') => {
+                let hex: String = it.by_ref().take(4).collect();
+                let Ok(hi) = u32::from_str_radix(&hex, 16) else {
+                    out.push_str("\\u");
+                    out.push_str(&hex);
+                    continue;
+                };
+                let mut cp = hi;
+                // суррогатная пара: \uD83D\uDE00
+                if (0xD800..0xDC00).contains(&hi) {
+                    let rest = it.as_str();
+                    if let Some(lo) = rest
+                        .strip_prefix("\\u")
+                        .and_then(|r| r.get(..4))
+                        .and_then(|h| u32::from_str_radix(h, 16).ok())
+                        .filter(|lo| (0xDC00..0xE000).contains(lo))
+                    {
+                        cp = 0x10000 + ((hi - 0xD800) << 10) + (lo - 0xDC00);
+                        it.nth(5);
+                    }
+                }
+                out.push(char::from_u32(cp).unwrap_or('\u{FFFD}'));
+            }
+            Some(c) => out.push(c),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
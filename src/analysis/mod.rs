@@ -0,0 +1,13 @@
+pub mod znode;
+pub mod fs_stats;
+pub mod analyzer;
+pub mod tar;
+pub mod elf;
+pub mod json;
+
+pub use znode::*;
+pub use fs_stats::*;
+pub use analyzer::*;
+pub use tar::*;
+pub use elf::*;
+pub use json::*;
@@ -0,0 +1,401 @@
+use std::io::Write;
+
+use crate::types::{BlockId, ObjectId, Z_TYPE_TAR};
+use crate::block::ZBlock;
+use crate::codec::{ZRegion, decode_z_region, tlv, tlv_iter, u32_decode, u32_encode, u64_decode, u64_encode};
+use crate::store::blockstore::{BlockStore, StoreError, StoreResult};
+use crate::store::typed::TypedStore;
+use crate::analysis::analyzer::{ZAnalyzer, ZScan, ZSource, zblocks_of, copy_zblock_range};
+
+/// Размер записи TAR.
+const RECORD: u64 = 512;
+/// Сколько данных pax/GNU long name держим в памяти; хвост отбрасывается.
+const EXT_LIMIT: usize = 64 * 1024;
+
+/// Тип члена архива (typeflag).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TarKind {
+    File,
+    HardLink,
+    Symlink,
+    Dir,
+    Other(u8),
+}
+
+impl TarKind {
+    fn from_flag(f: u8) -> Self {
+        match f {
+            b'0' | 0 | b'7' => TarKind::File,
+            b'1' => TarKind::HardLink,
+            b'2' => TarKind::Symlink,
+            b'5' => TarKind::Dir,
+            f => TarKind::Other(f),
+        }
+    }
+
+    fn flag(self) -> u8 {
+        match self {
+            TarKind::File => b'0',
+            TarKind::HardLink => b'1',
+            TarKind::Symlink => b'2',
+            TarKind::Dir => b'5',
+            TarKind::Other(f) => f,
+        }
+    }
+
+    /// Есть ли у члена данные после заголовка.
+    fn has_data(self) -> bool {
+        !matches!(self, TarKind::HardLink | TarKind::Symlink | TarKind::Dir)
+    }
+}
+
+/// Член TAR-архива, восстановленный по его Z-блоку.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TarMember {
+    pub path:  String,
+    pub kind:  TarKind,
+    pub size:  u64,
+    pub mode:  u32,
+    pub mtime: i64,
+    /// Цель ссылки (Symlink/HardLink), иначе пусто.
+    pub link:  String,
+    /// Смещение данных члена в объекте архива.
+    pub data_offset: u64,
+    /// Z-блок члена.
+    pub zblock: BlockId,
+}
+
+/// Анализатор ustar/pax-архивов: Z-блок на каждый член (заголовки,
+/// включая pax/GNU long name, + данные с выравниванием до 512).
+///
+/// Архив распознаётся по первому заголовку (magic "ustar" + checksum);
+/// разбор останавливается на нулевой записи или первом битом заголовке.
+pub struct TarAnalyzer;
+
+impl ZAnalyzer for TarAnalyzer {
+    fn z_type(&self) -> u32 {
+        Z_TYPE_TAR
+    }
+
+    fn name(&self) -> &str {
+        "tar"
+    }
+
+    fn scan(&self) -> Box<dyn ZScan> {
+        Box::<TarScan>::default()
+    }
+}
+
+// Данные члена (ZRegion::data) <-> TLV
+//
+//   0x70: path
+//   0x71: typeflag:u8
+//   0x72: size:u64
+//   0x73: mode:u32
+//   0x74: mtime:i64 (как u64)
+//   0x75: link
+//   0x76: header_len:u64  (данные начинаются с region.offset + header_len)
+
+fn encode_member(m: &TarMember, header_len: u64) -> Vec<u8> {
+    let mut v = Vec::new();
+    v.extend_from_slice(&tlv(0x70, m.path.as_bytes()));
+    v.extend_from_slice(&tlv(0x71, &[m.kind.flag()]));
+    v.extend_from_slice(&tlv(0x72, &u64_encode(m.size)));
+    v.extend_from_slice(&tlv(0x73, &u32_encode(m.mode)));
+    v.extend_from_slice(&tlv(0x74, &u64_encode(m.mtime as u64)));
+    v.extend_from_slice(&tlv(0x75, m.link.as_bytes()));
+    v.extend_from_slice(&tlv(0x76, &u64_encode(header_len)));
+    v
+}
+
+fn decode_member(z: &ZBlock) -> Option<TarMember> {
+    let region = decode_z_region(&z.meta)?;
+    let mut path = None;
+    let mut kind = None;
+    let mut size = None;
+    let mut mode = 0;
+    let mut mtime = 0;
+    let mut link = String::new();
+    let mut header_len = None;
+    for (tag, val) in tlv_iter(&region.data).ok()? {
+        match tag {
+            0x70 => path = Some(String::from_utf8(val).ok()?),
+            0x71 => kind = Some(TarKind::from_flag(*val.first()?)),
+            0x72 => size = Some(u64_decode(&val).ok()?),
+            0x73 => mode = u32_decode(&val).ok()?,
+            0x74 => mtime = u64_decode(&val).ok()? as i64,
+            0x75 => link = String::from_utf8(val).ok()?,
+            0x76 => header_len = Some(u64_decode(&val).ok()?),
+            _ => {}
+        }
+    }
+    Some(TarMember {
+        path: path?,
+        kind: kind?,
+        size: size?,
+        mode,
+        mtime,
+        link,
+        data_offset: region.offset + header_len?,
+        zblock: z.id,
+    })
+}
+
+/// Члены архива `obj` (файл или его Z-node) в порядке архива.
+/// Не-архив — пустой список.
+pub fn tar_members<S: BlockStore + ?Sized>(store: &S, obj: ObjectId) -> StoreResult<Vec<TarMember>> {
+    zblocks_of(store, obj, Some(Z_TYPE_TAR))?
+        .iter()
+        .map(|z| decode_member(z).ok_or(StoreError::Corrupt(format!("TAR Z block {} has bad meta", z.id))))
+        .collect()
+}
+
+/// Член архива по пути (как он записан в архиве); последний из одноимённых.
+pub fn tar_member<S: BlockStore + ?Sized>(store: &S, obj: ObjectId, path: &str) -> StoreResult<Option<TarMember>> {
+    Ok(tar_members(store, obj)?.into_iter().rev().find(|m| m.path == path))
+}
+
+/// Записать данные члена в `out`: читаются только L0 его Z-блока.
+pub fn tar_extract<S: BlockStore + ?Sized, W: Write>(store: &S, m: &TarMember, out: &mut W) -> StoreResult<u64> {
+    let z = store.get_z(m.zblock)?;
+    copy_zblock_range(store, &z, m.data_offset, m.size, out)
+}
+
+/// Поля из pax 'x' / GNU 'L','K', действующие на следующий член.
+#[derive(Default)]
+struct Overrides {
+    path:  Option<String>,
+    link:  Option<String>,
+    size:  Option<u64>,
+    mtime: Option<i64>,
+}
+
+/// Данные расширенного заголовка, которые собираем при пропуске.
+struct Ext {
+    flag: u8,
+    buf:  Vec<u8>,
+    left: u64,
+}
+
+#[derive(Default)]
+struct TarScan {
+    pos:  u64,
+    hdr:  Vec<u8>,
+    /// Байт данных (с выравниванием), которые ещё надо пропустить.
+    skip: u64,
+    ext:  Option<Ext>,
+    ov:   Overrides,
+    /// Начало текущего члена (первый из его заголовков).
+    start: Option<u64>,
+    out:  Vec<ZRegion>,
+    /// Байт meta в `out` (для `mem_usage`).
+    out_bytes: usize,
+    done: bool,
+}
+
+impl ZScan for TarScan {
+    fn feed(&mut self, mut data: &[u8]) {
+        while !data.is_empty() && !self.done {
+            if self.skip > 0 {
+                let n = (self.skip as usize).min(data.len());
+                if let Some(ext) = &mut self.ext {
+                    let take = (ext.left as usize).min(n);
+                    let room = EXT_LIMIT.saturating_sub(ext.buf.len()).min(take);
+                    ext.buf.extend_from_slice(&data[..room]);
+                    ext.left -= take as u64;
+                }
+                self.skip -= n as u64;
+                self.pos += n as u64;
+                data = &data[n..];
+                if self.skip == 0 {
+                    self.end_ext();
+                }
+                continue;
+            }
+            let n = (RECORD as usize - self.hdr.len()).min(data.len());
+            self.hdr.extend_from_slice(&data[..n]);
+            self.pos += n as u64;
+            data = &data[n..];
+            if self.hdr.len() == RECORD as usize {
+                let h = std::mem::take(&mut self.hdr);
+                self.header(&h);
+            }
+        }
+    }
+
+    fn finish(self: Box<Self>, _src: &dyn ZSource) -> StoreResult<Vec<ZRegion>> {
+        // усечённый архив: последний член может не уместиться
+        let pos = self.pos;
+        Ok(self.out.into_iter().filter(|r| r.offset.checked_add(r.len).is_some_and(|end| end <= pos)).collect())
+    }
+
+    fn mem_usage(&self) -> usize {
+        let ext = self.ext.as_ref().map_or(0, |e| e.buf.capacity());
+        self.hdr.capacity() + ext + self.out.capacity() * std::mem::size_of::<ZRegion>() + self.out_bytes
+    }
+}
+
+impl TarScan {
+    /// Разобрать заголовок, только что дочитанный до `self.pos`.
+    fn header(&mut self, h: &[u8]) {
+        let at = self.pos - RECORD;
+        if h.iter().all(|&b| b == 0) || !valid_header(h) {
+            self.done = true;
+            return;
+        }
+        let Some(hsize) = parse_num(&h[124..136]) else {
+            self.done = true;
+            return;
+        };
+        let start = *self.start.get_or_insert(at);
+
+        match h[156] {
+            flag @ (b'x' | b'L' | b'K') => {
+                let Some(skip) = padded(hsize) else {
+                    self.done = true;
+                    return;
+                };
+                self.ext = Some(Ext { flag, buf: Vec::new(), left: hsize });
+                self.skip = skip;
+                if self.skip == 0 {
+                    self.end_ext();
+                }
+            }
+            b'g' => {
+                // глобальный pax-заголовок — не член архива
+                let Some(skip) = padded(hsize) else {
+                    self.done = true;
+                    return;
+                };
+                self.skip = skip;
+                if start == at {
+                    self.start = None;
+                }
+            }
+            flag => {
+                let ov = std::mem::take(&mut self.ov);
+                let kind = TarKind::from_flag(flag);
+                let size = if kind.has_data() { ov.size.unwrap_or(hsize) } else { 0 };
+                let header_len = self.pos - start;
+                // размер из base-256 или pax может быть любым u64
+                let Some((skip, len)) = padded(size).and_then(|p| Some((p, header_len.checked_add(p)?))) else {
+                    self.done = true;
+                    return;
+                };
+                let m = TarMember {
+                    path: ov.path.unwrap_or_else(|| ustar_name(h)),
+                    kind,
+                    size,
+                    mode: parse_num(&h[100..108]).unwrap_or(0) as u32,
+                    mtime: ov.mtime.unwrap_or_else(|| parse_num(&h[136..148]).unwrap_or(0) as i64),
+                    link: ov.link.unwrap_or_else(|| cstr(&h[157..257])),
+                    data_offset: self.pos,
+                    zblock: 0,
+                };
+                let data = encode_member(&m, header_len);
+                self.out_bytes += data.len();
+                self.out.push(ZRegion {
+                    offset: start,
+                    len,
+                    data,
+                });
+                self.skip = skip;
+                self.start = None;
+            }
+        }
+    }
+
+    fn end_ext(&mut self) {
+        let Some(ext) = self.ext.take() else {
+            return;
+        };
+        match ext.flag {
+            b'L' => self.ov.path = Some(cstr(&ext.buf)),
+            b'K' => self.ov.link = Some(cstr(&ext.buf)),
+            _ => apply_pax(&ext.buf, &mut self.ov),
+        }
+    }
+}
+
+/// `n`, выровненное вверх до записи; None — не помещается в u64.
+fn padded(n: u64) -> Option<u64> {
+    n.checked_next_multiple_of(RECORD)
+}
+
+/// magic "ustar" (POSIX и GNU) + контрольная сумма заголовка.
+fn valid_header(h: &[u8]) -> bool {
+    if &h[257..262] != b"ustar" {
+        return false;
+    }
+    let Some(want) = parse_num(&h[148..156]) else {
+        return false;
+    };
+    let sum: u64 = h
+        .iter()
+        .enumerate()
+        .map(|(i, &b)| if (148..156).contains(&i) { b' ' as u64 } else { b as u64 })
+        .sum();
+    sum == want
+}
+
+/// Числовое поле: восьмеричный ASCII или base-256 (старший бит).
+fn parse_num(f: &[u8]) -> Option<u64> {
+    if f[0] & 0x80 != 0 {
+        if f[0] == 0xff {
+            return None; // отрицательные значения не поддерживаем
+        }
+        let mut v = (f[0] & 0x7f) as u64;
+        for &b in &f[1..] {
+            v = v.checked_mul(256)?.checked_add(b as u64)?;
+        }
+        return Some(v);
+    }
+    let s = std::str::from_utf8(f).ok()?;
+    let s = s.trim_matches(|c: char| c == '\0' || c == ' ');
+    if s.is_empty() {
+        return Some(0);
+    }
+    u64::from_str_radix(s, 8).ok()
+}
+
+fn cstr(b: &[u8]) -> String {
+    let end = b.iter().position(|&c| c == 0).unwrap_or(b.len());
+    String::from_utf8_lossy(&b[..end]).into_owned()
+}
+
+/// name, с prefix для POSIX ustar (у GNU на его месте другие поля).
+fn ustar_name(h: &[u8]) -> String {
+    let name = cstr(&h[0..100]);
+    if &h[257..263] == b"ustar\0" {
+        let prefix = cstr(&h[345..500]);
+        if !prefix.is_empty() {
+            return format!("{}/{}", prefix, name);
+        }
+    }
+    name
+}
+
+/// Записи pax: "<len> <key>=<value>\n".
+fn apply_pax(mut buf: &[u8], ov: &mut Overrides) {
+    while let Some(sp) = buf.iter().position(|&b| b == b' ') {
+        let Some(len) = std::str::from_utf8(&buf[..sp]).ok().and_then(|s| s.parse::<usize>().ok()) else {
+            return;
+        };
+        if len <= sp + 1 || len > buf.len() {
+            return;
+        }
+        let rec = &buf[sp + 1..len];
+        let rec = rec.strip_suffix(b"\n").unwrap_or(rec);
+        if let Some(eq) = rec.iter().position(|&b| b == b'=') {
+            let val = String::from_utf8_lossy(&rec[eq + 1..]).into_owned();
+            match &rec[..eq] {
+                b"path" => ov.path = Some(val),
+                b"linkpath" => ov.link = Some(val),
+                b"size" => ov.size = val.parse().ok(),
+                b"mtime" => ov.mtime = val.split('.').next().and_then(|s| s.parse().ok()),
+                _ => {}
+            }
+        }
+        buf = &buf[len..];
+    }
+}
@@ -0,0 +1,35 @@
+use crate::types::{BlockRef, ObjectId, ZNodeMeta, OBJ_TYPE_ZNODE};
+use crate::codec::{ObjectPayload, tlv_iter, encode_znode_meta, decode_znode_meta};
+use crate::store::blockstore::{BlockStore, StoreError, StoreResult};
+use crate::store::decode::BlockBody;
+
+/// Записать Z-node поверх Object'а файла: root -> файл, meta -> ZNodeMeta.
+///
+/// Читатели данных (ObjectReader, restore) проходят Z-node насквозь,
+/// как обычную цепочку Object -> Object.
+pub fn put_znode<S: BlockStore + ?Sized>(store: &mut S, file: ObjectId, meta: &ZNodeMeta) -> StoreResult<ObjectId> {
+    store.put_object(&ObjectPayload {
+        root:     BlockRef::Object(file),
+        obj_type: OBJ_TYPE_ZNODE,
+        meta:     encode_znode_meta(meta),
+    })
+}
+
+/// ZNodeMeta, если `id` — Z-node (иначе None).
+pub fn znode_meta<S: BlockStore + ?Sized>(store: &S, id: ObjectId) -> StoreResult<Option<ZNodeMeta>> {
+    match store.get_typed(id)?.2 {
+        BlockBody::Object(o) => znode_meta_of(id, &o),
+        _ => Ok(None),
+    }
+}
+
+/// ZNodeMeta из уже прочитанного Object'а.
+pub(crate) fn znode_meta_of(id: ObjectId, o: &ObjectPayload) -> StoreResult<Option<ZNodeMeta>> {
+    if o.obj_type != OBJ_TYPE_ZNODE {
+        return Ok(None);
+    }
+    let tlvs = tlv_iter(&o.meta)?;
+    decode_znode_meta(&tlvs)
+        .map(Some)
+        .ok_or(StoreError::Corrupt(format!("Z-node {} has bad meta", id)))
+}
@@ -4,6 +4,7 @@ use crate::types::{BlockId};
 #[derive(Clone, Debug)]
 pub struct L0Block {
     pub id:   BlockId,
+    /// hash frame'а (blake3 payload, как `hash_l0`), ключ `find_hash`.
     pub hash: [u8; 32],
     pub size: u32,   // фактическая длина; обычно 8192
     pub tier: u8,    // уровень хранения (RAM/SSD/HDD/...)
@@ -4,6 +4,6 @@ pub mod zblock;
 pub mod object;
 
 pub use l0::L0Block;
-pub use multi::{MultiBlock, MultiRecipe, CodecRef, DictRef};
+pub use multi::{MultiBlock, MultiInfo, MultiRecipe, CodecRef, DictRef};
 pub use zblock::ZBlock;
 pub use object::Object;
@@ -27,6 +27,15 @@ pub enum MultiRecipe {
         blocks: SmallVec<[BlockId; 8]>,
     },
 
+    /// Узел сбалансированного дерева: дети (L0 или Multi) + накопленные
+    /// логические концы детей. `ends[i]` — смещение конца i-го ребёнка
+    /// от начала узла, поэтому поиск смещения — бинарный поиск по `ends`,
+    /// без чтения детей.
+    Indexed {
+        blocks: SmallVec<[BlockId; 8]>,
+        ends:   Vec<u64>,
+    },
+
     /// Рецепт, завязанный на кодек/словарь/кластер (в т.ч. пользовательский).
     CodecRecipe {
         codec:  CodecRef,
@@ -49,11 +58,24 @@ pub enum MultiRecipe {
     },
 }
 
+/// Сведения о восстановленных данных Multi-блока, хранятся в его frame.
+///
+/// `hash` — blake3 от склеенных логических байт (не от рецепта), поэтому
+/// одинаковые данные дают одинаковый hash при любой нарезке и форме дерева.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MultiInfo {
+    pub logical_len: u64,
+    pub hash:        [u8; 32],
+}
+
 /// MultiBlock: мультиблок / логический блок (например, 64K).
 #[derive(Clone, Debug)]
 pub struct MultiBlock {
-    pub id:          BlockId,
-    pub hash:        [u8; 32],  // хэш логического блока
-    pub logical_len: u32,       // длина в байтах (64K и т.п.)
-    pub recipe:      MultiRecipe,
+    pub id:     BlockId,
+    /// hash frame'а (рецепт + MultiInfo, как `hash_multi_info`), ключ
+    /// `find_hash` — как у L0Block/ZBlock. hash данных — в `info`.
+    pub hash:   [u8; 32],
+    /// Длина и blake3 восстановленных данных, если записаны в frame.
+    pub info:   Option<MultiInfo>,
+    pub recipe: MultiRecipe,
 }
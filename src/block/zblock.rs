@@ -4,6 +4,7 @@ use crate::types::BlockId;
 #[derive(Clone, Debug)]
 pub struct ZBlock {
     pub id:       BlockId,
+    /// hash frame'а (blake3 payload, как `hash_z`), ключ `find_hash`.
     pub hash:     [u8; 32],
 
     /// Диапазон L0-блоков, покрываемый этим Z-блоком (включительно).
@@ -0,0 +1,122 @@
+use crate::types::BlockRef;
+use crate::codec::common::*;
+
+// Каталог <-> TLV (meta Object'а с obj_type = OBJ_TYPE_DIR)
+//
+// Формат: последовательность TLV, по одному на запись.
+//   0x40: DirEntry
+//       kind:u8          (0 = file, 1 = dir, 2 = symlink)
+//       mode:u32         (права + тип в стиле st_mode)
+//       uid:u32
+//       gid:u32
+//       mtime_sec:i64    (секунды от UNIX epoch, может быть < 0)
+//       mtime_nsec:u32
+//       size:u64         (file — длина данных, symlink — длина цели, dir — 0)
+//       ref_kind:u8      (0 = L0, 1 = Multi, 2 = Z, 3 = Object)
+//       ref_id:u64
+//       name:bytes       (UTF-8, до конца значения)
+//
+// child: file/dir — Object (OBJ_TYPE_FILE / OBJ_TYPE_DIR), symlink — L0 с целью ссылки.
+
+const ENTRY_FIXED: usize = 1 + 4 + 4 + 4 + 8 + 4 + 8 + 1 + 8;
+
+/// Вид записи каталога.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DirEntryKind {
+    File,
+    Dir,
+    Symlink,
+}
+
+/// Запись каталога: имя, атрибуты и ссылка на содержимое.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DirEntry {
+    pub name:       String,
+    pub kind:       DirEntryKind,
+    pub mode:       u32,
+    pub uid:        u32,
+    pub gid:        u32,
+    pub mtime_sec:  i64,
+    pub mtime_nsec: u32,
+    pub size:       u64,
+    pub child:      BlockRef,
+}
+
+fn encode_ref(r: BlockRef, out: &mut Vec<u8>) {
+    let (k, id) = match r {
+        BlockRef::L0(id)     => (0, id),
+        BlockRef::Multi(id)  => (1, id),
+        BlockRef::Z(id)      => (2, id),
+        BlockRef::Object(id) => (3, id),
+    };
+    out.push(k);
+    out.extend_from_slice(&u64_encode(id));
+}
+
+fn decode_ref(b: &[u8]) -> Option<BlockRef> {
+    let id = u64_decode(b.get(1..9)?).ok()?;
+    Some(match b[0] {
+        0 => BlockRef::L0(id),
+        1 => BlockRef::Multi(id),
+        2 => BlockRef::Z(id),
+        3 => BlockRef::Object(id),
+        _ => return None,
+    })
+}
+
+pub fn encode_dir_entries(entries: &[DirEntry]) -> Vec<u8> {
+    let mut v = Vec::new();
+    for e in entries {
+        let mut buf = Vec::with_capacity(ENTRY_FIXED + e.name.len());
+        buf.push(match e.kind {
+            DirEntryKind::File    => 0,
+            DirEntryKind::Dir     => 1,
+            DirEntryKind::Symlink => 2,
+        });
+        buf.extend_from_slice(&u32_encode(e.mode));
+        buf.extend_from_slice(&u32_encode(e.uid));
+        buf.extend_from_slice(&u32_encode(e.gid));
+        buf.extend_from_slice(&u64_encode(e.mtime_sec as u64));
+        buf.extend_from_slice(&u32_encode(e.mtime_nsec));
+        buf.extend_from_slice(&u64_encode(e.size));
+        encode_ref(e.child, &mut buf);
+        buf.extend_from_slice(e.name.as_bytes());
+        v.extend_from_slice(&tlv(0x40, &buf));
+    }
+    v
+}
+
+pub fn decode_dir_entries(tlvs: &[(u8, Vec<u8>)]) -> Option<Vec<DirEntry>> {
+    let mut out = Vec::new();
+    for (tag, val) in tlvs {
+        if *tag != 0x40 {
+            continue;
+        }
+        if val.len() < ENTRY_FIXED {
+            return None;
+        }
+        let kind = match val[0] {
+            0 => DirEntryKind::File,
+            1 => DirEntryKind::Dir,
+            2 => DirEntryKind::Symlink,
+            _ => return None,
+        };
+        out.push(DirEntry {
+            kind,
+            mode:       u32_decode(&val[1..5]).ok()?,
+            uid:        u32_decode(&val[5..9]).ok()?,
+            gid:        u32_decode(&val[9..13]).ok()?,
+            mtime_sec:  u64_decode(&val[13..21]).ok()? as i64,
+            mtime_nsec: u32_decode(&val[21..25]).ok()?,
+            size:       u64_decode(&val[25..33]).ok()?,
+            child:      decode_ref(&val[33..42])?,
+            name:       String::from_utf8(val[ENTRY_FIXED..].to_vec()).ok()?,
+        });
+    }
+    Some(out)
+}
+
+/// Записи каталога прямо из meta Object'а.
+pub fn decode_dir_meta(meta: &[u8]) -> Option<Vec<DirEntry>> {
+    decode_dir_entries(&tlv_iter(meta).ok()?)
+}
@@ -1,7 +1,7 @@
 use crate::codec::common::*;
 
-/// L0: в payload храним просто сырые байты блока.
-/// Здесь нет привязки к L0Block (id/hash/size/tier) — это уровень выше.
+// L0: в payload храним просто сырые байты блока.
+// Здесь нет привязки к L0Block (id/hash/size/tier) — это уровень выше.
 
 /// tag 0x01 = raw L0 bytes
 pub fn encode_l0_raw(raw: &[u8]) -> Vec<u8> {
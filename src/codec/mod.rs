@@ -3,9 +3,17 @@ pub mod l0;
 pub mod multi;
 pub mod z;
 pub mod object;
+pub mod dir;
+pub mod znode;
+pub mod snapshot;
+pub mod refs;
 
 pub use common::*;
 pub use l0::*;
 pub use multi::*;
 pub use z::*;
 pub use object::*;
+pub use dir::*;
+pub use znode::*;
+pub use snapshot::*;
+pub use refs::*;
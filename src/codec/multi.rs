@@ -1,38 +1,48 @@
 use smallvec::SmallVec;
 
 use crate::codec::common::*;
-use crate::block::multi::{MultiRecipe, CodecRef, DictRef};
+use crate::net_core::error::{NetError, NetResult};
+use crate::block::multi::{MultiRecipe, MultiInfo, CodecRef, DictRef};
 use crate::types::{BlockId, ClusterId, ObjectId};
 
-/// MultiRecipe <-> TLV payload
-///
-/// Формат:
-///   0x10: Aggregate
-///       [ id_0:u64, id_1:u64, ... ]
-///
-///   0x11: CodecRecipe
-///       codec_id:u64
-///       codec_cluster:u64 (0 = None)
-///       dict_flag:u8 (0/1)
-///       if dict_flag==1:
-///         dict_id:u64
-///         dict_cluster:u64 (0=None)
-///         dict_object_id:u64 (0=None)
-///       recipe_id:u64
-///       has_recipe_data:u8 (0/1)
-///       if has_recipe_data==1:
-///         recipe_len:u32
-///         recipe_bytes[recipe_len]
-///       has_blocks:u8 (0/1)
-///       if has_blocks==1:
-///         blocks_count:u32
-///         blocks[blocks_count]*u64
-///
-///   0x12: Custom
-///       kind_id:u32
-///       payload:bytes
-///
-/// Всё это — только про recipe. id/hash/logical_len живут снаружи.
+// MultiRecipe <-> TLV payload
+//
+// Формат:
+//   0x10: Aggregate
+//       [ id_0:u64, id_1:u64, ... ]
+//
+//   0x11: CodecRecipe
+//       codec_id:u64
+//       codec_cluster:u64 (0 = None)
+//       dict_flag:u8 (0/1)
+//       if dict_flag==1:
+//         dict_id:u64
+//         dict_cluster:u64 (0=None)
+//         dict_object_id:u64 (0=None)
+//       recipe_id:u64
+//       has_recipe_data:u8 (0/1)
+//       if has_recipe_data==1:
+//         recipe_len:u32
+//         recipe_bytes[recipe_len]
+//       has_blocks:u8 (0/1)
+//       if has_blocks==1:
+//         blocks_count:u32
+//         blocks[blocks_count]*u64
+//
+//   0x12: Custom
+//       kind_id:u32
+//       payload:bytes
+//
+//   0x13: Indexed
+//       count:u32
+//       [ id:u64, end:u64 ] * count   (end — накопленное логическое смещение)
+//
+// Рядом с рецептом (необязательный TLV, идёт после него):
+//   0x14: MultiInfo
+//       logical_len:u64
+//       hash[32]              (blake3 восстановленных данных)
+//
+// id живёт в заголовке frame; hash frame'а покрывает и рецепт, и MultiInfo.
 
 fn opt_cluster_to_u64(c: Option<ClusterId>) -> u64 {
     c.unwrap_or(0)
@@ -41,7 +51,9 @@ fn opt_obj_to_u64(o: Option<ObjectId>) -> u64 {
     o.unwrap_or(0)
 }
 
-pub fn encode_multi_recipe(recipe: &MultiRecipe) -> Vec<u8> {
+/// EncodeError — Indexed-рецепт с разным числом блоков и концов
+/// или с убывающими концами.
+pub fn encode_multi_recipe(recipe: &MultiRecipe) -> NetResult<Vec<u8>> {
     let mut v = Vec::new();
 
     match recipe {
@@ -53,6 +65,20 @@ pub fn encode_multi_recipe(recipe: &MultiRecipe) -> Vec<u8> {
             v.extend_from_slice(&tlv(0x10, &buf));
         }
 
+        MultiRecipe::Indexed { blocks, ends } => {
+            if blocks.len() != ends.len() || ends.windows(2).any(|w| w[1] < w[0]) {
+                return Err(NetError::EncodeError);
+            }
+            let n = u32::try_from(blocks.len()).map_err(|_| NetError::EncodeError)?;
+            let mut buf = Vec::with_capacity(4 + blocks.len() * 16);
+            buf.extend_from_slice(&u32_encode(n));
+            for (id, end) in blocks.iter().zip(ends.iter()) {
+                buf.extend_from_slice(&u64_encode(*id));
+                buf.extend_from_slice(&u64_encode(*end));
+            }
+            v.extend_from_slice(&tlv(0x13, &buf));
+        }
+
         MultiRecipe::CodecRecipe {
             codec,
             dict,
@@ -113,7 +139,27 @@ pub fn encode_multi_recipe(recipe: &MultiRecipe) -> Vec<u8> {
         }
     }
 
-    v
+    Ok(v)
+}
+
+/// MultiInfo -> TLV 0x14 (дописывается в payload после рецепта).
+pub fn encode_multi_info(info: &MultiInfo) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(8 + 32);
+    buf.extend_from_slice(&u64_encode(info.logical_len));
+    buf.extend_from_slice(&info.hash);
+    tlv(0x14, &buf)
+}
+
+/// Найти MultiInfo среди TLV payload'а Multi-блока (None — не записан).
+pub fn decode_multi_info(tlvs: &[(u8, Vec<u8>)]) -> Option<MultiInfo> {
+    let (_, val) = tlvs.iter().find(|(tag, _)| *tag == 0x14)?;
+    if val.len() != 8 + 32 {
+        return None;
+    }
+    let logical_len = u64_decode(&val[0..8]).ok()?;
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(&val[8..40]);
+    Some(MultiInfo { logical_len, hash })
 }
 
 pub fn decode_multi_recipe(tlvs: &[(u8, Vec<u8>)]) -> Option<MultiRecipe> {
@@ -209,6 +255,23 @@ pub fn decode_multi_recipe(tlvs: &[(u8, Vec<u8>)]) -> Option<MultiRecipe> {
                 });
             }
 
+            0x13 => {
+                if val.len() < 4 {
+                    return None;
+                }
+                let count = u32_decode(&val[0..4]).ok()? as usize;
+                if val.len() != 4 + count * 16 {
+                    return None;
+                }
+                let mut blocks: SmallVec<[BlockId; 8]> = SmallVec::with_capacity(count);
+                let mut ends = Vec::with_capacity(count);
+                for pair in val[4..].chunks(16) {
+                    blocks.push(u64_decode(&pair[0..8]).ok()?);
+                    ends.push(u64_decode(&pair[8..16]).ok()?);
+                }
+                return Some(MultiRecipe::Indexed { blocks, ends });
+            }
+
             0x12 => {
                 if val.len() < 4 {
                     return None;
@@ -0,0 +1,65 @@
+use crate::types::ObjectId;
+use crate::codec::common::*;
+
+// Журнал ссылок <-> TLV (файл рядом со store, записи подряд, только дописываются)
+//
+//   0xB0: запись, внутри — TLV:
+//       0xB1: name (UTF-8)
+//       0xB2: old:u64        (нет — ссылки не было)
+//       0xB3: new:u64        (нет — ссылка удалена)
+//       0xB4: time_sec:i64   (секунды от UNIX epoch)
+//       0xB5: message (UTF-8)
+
+/// Тег одной записи журнала ссылок.
+pub const REF_RECORD_TAG: u8 = 0xB0;
+
+/// Одно изменение ссылки (запись reflog).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RefLogEntry {
+    pub name:     String,
+    pub old:      Option<ObjectId>,
+    pub new:      Option<ObjectId>,
+    pub time_sec: i64,
+    pub message:  String,
+}
+
+/// Запись журнала целиком (с внешней TLV 0xB0).
+pub fn encode_ref_entry(e: &RefLogEntry) -> Vec<u8> {
+    let mut v = Vec::new();
+    v.extend_from_slice(&tlv(0xB1, e.name.as_bytes()));
+    if let Some(old) = e.old {
+        v.extend_from_slice(&tlv(0xB2, &u64_encode(old)));
+    }
+    if let Some(new) = e.new {
+        v.extend_from_slice(&tlv(0xB3, &u64_encode(new)));
+    }
+    v.extend_from_slice(&tlv(0xB4, &u64_encode(e.time_sec as u64)));
+    v.extend_from_slice(&tlv(0xB5, e.message.as_bytes()));
+    tlv(REF_RECORD_TAG, &v)
+}
+
+/// Значение TLV 0xB0 -> запись.
+pub fn decode_ref_entry(val: &[u8]) -> Option<RefLogEntry> {
+    let mut name = None;
+    let mut old = None;
+    let mut new = None;
+    let mut time = None;
+    let mut message = String::new();
+    for (tag, val) in tlv_iter(val).ok()? {
+        match tag {
+            0xB1 => name = Some(String::from_utf8(val).ok()?),
+            0xB2 => old = Some(u64_decode(&val).ok()?),
+            0xB3 => new = Some(u64_decode(&val).ok()?),
+            0xB4 => time = Some(u64_decode(&val).ok()? as i64),
+            0xB5 => message = String::from_utf8(val).ok()?,
+            _ => {}
+        }
+    }
+    Some(RefLogEntry {
+        name: name?,
+        old,
+        new,
+        time_sec: time?,
+        message,
+    })
+}
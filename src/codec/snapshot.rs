@@ -0,0 +1,60 @@
+use crate::types::ObjectId;
+use crate::codec::common::*;
+
+// Снапшот <-> TLV (meta Object'а с obj_type = OBJ_TYPE_SNAPSHOT)
+//
+//   0xA0: parent:u64      (по одной TLV на родителя, порядок сохраняется)
+//   0xA1: time_sec:i64    (секунды от UNIX epoch)
+//   0xA2: message         (UTF-8)
+//   0xA3: generation:u64  (0 — без родителей, иначе 1 + max у родителей;
+//                          пишет put_snapshot)
+//
+// root снапшота — Object с содержимым версии.
+
+/// Метаданные снапшота: родители, время и сообщение, как у коммита.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SnapshotMeta {
+    /// Родительские снапшоты; первый — основная линия, пусто — начало истории.
+    pub parents:  Vec<ObjectId>,
+    pub time_sec: i64,
+    pub message:  String,
+}
+
+pub fn encode_snapshot_meta(m: &SnapshotMeta) -> Vec<u8> {
+    let mut v = Vec::new();
+    for p in &m.parents {
+        v.extend_from_slice(&tlv(0xA0, &u64_encode(*p)));
+    }
+    v.extend_from_slice(&tlv(0xA1, &u64_encode(m.time_sec as u64)));
+    v.extend_from_slice(&tlv(0xA2, m.message.as_bytes()));
+    v
+}
+
+pub fn decode_snapshot_meta(tlvs: &[(u8, Vec<u8>)]) -> Option<SnapshotMeta> {
+    let mut parents = Vec::new();
+    let mut time = None;
+    let mut message = String::new();
+    for (tag, val) in tlvs {
+        match *tag {
+            0xA0 => parents.push(u64_decode(val).ok()?),
+            0xA1 => time = Some(u64_decode(val).ok()? as i64),
+            0xA2 => message = String::from_utf8(val.clone()).ok()?,
+            _ => {}
+        }
+    }
+    Some(SnapshotMeta {
+        parents,
+        time_sec: time?,
+        message,
+    })
+}
+
+pub fn encode_snapshot_generation(generation: u64) -> Vec<u8> {
+    tlv(0xA3, &u64_encode(generation))
+}
+
+/// Поколение снапшота; None — TLV нет или она битая.
+pub fn decode_snapshot_generation(tlvs: &[(u8, Vec<u8>)]) -> Option<u64> {
+    let (_, val) = tlvs.iter().find(|(tag, _)| *tag == 0xA3)?;
+    u64_decode(val).ok()
+}
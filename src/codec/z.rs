@@ -42,3 +42,140 @@ pub fn decode_z_payload(tlvs: &[(u8, Vec<u8>)]) -> Option<ZPayload> {
         meta,
     })
 }
+
+/// Зона, найденная анализатором: байтовый диапазон внутри объекта
+/// и данные самого анализатора. Лежит в `ZPayload::meta` Z-блоков,
+/// которые пишет импорт (см. `analysis::ZRegistry`).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ZRegion {
+    pub offset: u64,
+    pub len:    u64,
+    pub data:   Vec<u8>,
+}
+
+// ZRegion <-> TLV (meta Z-блока)
+//
+//   0x24: offset:u64
+//   0x25: len:u64
+//   0x26: data (opaque, формат анализатора)
+
+pub fn encode_z_region(r: &ZRegion) -> Vec<u8> {
+    let mut v = Vec::new();
+    v.extend_from_slice(&tlv(0x24, &u64_encode(r.offset)));
+    v.extend_from_slice(&tlv(0x25, &u64_encode(r.len)));
+    v.extend_from_slice(&tlv(0x26, &r.data));
+    v
+}
+
+pub fn decode_z_region(meta: &[u8]) -> Option<ZRegion> {
+    let mut offset = None;
+    let mut len    = None;
+    let mut data   = Vec::new();
+    for (tag, val) in tlv_iter(meta).ok()? {
+        match tag {
+            0x24 => offset = Some(u64_decode(&val).ok()?),
+            0x25 => len    = Some(u64_decode(&val).ok()?),
+            0x26 => data   = val,
+            _ => {}
+        }
+    }
+    Some(ZRegion {
+        offset: offset?,
+        len:    len?,
+        data,
+    })
+}
+
+/// Индекс Z-блоков одного `z_type` в meta Object'а файла.
+///
+/// `index` — Multi `Indexed` с Z-блоками в порядке зон (`ends[i] = i + 1`):
+/// i-я зона — `blocks[i]`. `by_key` — тот же набор, отсортированный по
+/// ключу зоны (`ZAnalyzer::region_key`), ключи лежат в `ends`; пишется,
+/// только если анализатор даёт ключи.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ZIndexRef {
+    pub z_type: u32,
+    pub index:  BlockId,
+    pub by_key: Option<BlockId>,
+}
+
+// Индексы Z-блоков в meta Object'а (OBJ_TYPE_FILE):
+//
+//   0x61: z_type:u32 || index:u64 [|| by_key:u64]   (по одной TLV на z_type)
+
+pub fn encode_z_index(refs: &[ZIndexRef]) -> Vec<u8> {
+    let mut v = Vec::new();
+    for r in refs {
+        let mut val = u32_encode(r.z_type);
+        val.extend_from_slice(&u64_encode(r.index));
+        if let Some(k) = r.by_key {
+            val.extend_from_slice(&u64_encode(k));
+        }
+        v.extend_from_slice(&tlv(0x61, &val));
+    }
+    v
+}
+
+/// Индексы из meta; прочие TLV пропускаются. None — meta не TLV
+/// или битая 0x61.
+pub fn decode_z_index(meta: &[u8]) -> Option<Vec<ZIndexRef>> {
+    let mut out = Vec::new();
+    for (tag, val) in tlv_iter(meta).ok()? {
+        if tag != 0x61 {
+            continue;
+        }
+        if val.len() != 12 && val.len() != 20 {
+            return None;
+        }
+        out.push(ZIndexRef {
+            z_type: u32_decode(&val[..4]).ok()?,
+            index:  u64_decode(&val[4..12]).ok()?,
+            by_key: match val.get(12..) {
+                Some(k) if !k.is_empty() => Some(u64_decode(k).ok()?),
+                _ => None,
+            },
+        });
+    }
+    Some(out)
+}
+
+/// L0 зоны в порядке объекта: `base` — смещение начала первого из них.
+/// Пишется импортом рядом с `ZRegion`, чтобы зону можно было прочитать
+/// по одному Z-блоку, без дерева данных объекта.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ZSpan {
+    pub base: u64,
+    pub l0s:  Vec<BlockId>,
+}
+
+// ZSpan <-> TLV (meta Z-блока, рядом с ZRegion)
+//
+//   0x27: base:u64
+//   0x28: l0s: u64 * N
+
+pub fn encode_z_span(s: &ZSpan) -> Vec<u8> {
+    let mut v = Vec::new();
+    v.extend_from_slice(&tlv(0x27, &u64_encode(s.base)));
+    let ids: Vec<u8> = s.l0s.iter().flat_map(|id| u64_encode(*id)).collect();
+    v.extend_from_slice(&tlv(0x28, &ids));
+    v
+}
+
+/// ZSpan из meta; None — meta без span (Z-блок не от импорта).
+pub fn decode_z_span(meta: &[u8]) -> Option<ZSpan> {
+    let mut base = None;
+    let mut l0s  = None;
+    for (tag, val) in tlv_iter(meta).ok()? {
+        match tag {
+            0x27 => base = Some(u64_decode(&val).ok()?),
+            0x28 => {
+                if val.len() % 8 != 0 {
+                    return None;
+                }
+                l0s = Some(val.chunks(8).map(u64_decode).collect::<Result<Vec<_>, _>>().ok()?);
+            }
+            _ => {}
+        }
+    }
+    Some(ZSpan { base: base?, l0s: l0s? })
+}
@@ -0,0 +1,30 @@
+use crate::types::ZNodeMeta;
+use crate::codec::common::*;
+
+// ZNodeMeta <-> TLV (meta Object'а с obj_type = OBJ_TYPE_ZNODE)
+//
+//   0x50: size_bytes:u64   (логический размер данных объекта)
+//   0x51: blocks:u32       (сколько L0-чанков)
+
+pub fn encode_znode_meta(m: &ZNodeMeta) -> Vec<u8> {
+    let mut v = Vec::new();
+    v.extend_from_slice(&tlv(0x50, &u64_encode(m.size_bytes)));
+    v.extend_from_slice(&tlv(0x51, &u32_encode(m.blocks)));
+    v
+}
+
+pub fn decode_znode_meta(tlvs: &[(u8, Vec<u8>)]) -> Option<ZNodeMeta> {
+    let mut size = None;
+    let mut blocks = None;
+    for (tag, val) in tlvs {
+        match *tag {
+            0x50 => size = Some(u64_decode(val).ok()?),
+            0x51 => blocks = Some(u32_decode(val).ok()?),
+            _ => {}
+        }
+    }
+    Some(ZNodeMeta {
+        size_bytes: size?,
+        blocks: blocks?,
+    })
+}
@@ -2,6 +2,15 @@ use std::env;
 use std::fs;
 use std::path::PathBuf;
 
+/// Алгоритм нарезки данных на L0 при импорте.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChunkerKind {
+    /// Фиксированные чанки по `l0_chunk`.
+    Fixed,
+    /// Content-defined (FastCDC) по `cdc_min/cdc_avg/cdc_max`.
+    FastCdc,
+}
+
 /// Глобальный конфиг QuarXTor / QuarXDrive / quarxctl.
 ///
 /// Источники:
@@ -13,6 +22,16 @@ pub struct QuarxConfig {
     /// Размер L0-чунка (байт).
     pub l0_chunk: usize,
 
+    /// Чанкер импорта.
+    pub chunker: ChunkerKind,
+    /// Границы размеров чанков для FastCDC (байт).
+    pub cdc_min: usize,
+    pub cdc_avg: usize,
+    pub cdc_max: usize,
+
+    /// Fanout дерева Multi-of-Multi: максимум детей в одном Multi-узле.
+    pub multi_fanout: usize,
+
     /// Минимальный размер файла (в байтах) для импорта.
     /// Если 0 — порога нет.
     pub import_min_file_size: u64,
@@ -24,11 +43,24 @@ pub struct QuarxConfig {
     pub import_skip_devices: bool,
     pub import_skip_special: bool,
 
+    /// Лимит памяти одного потокового импорта (байт): окно чтения
+    /// и состояние анализаторов. 0 — без лимита.
+    pub import_mem_limit: u64,
+
+    /// Рабочих потоков импорта (hash и кодирование L0).
+    /// 0 — по числу ядер, 1 — всё в вызывающем потоке.
+    pub import_workers: usize,
+
     /// Лимит RAM для будущего RAM-tier / кэша, в байтах.
     /// 0        = RAM-слой выключен (работаем только по диску).
     /// u64::MAX = "full/unlimited" — не ограничиваем со своей стороны.
     pub ram_limit_bytes: u64,
 
+    /// Глубина graph-aware prefetch в RAM-tier (0 = выключен).
+    pub ram_prefetch_depth: u32,
+    /// Бюджет prefetch (байт frame'ов) на один вызов `RamStore::prefetch`.
+    pub ram_prefetch_bytes: u64,
+
     /// Импорт использовать Z-node/cheap-size (на уровне FS-импортера).
     pub fs_import_use_z: bool,
     /// Порог в блоках/условном размере для применения Z-анализа (резерв).
@@ -40,6 +72,20 @@ pub struct QuarxConfig {
     ///   true  = если нет Z-node, читаем payload (дорого);
     ///   false = если нет Z-node, считаем размер 0 (дёшево).
     pub analysis_fs_stats_fallback: bool,
+
+    /// Встроенные Z-анализаторы, которые импорт включает сам
+    /// (`ZRegistry::from_config`).
+    ///
+    /// tar: Z-блок на каждый член архива. По умолчанию выключен —
+    /// анализ читает весь поток импорта.
+    pub analysis_z_tar: bool,
+    /// ELF: Z-блок на каждую секцию с данными. По умолчанию выключен —
+    /// таблица секций разбирается в конце импорта повторным чтением.
+    pub analysis_z_elf: bool,
+    /// JSON/NDJSON: Z-блок на каждый член/элемент верхнего уровня
+    /// или запись NDJSON. По умолчанию выключен — в большом логе
+    /// Z-блоков столько же, сколько записей.
+    pub analysis_z_json: bool,
 }
 
 impl Default for QuarxConfig {
@@ -48,6 +94,15 @@ impl Default for QuarxConfig {
             // базовый дефолт: 8 KiB
             l0_chunk: 8 * 1024,
 
+            // По умолчанию фиксированные чанки; FastCDC — 2K / 8K / 64K.
+            chunker: ChunkerKind::Fixed,
+            cdc_min: 2 * 1024,
+            cdc_avg: 8 * 1024,
+            cdc_max: 64 * 1024,
+
+            // 1024 ребёнка на узел: ~16 KiB frame, 3 уровня покрывают 8 TiB при 8K-чанках.
+            multi_fanout: 1024,
+
             import_min_file_size: 0, // нет порога по умолчанию
 
             // По умолчанию:
@@ -60,9 +115,19 @@ impl Default for QuarxConfig {
             import_skip_devices: true,
             import_skip_special: true,
 
+            // 256 MiB на импорт: окно чтения — единицы MiB, остальное анализаторам.
+            import_mem_limit: 256 * 1024 * 1024,
+
+            // Рабочие потоки — по числу ядер.
+            import_workers: 0,
+
             // По умолчанию RAM-tier выключен.
             ram_limit_bytes: 0,
 
+            // Prefetch: только прямые дети, не больше 4 MiB за чтение.
+            ram_prefetch_depth: 1,
+            ram_prefetch_bytes: 4 * 1024 * 1024,
+
             // Импорт по умолчанию Z-node включает, с порогом 10.
             fs_import_use_z: true,
             fs_import_z_threshold: 10,
@@ -72,6 +137,12 @@ impl Default for QuarxConfig {
             // По умолчанию bytes в fs-stats считаем только по Z-node,
             // без fallback на чтение payload.
             analysis_fs_stats_fallback: false,
+
+            // TAR/ELF/JSON-анализаторы выключены: включаются явно
+            // (analysis.z_* / QUARX_ANALYSIS_Z_*).
+            analysis_z_tar: false,
+            analysis_z_elf: false,
+            analysis_z_json: false,
         }
     }
 }
@@ -145,6 +216,19 @@ fn parse_size_bytes(s: &str) -> Option<u64> {
     base.checked_mul(mul)
 }
 
+fn parse_chunker(s: &str) -> Option<ChunkerKind> {
+    let v = s.trim().to_ascii_lowercase();
+    match v.as_str() {
+        "fixed" => Some(ChunkerKind::Fixed),
+        "fastcdc" | "cdc" => Some(ChunkerKind::FastCdc),
+        _ => None,
+    }
+}
+
+fn parse_size_usize(s: &str) -> Option<usize> {
+    parse_size_bytes(s).and_then(|n| usize::try_from(n).ok())
+}
+
 impl QuarxConfig {
     pub fn load() -> Self {
         let mut cfg = QuarxConfig::default();
@@ -168,6 +252,36 @@ impl QuarxConfig {
                         }
                     }
 
+                    // Чанкер:
+                    //   chunker=fastcdc
+                    //   cdc.min=2K  cdc.avg=8K  cdc.max=64K
+                    "chunker" => {
+                        if let Some(k) = parse_chunker(value) {
+                            cfg.chunker = k;
+                        }
+                    }
+                    "cdc.min" => {
+                        if let Some(n) = parse_size_usize(value) {
+                            cfg.cdc_min = n;
+                        }
+                    }
+                    "cdc.avg" => {
+                        if let Some(n) = parse_size_usize(value) {
+                            cfg.cdc_avg = n;
+                        }
+                    }
+                    "cdc.max" => {
+                        if let Some(n) = parse_size_usize(value) {
+                            cfg.cdc_max = n;
+                        }
+                    }
+
+                    "multi.fanout" => {
+                        if let Some(n) = parse_usize_simple(value) {
+                            cfg.multi_fanout = n;
+                        }
+                    }
+
                     "import.min_file_size" => {
                         if let Some(n) = parse_u64_simple(value) {
                             cfg.import_min_file_size = n;
@@ -200,6 +314,20 @@ impl QuarxConfig {
                         }
                     }
 
+                    // Лимит памяти импорта:
+                    //   import.mem_limit=64M
+                    "import.mem_limit" => {
+                        if let Some(n) = parse_size_bytes(value) {
+                            cfg.import_mem_limit = n;
+                        }
+                    }
+
+                    "import.workers" => {
+                        if let Some(n) = parse_usize_simple(value) {
+                            cfg.import_workers = n;
+                        }
+                    }
+
                     // RAM лимит из ini:
                     //   ram.limit=none
                     //   ram.limit=full
@@ -211,6 +339,20 @@ impl QuarxConfig {
                         }
                     }
 
+                    // Prefetch RAM-tier:
+                    //   ram.prefetch_depth=2
+                    //   ram.prefetch_bytes=16M
+                    "ram.prefetch_depth" => {
+                        if let Some(n) = parse_u64_simple(value) {
+                            cfg.ram_prefetch_depth = n.min(u32::MAX as u64) as u32;
+                        }
+                    }
+                    "ram.prefetch_bytes" => {
+                        if let Some(n) = parse_size_bytes(value) {
+                            cfg.ram_prefetch_bytes = n;
+                        }
+                    }
+
                     // FS-import / Z-node-порог
                     "fs_import.use_z" => {
                         if let Some(b) = parse_bool_simple(value) {
@@ -234,6 +376,21 @@ impl QuarxConfig {
                             cfg.analysis_fs_stats_fallback = b;
                         }
                     }
+                    "analysis.z_tar" => {
+                        if let Some(b) = parse_bool_simple(value) {
+                            cfg.analysis_z_tar = b;
+                        }
+                    }
+                    "analysis.z_elf" => {
+                        if let Some(b) = parse_bool_simple(value) {
+                            cfg.analysis_z_elf = b;
+                        }
+                    }
+                    "analysis.z_json" => {
+                        if let Some(b) = parse_bool_simple(value) {
+                            cfg.analysis_z_json = b;
+                        }
+                    }
 
                     _ => {
                         // неизвестные ключи игнорируем
@@ -249,6 +406,33 @@ impl QuarxConfig {
             }
         }
 
+        if let Ok(v) = env::var("QUARX_CHUNKER") {
+            if let Some(k) = parse_chunker(&v) {
+                cfg.chunker = k;
+            }
+        }
+        if let Ok(v) = env::var("QUARX_CDC_MIN") {
+            if let Some(n) = parse_size_usize(&v) {
+                cfg.cdc_min = n;
+            }
+        }
+        if let Ok(v) = env::var("QUARX_CDC_AVG") {
+            if let Some(n) = parse_size_usize(&v) {
+                cfg.cdc_avg = n;
+            }
+        }
+        if let Ok(v) = env::var("QUARX_CDC_MAX") {
+            if let Some(n) = parse_size_usize(&v) {
+                cfg.cdc_max = n;
+            }
+        }
+
+        if let Ok(v) = env::var("QUARX_MULTI_FANOUT") {
+            if let Some(n) = parse_usize_simple(&v) {
+                cfg.multi_fanout = n;
+            }
+        }
+
         if let Ok(v) = env::var("QUARX_IMPORT_MIN_FILE_SIZE") {
             if let Some(n) = parse_u64_simple(&v) {
                 cfg.import_min_file_size = n;
@@ -281,6 +465,18 @@ impl QuarxConfig {
             }
         }
 
+        if let Ok(v) = env::var("QUARX_IMPORT_MEM_LIMIT") {
+            if let Some(n) = parse_size_bytes(&v) {
+                cfg.import_mem_limit = n;
+            }
+        }
+
+        if let Ok(v) = env::var("QUARX_IMPORT_WORKERS") {
+            if let Some(n) = parse_usize_simple(&v) {
+                cfg.import_workers = n;
+            }
+        }
+
         // RAM-лимит через ENV
         if let Ok(v) = env::var("QUARX_RAM_LIMIT") {
             if let Some(n) = parse_size_bytes(&v) {
@@ -288,6 +484,17 @@ impl QuarxConfig {
             }
         }
 
+        if let Ok(v) = env::var("QUARX_RAM_PREFETCH_DEPTH") {
+            if let Some(n) = parse_u64_simple(&v) {
+                cfg.ram_prefetch_depth = n.min(u32::MAX as u64) as u32;
+            }
+        }
+        if let Ok(v) = env::var("QUARX_RAM_PREFETCH_BYTES") {
+            if let Some(n) = parse_size_bytes(&v) {
+                cfg.ram_prefetch_bytes = n;
+            }
+        }
+
         // FS-import / Z-node
         if let Ok(v) = env::var("QUARX_FS_IMPORT_USE_Z") {
             if let Some(b) = parse_bool_simple(&v) {
@@ -311,6 +518,21 @@ impl QuarxConfig {
                 cfg.analysis_fs_stats_fallback = b;
             }
         }
+        if let Ok(v) = env::var("QUARX_ANALYSIS_Z_TAR") {
+            if let Some(b) = parse_bool_simple(&v) {
+                cfg.analysis_z_tar = b;
+            }
+        }
+        if let Ok(v) = env::var("QUARX_ANALYSIS_Z_ELF") {
+            if let Some(b) = parse_bool_simple(&v) {
+                cfg.analysis_z_elf = b;
+            }
+        }
+        if let Ok(v) = env::var("QUARX_ANALYSIS_Z_JSON") {
+            if let Some(b) = parse_bool_simple(&v) {
+                cfg.analysis_z_json = b;
+            }
+        }
 
         cfg
     }
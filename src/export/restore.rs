@@ -0,0 +1,422 @@
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::types::{BlockId, BlockRef, ObjectId, OBJ_TYPE_DIR};
+use crate::codec::{DirEntry, DirEntryKind, decode_dir_meta};
+use crate::store::blockstore::{BlockStore, StoreError, StoreResult, hash_l0};
+use crate::store::decode::BlockBody;
+use crate::reader::{ObjectReader, object_info};
+
+/// Гранула поиска дыр: нулевые куски такого размера не пишутся.
+const HOLE_GRANULE: usize = 4096;
+
+/// Что делать, если путь назначения уже существует.
+///
+/// Каталоги всегда сливаются с существующими; политика касается
+/// файлов и симлинков (и файлов/симлинков на месте каталога).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverwritePolicy {
+    /// Оставить существующее, записать путь в `RestoreReport::skipped`.
+    Skip,
+    /// Заменить файл/симлинк. Каталог на месте файла — ошибка.
+    ///
+    /// Файл пишется во временный рядом и заменяет старый rename'ом
+    /// только после проверки: при ошибке старый остаётся нетронутым.
+    Overwrite,
+    /// Остановить восстановление с ошибкой AlreadyExists.
+    Error,
+}
+
+/// Параметры восстановления.
+#[derive(Debug, Clone)]
+pub struct RestoreOptions {
+    /// Ничего не писать, только посчитать отчёт.
+    pub dry_run: bool,
+    pub overwrite: OverwritePolicy,
+    /// Права из записей каталога (mode & 0o7777).
+    pub preserve_perms: bool,
+    pub preserve_mtime: bool,
+    /// uid/gid из записей (обычно требует root).
+    pub preserve_owner: bool,
+    /// Нулевые гранулы пропускать seek'ом, а не писать.
+    pub sparse: bool,
+    /// Сверять данные с hash'ами store по ходу записи.
+    pub verify: bool,
+}
+
+impl Default for RestoreOptions {
+    fn default() -> Self {
+        Self {
+            dry_run: false,
+            overwrite: OverwritePolicy::Error,
+            preserve_perms: true,
+            preserve_mtime: true,
+            preserve_owner: false,
+            sparse: true,
+            verify: true,
+        }
+    }
+}
+
+/// Итог восстановления (для dry-run — что было бы сделано).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RestoreReport {
+    pub files:    u64,
+    pub dirs:     u64,
+    pub symlinks: u64,
+    /// Логических байт данных файлов.
+    pub bytes:    u64,
+    /// Из них пропущено как дыры (sparse).
+    pub hole_bytes: u64,
+    /// Файлов/симлинков, сверенных с hash'ами store.
+    pub verified:   u64,
+    /// Файлов без записанного content hash (MultiInfo) — не сверены.
+    pub unverified: u64,
+    /// Пути, оставленные как есть по `OverwritePolicy::Skip`.
+    pub skipped: Vec<PathBuf>,
+}
+
+/// Восстановление Object'а (файла или дерева каталогов) в host FS.
+pub struct Restorer<'a, S: BlockStore> {
+    store: &'a S,
+    opts:  RestoreOptions,
+}
+
+impl<'a, S: BlockStore> Restorer<'a, S> {
+    pub fn new(store: &'a S, opts: RestoreOptions) -> Self {
+        Self { store, opts }
+    }
+
+    /// Восстановить `id` в `dest`: каталог (OBJ_TYPE_DIR) — рекурсивно,
+    /// иначе данные объекта — в файл.
+    pub fn restore(&self, id: ObjectId, dest: &Path) -> StoreResult<RestoreReport> {
+        let mut report = RestoreReport::default();
+        self.restore_object(id, dest, None, &mut report)?;
+        Ok(report)
+    }
+
+    fn restore_object(
+        &self,
+        id: BlockId,
+        dest: &Path,
+        attrs: Option<&DirEntry>,
+        report: &mut RestoreReport,
+    ) -> StoreResult<()> {
+        if let (_, _, BlockBody::Object(o)) = self.store.get_typed(id)? {
+            if o.obj_type == OBJ_TYPE_DIR {
+                let entries = decode_dir_meta(&o.meta)
+                    .ok_or(StoreError::Corrupt(format!("directory object {} has bad entries", id)))?;
+                return self.restore_dir(&entries, dest, attrs, report);
+            }
+        }
+        self.restore_file(id, dest, attrs, report)
+    }
+
+    fn restore_dir(
+        &self,
+        entries: &[DirEntry],
+        dest: &Path,
+        attrs: Option<&DirEntry>,
+        report: &mut RestoreReport,
+    ) -> StoreResult<()> {
+        match fs::symlink_metadata(dest) {
+            Ok(md) if md.is_dir() => {}
+            Ok(_) => {
+                if !self.claim(dest, report)? {
+                    return Ok(());
+                }
+                if !self.opts.dry_run {
+                    fs::remove_file(dest)?;
+                }
+                self.create_dir(dest)?;
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => self.create_dir(dest)?,
+            Err(e) => return Err(e.into()),
+        }
+
+        for e in entries {
+            check_name(&e.name)?;
+            let path = dest.join(&e.name);
+            match (e.kind, e.child) {
+                (DirEntryKind::Symlink, BlockRef::L0(target)) => self.restore_symlink(target, &path, e, report)?,
+                (DirEntryKind::Symlink, _) => {
+                    return Err(StoreError::Corrupt(format!("symlink {} does not point to L0", e.name)));
+                }
+                (_, child) => self.restore_object(ref_id(child), &path, Some(e), report)?,
+            }
+        }
+
+        report.dirs += 1;
+        // атрибуты каталога — после детей: mtime не сбивается, права не мешают записи
+        self.apply_attrs(dest, attrs, None)
+    }
+
+    fn restore_file(
+        &self,
+        id: BlockId,
+        dest: &Path,
+        attrs: Option<&DirEntry>,
+        report: &mut RestoreReport,
+    ) -> StoreResult<()> {
+        if !self.claim(dest, report)? {
+            return Ok(());
+        }
+        let mut rd = ObjectReader::open(self.store, id)?;
+        report.files += 1;
+        report.bytes += rd.len()?;
+        if self.opts.dry_run {
+            return Ok(());
+        }
+
+        // пишем рядом и подменяем rename'ом: оборванный или не прошедший
+        // проверку файл не заменяет существующий
+        let tmp = temp_path(dest);
+        let res = self
+            .write_file(&mut rd, id, &tmp, attrs, report)
+            .and_then(|()| Ok(fs::rename(&tmp, dest)?));
+        if res.is_err() {
+            let _ = fs::remove_file(&tmp);
+        }
+        res
+    }
+
+    /// Данные `rd` в новый файл `path` (+ проверка и атрибуты).
+    fn write_file(
+        &self,
+        rd: &mut ObjectReader<'_, S>,
+        id: BlockId,
+        path: &Path,
+        attrs: Option<&DirEntry>,
+        report: &mut RestoreReport,
+    ) -> StoreResult<()> {
+        let mut out = File::create_new(path)?;
+        let mut hasher = blake3::Hasher::new();
+        let mut buf = vec![0u8; 16 * HOLE_GRANULE];
+        let mut written = 0u64;
+        let mut hole_from: Option<u64> = None;
+
+        loop {
+            // дыра из рецепта (нулевой L0): пропускаем целиком, не читая нулей
+            let hole = if self.opts.sparse { rd.hole_len()? } else { 0 };
+            if hole > 0 {
+                if self.opts.verify {
+                    let zeros = [0u8; HOLE_GRANULE];
+                    let mut left = hole;
+                    while left > 0 {
+                        let k = left.min(HOLE_GRANULE as u64) as usize;
+                        hasher.update(&zeros[..k]);
+                        left -= k as u64;
+                    }
+                }
+                hole_from.get_or_insert(written);
+                report.hole_bytes += hole;
+                written += hole;
+                rd.seek(SeekFrom::Start(written))?;
+                continue;
+            }
+
+            let n = rd.read(&mut buf)?;
+            if n == 0 {
+                break;
+            }
+            if self.opts.verify {
+                hasher.update(&buf[..n]);
+            }
+            for g in buf[..n].chunks(HOLE_GRANULE) {
+                if self.opts.sparse && g.iter().all(|b| *b == 0) {
+                    hole_from.get_or_insert(written);
+                    report.hole_bytes += g.len() as u64;
+                } else {
+                    if hole_from.take().is_some() {
+                        out.seek(SeekFrom::Start(written))?;
+                    }
+                    out.write_all(g)?;
+                }
+                written += g.len() as u64;
+            }
+        }
+        // хвостовая дыра: длина файла без записи нулей
+        out.set_len(written)?;
+
+        if self.opts.verify {
+            match object_info(self.store, id)? {
+                Some(info) if info.logical_len == written && &info.hash == hasher.finalize().as_bytes() => {
+                    report.verified += 1;
+                }
+                Some(_) => {
+                    return Err(StoreError::Corrupt(format!(
+                        "content hash mismatch restoring object {}",
+                        id
+                    )));
+                }
+                None => report.unverified += 1,
+            }
+        }
+
+        self.apply_attrs(path, attrs, Some(&out))
+    }
+
+    fn restore_symlink(
+        &self,
+        target_id: BlockId,
+        dest: &Path,
+        e: &DirEntry,
+        report: &mut RestoreReport,
+    ) -> StoreResult<()> {
+        if !self.claim(dest, report)? {
+            return Ok(());
+        }
+        let (_, hash, body) = self.store.get_typed(target_id)?;
+        let BlockBody::L0(target) = body else {
+            return Err(StoreError::Corrupt(format!("symlink target {} is not L0", target_id)));
+        };
+        if self.opts.verify {
+            if hash_l0(&target) != hash {
+                return Err(StoreError::Corrupt(format!("symlink target {} hash mismatch", target_id)));
+            }
+            report.verified += 1;
+        }
+        report.symlinks += 1;
+        if self.opts.dry_run {
+            return Ok(());
+        }
+        // как и файл: новый симлинк рядом, затем rename поверх старого
+        let tmp = temp_path(dest);
+        let res = make_symlink(&target, &tmp).and_then(|()| fs::rename(&tmp, dest));
+        if let Err(e) = res {
+            let _ = fs::remove_file(&tmp);
+            return Err(e.into());
+        }
+        if self.opts.preserve_owner {
+            set_owner(dest, e)?;
+        }
+        Ok(())
+    }
+
+    /// Проверить путь назначения по политике: true — можно писать.
+    ///
+    /// Существующий файл не удаляется: его заменит rename готового.
+    fn claim(&self, path: &Path, report: &mut RestoreReport) -> StoreResult<bool> {
+        let md = match fs::symlink_metadata(path) {
+            Ok(md) => md,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(true),
+            Err(e) => return Err(e.into()),
+        };
+        let exists = || io::Error::new(io::ErrorKind::AlreadyExists, format!("{} already exists", path.display()));
+        match self.opts.overwrite {
+            OverwritePolicy::Skip => {
+                report.skipped.push(path.to_path_buf());
+                Ok(false)
+            }
+            OverwritePolicy::Error => Err(exists().into()),
+            OverwritePolicy::Overwrite if md.is_dir() => Err(exists().into()),
+            OverwritePolicy::Overwrite => Ok(true),
+        }
+    }
+
+    fn create_dir(&self, path: &Path) -> StoreResult<()> {
+        if !self.opts.dry_run {
+            fs::create_dir_all(path)?;
+        }
+        Ok(())
+    }
+
+    /// mtime, затем права и владелец (права могут закрыть файл на запись).
+    fn apply_attrs(&self, path: &Path, attrs: Option<&DirEntry>, open: Option<&File>) -> StoreResult<()> {
+        let Some(e) = attrs else {
+            return Ok(());
+        };
+        if self.opts.dry_run {
+            return Ok(());
+        }
+        if self.opts.preserve_mtime {
+            let t = entry_mtime(e);
+            match open {
+                Some(f) => f.set_modified(t)?,
+                None => File::open(path)?.set_modified(t)?,
+            }
+        }
+        if self.opts.preserve_owner {
+            set_owner(path, e)?;
+        }
+        if self.opts.preserve_perms {
+            set_mode(path, e.mode)?;
+        }
+        Ok(())
+    }
+}
+
+/// Временное имя рядом с `dest`: тот же каталог, rename атомарен.
+fn temp_path(dest: &Path) -> PathBuf {
+    static SEQ: AtomicU64 = AtomicU64::new(0);
+    let name = dest.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    let seq = SEQ.fetch_add(1, Ordering::Relaxed);
+    dest.with_file_name(format!(".{}.{}-{}.restore", name, std::process::id(), seq))
+}
+
+fn ref_id(r: BlockRef) -> BlockId {
+    match r {
+        BlockRef::L0(id) | BlockRef::Multi(id) | BlockRef::Z(id) | BlockRef::Object(id) => id,
+    }
+}
+
+/// Имя записи — ровно одна компонента пути (без выхода за каталог).
+fn check_name(name: &str) -> StoreResult<()> {
+    let bad = name.is_empty() || name == "." || name == ".." || name.contains('/') || name.contains('\0');
+    #[cfg(windows)]
+    let bad = bad || name.contains('\\');
+    if bad {
+        return Err(StoreError::Corrupt(format!("bad directory entry name {:?}", name)));
+    }
+    Ok(())
+}
+
+fn entry_mtime(e: &DirEntry) -> SystemTime {
+    let nanos = Duration::from_nanos(e.mtime_nsec as u64);
+    if e.mtime_sec >= 0 {
+        UNIX_EPOCH + Duration::from_secs(e.mtime_sec as u64) + nanos
+    } else {
+        UNIX_EPOCH - Duration::from_secs(e.mtime_sec.unsigned_abs()) + nanos
+    }
+}
+
+#[cfg(unix)]
+fn set_mode(path: &Path, mode: u32) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(mode & 0o7777))
+}
+
+#[cfg(not(unix))]
+fn set_mode(path: &Path, mode: u32) -> io::Result<()> {
+    let mut p = fs::metadata(path)?.permissions();
+    p.set_readonly(mode & 0o222 == 0);
+    fs::set_permissions(path, p)
+}
+
+#[cfg(unix)]
+fn set_owner(path: &Path, e: &DirEntry) -> io::Result<()> {
+    std::os::unix::fs::lchown(path, Some(e.uid), Some(e.gid))
+}
+
+#[cfg(not(unix))]
+fn set_owner(_path: &Path, _e: &DirEntry) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(unix)]
+fn make_symlink(target: &[u8], dest: &Path) -> io::Result<()> {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+    std::os::unix::fs::symlink(OsStr::from_bytes(target), dest)
+}
+
+#[cfg(not(unix))]
+fn make_symlink(_target: &[u8], dest: &Path) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("cannot create symlink {} on this platform", dest.display()),
+    ))
+}
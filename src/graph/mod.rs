@@ -1,3 +1,5 @@
 pub mod object_graph;
+pub mod snapshot;
 
 pub use object_graph::*;
+pub use snapshot::*;
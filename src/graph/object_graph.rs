@@ -1,9 +1,9 @@
 use std::collections::HashSet;
 
-use crate::types::{BlockId, BlockKind, BlockRef};
-use crate::codec::{ZPayload, ObjectPayload};
+use crate::types::{BlockId, BlockKind, BlockRef, OBJ_TYPE_DIR, OBJ_TYPE_FILE, OBJ_TYPE_SNAPSHOT};
+use crate::codec::{ZPayload, ObjectPayload, decode_dir_meta, decode_z_index, decode_z_span, decode_snapshot_meta, tlv_iter};
 use crate::block::multi::MultiRecipe;
-use crate::store::blockstore::{BlockStore, StoreResult, StoreError};
+use crate::store::blockstore::{BlockStore, StoreResult, StoreError, is_zero_block};
 use crate::store::decode::BlockBody;
 
 /// Результат замыкания графа относительно корня.
@@ -18,11 +18,19 @@ pub struct GraphClosure {
 /// Высокоуровневый обход ссылок (Multi/Z/Object) поверх BlockStore.
 pub struct ObjectGraph<'a, S: BlockStore> {
     store: &'a S,
+    /// Идти ли от снапшотов к родителям (вся история, а не одна версия).
+    ancestors: bool,
 }
 
 impl<'a, S: BlockStore> ObjectGraph<'a, S> {
     pub fn new(store: &'a S) -> Self {
-        Self { store }
+        Self { store, ancestors: true }
+    }
+
+    /// Включать ли в замыкание предков снапшотов (по умолчанию да).
+    pub fn with_ancestors(mut self, ancestors: bool) -> Self {
+        self.ancestors = ancestors;
+        self
     }
 
     /// Построить замыкание, начиная с Object-блока (BlockKind::Object).
@@ -40,11 +48,16 @@ impl<'a, S: BlockStore> ObjectGraph<'a, S> {
 
     /// Построить замыкание от произвольного блока (L0/Multi/Z/Object).
     pub fn compute_closure_from_block(&self, root_id: BlockId) -> StoreResult<GraphClosure> {
+        self.compute_closure_from_roots(&[root_id])
+    }
+
+    /// Общее замыкание нескольких корней (например, корни GC из ссылок).
+    pub fn compute_closure_from_roots(&self, roots: &[BlockId]) -> StoreResult<GraphClosure> {
         let mut visited: HashSet<BlockId> = HashSet::new();
         let mut order: Vec<BlockId> = Vec::new();
         let mut stack: Vec<BlockId> = Vec::new();
 
-        stack.push(root_id);
+        stack.extend(roots.iter().rev());
 
         while let Some(id) = stack.pop() {
             if !visited.insert(id) {
@@ -54,7 +67,15 @@ impl<'a, S: BlockStore> ObjectGraph<'a, S> {
             let (kind, _hash, body) = self.store.get_typed(id)?;
             order.push(id);
 
-            let children = children_from_body(kind, &body);
+            let mut children = children_from_body(kind, &body);
+            if !self.ancestors {
+                if let BlockBody::Object(op) = &body {
+                    if op.obj_type == OBJ_TYPE_SNAPSHOT {
+                        // root идёт первым, за ним родители
+                        children.truncate(1);
+                    }
+                }
+            }
             for cid in children {
                 if !visited.contains(&cid) {
                     stack.push(cid);
@@ -63,15 +84,17 @@ impl<'a, S: BlockStore> ObjectGraph<'a, S> {
         }
 
         Ok(GraphClosure {
-            roots: vec![root_id],
+            roots: roots.to_vec(),
             blocks: order,
         })
     }
 }
 
 /// Извлечь дочерние BlockId из типизированного тела блока.
-fn children_from_body(kind: BlockKind, body: &BlockBody) -> Vec<BlockId> {
-    match (kind, body) {
+///
+/// Нулевые L0 (дыры, `is_zero_block`) не возвращаются: frame'ов у них нет.
+pub fn children_from_body(kind: BlockKind, body: &BlockBody) -> Vec<BlockId> {
+    let mut out = match (kind, body) {
         (BlockKind::L0, _) => Vec::new(),
 
         (BlockKind::Multi, BlockBody::Multi(recipe)) => children_from_multi(recipe),
@@ -82,12 +105,14 @@ fn children_from_body(kind: BlockKind, body: &BlockBody) -> Vec<BlockId> {
 
         // несоответствие kind/body — считаем пустым (можно усилить позже)
         _ => Vec::new(),
-    }
+    };
+    out.retain(|id| !is_zero_block(*id));
+    out
 }
 
 fn children_from_multi(recipe: &MultiRecipe) -> Vec<BlockId> {
     match recipe {
-        MultiRecipe::Aggregate { blocks } => {
+        MultiRecipe::Aggregate { blocks } | MultiRecipe::Indexed { blocks, .. } => {
             blocks.iter().copied().collect()
         }
         MultiRecipe::CodecRecipe { blocks, .. } => {
@@ -102,6 +127,10 @@ fn children_from_multi(recipe: &MultiRecipe) -> Vec<BlockId> {
 }
 
 fn children_from_z(zp: &ZPayload) -> Vec<BlockId> {
+    // зона анализатора: L0 перечислены явно
+    if let Some(span) = decode_z_span(&zp.meta) {
+        return span.l0s;
+    }
     if zp.last_l0 < zp.first_l0 {
         return Vec::new();
     }
@@ -121,11 +150,35 @@ fn children_from_z(zp: &ZPayload) -> Vec<BlockId> {
     out
 }
 
-fn children_from_object(op: &ObjectPayload) -> Vec<BlockId> {
-    match op.root {
+fn ref_id(r: BlockRef) -> BlockId {
+    match r {
         BlockRef::L0(id)
         | BlockRef::Multi(id)
         | BlockRef::Z(id)
-        | BlockRef::Object(id) => vec![id],
+        | BlockRef::Object(id) => id,
     }
 }
+
+fn children_from_object(op: &ObjectPayload) -> Vec<BlockId> {
+    let mut out = vec![ref_id(op.root)];
+    // каталог: плюс содержимое каждой записи (файлы, подкаталоги, цели симлинков)
+    if op.obj_type == OBJ_TYPE_DIR {
+        if let Some(entries) = decode_dir_meta(&op.meta) {
+            out.extend(entries.iter().map(|e| ref_id(e.child)));
+        }
+    }
+    // файл: плюс индексы Z-блоков анализаторов (через них — сами Z-блоки)
+    if op.obj_type == OBJ_TYPE_FILE {
+        for r in decode_z_index(&op.meta).unwrap_or_default() {
+            out.push(r.index);
+            out.extend(r.by_key);
+        }
+    }
+    // снапшот: плюс родители (история)
+    if op.obj_type == OBJ_TYPE_SNAPSHOT {
+        if let Some(m) = tlv_iter(&op.meta).ok().and_then(|t| decode_snapshot_meta(&t)) {
+            out.extend(m.parents);
+        }
+    }
+    out
+}
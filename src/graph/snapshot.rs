@@ -0,0 +1,189 @@
+use std::collections::{BinaryHeap, HashSet};
+use std::io;
+
+use crate::types::{BlockRef, ObjectId, OBJ_TYPE_DIR, OBJ_TYPE_SNAPSHOT};
+use crate::codec::{
+    ObjectPayload, SnapshotMeta, tlv_iter, encode_snapshot_meta, decode_snapshot_meta,
+    encode_snapshot_generation, decode_snapshot_generation,
+};
+use crate::store::blockstore::{BlockStore, StoreError, StoreResult};
+use crate::store::decode::BlockBody;
+use crate::store::typed::TypedStore;
+use crate::reader::{DirChange, ObjectDiff, diff_dirs, diff_objects};
+
+/// Снапшот: версия объекта с родителями, временем и сообщением.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Snapshot {
+    pub id:      ObjectId,
+    /// Object с содержимым версии (файл, каталог, ...).
+    pub content: ObjectId,
+    pub meta:    SnapshotMeta,
+    /// 0 — без родителей, иначе 1 + max поколения родителей.
+    pub generation: u64,
+}
+
+/// Отличие снапшота от родителя.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SnapshotDiff {
+    /// Содержимое — данные (файл и т.п.): diff по блокам.
+    Data(ObjectDiff),
+    /// Содержимое — каталог: изменения по путям.
+    Dir(Vec<DirChange>),
+}
+
+/// Записать снапшот поверх Object'а `content`.
+///
+/// Родители из `meta.parents` должны быть снапшотами. Читатели данных
+/// проходят снапшот насквозь, как цепочку Object -> Object.
+/// Поколение снапшота считается по родителям и пишется рядом с meta.
+pub fn put_snapshot<S: BlockStore + ?Sized>(store: &mut S, content: ObjectId, meta: &SnapshotMeta) -> StoreResult<ObjectId> {
+    let mut generation = 0;
+    for p in &meta.parents {
+        let Some(parent) = get_snapshot(store, *p)? else {
+            return Err(StoreError::Io(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("parent {} is not a snapshot", p),
+            )));
+        };
+        generation = generation.max(parent.generation + 1);
+    }
+    let mut tlvs = encode_snapshot_meta(meta);
+    tlvs.extend_from_slice(&encode_snapshot_generation(generation));
+    store.put_object(&ObjectPayload {
+        root:     BlockRef::Object(content),
+        obj_type: OBJ_TYPE_SNAPSHOT,
+        meta:     tlvs,
+    })
+}
+
+/// Снапшот по id (None — `id` не снапшот).
+pub fn get_snapshot<S: BlockStore + ?Sized>(store: &S, id: ObjectId) -> StoreResult<Option<Snapshot>> {
+    let BlockBody::Object(o) = store.get_typed(id)?.2 else {
+        return Ok(None);
+    };
+    if o.obj_type != OBJ_TYPE_SNAPSHOT {
+        return Ok(None);
+    }
+    let BlockRef::Object(content) = o.root else {
+        return Err(StoreError::Corrupt(format!("snapshot {} root is not an Object", id)));
+    };
+    let tlvs = tlv_iter(&o.meta)?;
+    let bad = || StoreError::Corrupt(format!("snapshot {} has bad meta", id));
+    let meta = decode_snapshot_meta(&tlvs).ok_or_else(bad)?;
+    let generation = decode_snapshot_generation(&tlvs).ok_or_else(bad)?;
+    Ok(Some(Snapshot { id, content, meta, generation }))
+}
+
+/// История снапшота `id`: он сам и все предки, каждый один раз,
+/// от новых к старым по поколению (при равенстве — по id): потомок
+/// всегда раньше предка, даже если часы при записи шли вразнобой.
+pub fn snapshot_history<S: BlockStore + ?Sized>(store: &S, id: ObjectId) -> StoreResult<Vec<Snapshot>> {
+    let mut out = Vec::new();
+    walk_history(store, id, |s| {
+        out.push(s);
+        false
+    })?;
+    Ok(out)
+}
+
+/// Ближайший общий предок снапшотов `a` и `b` (снапшот сам себе предок).
+///
+/// Из общих предков выбирается первый в обходе истории `b`, т.е. с
+/// наибольшим поколением; время снапшотов не учитывается.
+pub fn common_ancestor<S: BlockStore + ?Sized>(store: &S, a: ObjectId, b: ObjectId) -> StoreResult<Option<ObjectId>> {
+    let mut seen = HashSet::new();
+    walk_history(store, a, |s| {
+        seen.insert(s.id);
+        false
+    })?;
+    let mut found = None;
+    walk_history(store, b, |s| {
+        if seen.contains(&s.id) {
+            found = Some(s.id);
+        }
+        found.is_some()
+    })?;
+    Ok(found)
+}
+
+/// Diff содержимого снапшота с первым родителем (None — начало истории).
+///
+/// Каталоги сравниваются по именам записей (`diff_dirs`), прочее — по
+/// данным. Каталог против не-каталога — ошибка.
+pub fn diff_with_parent<S: BlockStore + ?Sized>(store: &S, id: ObjectId) -> StoreResult<Option<SnapshotDiff>> {
+    let snap = get_snapshot(store, id)?
+        .ok_or(StoreError::Corrupt(format!("object {} is not a snapshot", id)))?;
+    let Some(parent) = snap.meta.parents.first() else {
+        return Ok(None);
+    };
+    let parent = get_snapshot(store, *parent)?
+        .ok_or(StoreError::Corrupt(format!("parent {} of {} is not a snapshot", parent, id)))?;
+    let dir_a = store.get_object(parent.content)?.obj_type == OBJ_TYPE_DIR;
+    let dir_b = store.get_object(snap.content)?.obj_type == OBJ_TYPE_DIR;
+    match (dir_a, dir_b) {
+        (true, true) => Ok(Some(SnapshotDiff::Dir(diff_dirs(store, parent.content, snap.content)?))),
+        (false, false) => Ok(Some(SnapshotDiff::Data(diff_objects(store, parent.content, snap.content)?))),
+        _ => Err(StoreError::Io(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("snapshot {} and its parent {} differ in content kind (directory vs data)", id, parent.id),
+        ))),
+    }
+}
+
+/// Обход предков от новых к старым; `f` возвращает true — остановиться.
+fn walk_history<S, F>(store: &S, id: ObjectId, mut f: F) -> StoreResult<()>
+where
+    S: BlockStore + ?Sized,
+    F: FnMut(Snapshot) -> bool,
+{
+    let mut seen = HashSet::new();
+    let mut heap = BinaryHeap::new();
+    let first = get_snapshot(store, id)?
+        .ok_or(StoreError::Corrupt(format!("object {} is not a snapshot", id)))?;
+    seen.insert(id);
+    heap.push(Queued(first));
+
+    while let Some(Queued(s)) = heap.pop() {
+        for p in &s.meta.parents {
+            if !seen.insert(*p) {
+                continue;
+            }
+            let parent = get_snapshot(store, *p)?
+                .ok_or(StoreError::Corrupt(format!("parent {} of {} is not a snapshot", p, s.id)))?;
+            heap.push(Queued(parent));
+        }
+        if f(s) {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Снапшот в очереди обхода: сверху самый новый.
+struct Queued(Snapshot);
+
+impl Queued {
+    fn key(&self) -> (u64, ObjectId) {
+        (self.0.generation, self.0.id)
+    }
+}
+
+impl PartialEq for Queued {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Queued {}
+
+impl PartialOrd for Queued {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Queued {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.key().cmp(&other.key())
+    }
+}
@@ -0,0 +1,106 @@
+use crate::import::chunker::Chunker;
+
+/// Gear-таблица FastCDC: 256 псевдослучайных u64.
+///
+/// Генерируется детерминированно (splitmix64 от фиксированного seed),
+/// поэтому границы чанков одинаковы на любой сборке/платформе.
+const GEAR: [u64; 256] = gear_table();
+
+const fn gear_table() -> [u64; 256] {
+    let mut t = [0u64; 256];
+    let mut x: u64 = 0x5158_5254_4F52_4344; // "QXRTORCD"
+    let mut i = 0;
+    while i < 256 {
+        x = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = x;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        t[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    t
+}
+
+/// Маска из `bits` единиц в старших битах.
+///
+/// Бит k gear-хэша зависит от последних k+1 байт, поэтому старшие биты
+/// дают окно ~64 байта, а не `bits`.
+fn top_mask(bits: u32) -> u64 {
+    let bits = bits.clamp(1, 63);
+    ((1u64 << bits) - 1) << (64 - bits)
+}
+
+/// Content-defined chunking (FastCDC, normalized chunking уровня 1).
+///
+/// До `avg` действует более строгая маска (bits+1), после — более мягкая
+/// (bits-1): распределение длин прижимается к `avg`. Вставка байт
+/// сдвигает границы только локально, дальше чанки совпадают.
+#[derive(Debug, Clone, Copy)]
+pub struct FastCdcChunker {
+    min: usize,
+    avg: usize,
+    max: usize,
+    mask_s: u64,
+    mask_l: u64,
+}
+
+impl FastCdcChunker {
+    /// Размеры приводятся к 1 <= min <= avg <= max.
+    pub fn new(min: usize, avg: usize, max: usize) -> Self {
+        let min = min.max(1);
+        let avg = avg.max(min);
+        let max = max.max(avg);
+        let bits = usize::BITS - 1 - avg.leading_zeros(); // floor(log2(avg))
+        Self {
+            min,
+            avg,
+            max,
+            mask_s: top_mask(bits + 1),
+            mask_l: top_mask(bits.saturating_sub(1)),
+        }
+    }
+
+    pub fn min_size(&self) -> usize {
+        self.min
+    }
+
+    pub fn avg_size(&self) -> usize {
+        self.avg
+    }
+
+    pub fn max_size(&self) -> usize {
+        self.max
+    }
+}
+
+impl Chunker for FastCdcChunker {
+    fn max_chunk(&self) -> usize {
+        self.max
+    }
+
+    fn next_cut(&self, data: &[u8], _eof: bool) -> usize {
+        let n = data.len().min(self.max);
+        if n <= self.min {
+            return n;
+        }
+        let normal = self.avg.min(n);
+
+        let mut h: u64 = 0;
+        let mut i = self.min;
+        while i < normal {
+            h = (h << 1).wrapping_add(GEAR[data[i] as usize]);
+            if h & self.mask_s == 0 {
+                return i + 1;
+            }
+            i += 1;
+        }
+        while i < n {
+            h = (h << 1).wrapping_add(GEAR[data[i] as usize]);
+            if h & self.mask_l == 0 {
+                return i + 1;
+            }
+            i += 1;
+        }
+        n
+    }
+}
@@ -0,0 +1,51 @@
+use crate::config::{QuarxConfig, ChunkerKind};
+use crate::import::cdc::FastCdcChunker;
+
+/// Нарезка потока байт на L0-чанки.
+///
+/// Импортёр держит окно не меньше `max_chunk()` байт (или весь хвост
+/// на EOF) и спрашивает у чанкера длину следующего чанка.
+pub trait Chunker: Send + Sync {
+    /// Максимальная длина чанка (размер окна импортёра).
+    fn max_chunk(&self) -> usize;
+
+    /// Длина следующего чанка в начале `data`.
+    ///
+    /// `data.len() <= max_chunk()`; если `eof == false`, то окно полное.
+    /// Для непустого `data` результат в диапазоне 1..=data.len().
+    fn next_cut(&self, data: &[u8], eof: bool) -> usize;
+}
+
+/// Фиксированные чанки по `size` байт (последний — хвост).
+#[derive(Debug, Clone, Copy)]
+pub struct FixedChunker {
+    size: usize,
+}
+
+impl FixedChunker {
+    pub fn new(size: usize) -> Self {
+        Self { size: size.max(1) }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+}
+
+impl Chunker for FixedChunker {
+    fn max_chunk(&self) -> usize {
+        self.size
+    }
+
+    fn next_cut(&self, data: &[u8], _eof: bool) -> usize {
+        data.len().min(self.size)
+    }
+}
+
+/// Чанкер, выбранный в конфиге (`chunker=fixed|fastcdc`).
+pub fn chunker_from_config(cfg: &QuarxConfig) -> Box<dyn Chunker> {
+    match cfg.chunker {
+        ChunkerKind::Fixed => Box::new(FixedChunker::new(cfg.l0_chunk)),
+        ChunkerKind::FastCdc => Box::new(FastCdcChunker::new(cfg.cdc_min, cfg.cdc_avg, cfg.cdc_max)),
+    }
+}
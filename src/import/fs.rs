@@ -0,0 +1,336 @@
+use std::fs::{self, File, FileType, Metadata};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use smallvec::SmallVec;
+
+use crate::types::{BlockId, BlockRef, ObjectId, ZNodeMeta, OBJ_TYPE_DIR};
+use crate::block::multi::{MultiRecipe, MultiInfo};
+use crate::codec::{ObjectPayload, DirEntry, DirEntryKind, encode_dir_entries};
+use crate::config::QuarxConfig;
+use crate::store::blockstore::{BlockStore, StoreError, StoreResult, hash_l0, hash_multi_info};
+use crate::import::importer::{Importer, ImportStats};
+use crate::analysis::znode::put_znode;
+use crate::analysis::analyzer::ZRegistry;
+
+/// Почему запись host FS не попала в импорт.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SkipReason {
+    /// Имя начинается с '.' (`import_skip_hidden`).
+    Hidden,
+    /// Симлинк (`import_skip_symlink`).
+    Symlink,
+    /// Файл нулевой длины (`import_skip_zero`).
+    Zero,
+    /// Файл меньше `import_min_file_size`.
+    TooSmall,
+    /// Блочное/символьное устройство (`import_skip_devices`).
+    Device,
+    /// FIFO, сокет и прочее (`import_skip_special`).
+    Special,
+    /// Тип записи не представим в store (устройство/спец-файл при
+    /// выключенном skip).
+    Unsupported,
+    /// Имя не UTF-8.
+    BadName,
+    /// Ошибка чтения; импорт остальных записей продолжается.
+    Error(String),
+}
+
+/// Пропущенная запись.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Skipped {
+    pub path:   PathBuf,
+    pub reason: SkipReason,
+}
+
+/// Итог импорта дерева каталогов.
+#[derive(Debug, Clone, Default)]
+pub struct FsImportSummary {
+    /// Импортировано файлов / каталогов (включая корень).
+    pub files: u64,
+    pub dirs:  u64,
+    /// Файлов, получивших Z-node (см. `analysis::put_znode`).
+    pub znodes: u64,
+    /// Суммарная статистика импорта данных файлов.
+    pub stats: ImportStats,
+    pub skipped: Vec<Skipped>,
+}
+
+impl FsImportSummary {
+    /// Сколько записей пропущено по данной причине.
+    pub fn skipped_for(&self, reason: &SkipReason) -> usize {
+        self.skipped
+            .iter()
+            .filter(|s| match (&s.reason, reason) {
+                (SkipReason::Error(_), SkipReason::Error(_)) => true,
+                (a, b) => a == b,
+            })
+            .count()
+    }
+}
+
+/// Результат: Object корневого каталога + сводка.
+#[derive(Debug, Clone)]
+pub struct FsImportResult {
+    pub root:    ObjectId,
+    pub summary: FsImportSummary,
+}
+
+/// Импорт дерева каталогов host FS по политике `import_*` из конфига.
+///
+/// Каждый обычный файл — Object (OBJ_TYPE_FILE, см. `Importer`), каждый
+/// каталог — Object с OBJ_TYPE_DIR: записи `codec::DirEntry` в meta,
+/// root — общее пустое дерево данных. Записи каталога отсортированы по имени.
+///
+/// Симлинки при `import_skip_symlink = false` не разворачиваются:
+/// запись Symlink ссылается на L0 с целью ссылки.
+///
+/// Файлы от `fs_import_z_threshold` блоков получают Z-node с ZNodeMeta;
+/// запись каталога тогда ссылается на Z-node, а не на Object файла.
+/// Анализаторы из `set_analyzers` прогоняются по каждому файлу.
+pub struct FsImporter<'a, S: BlockStore> {
+    store: &'a mut S,
+    cfg:   QuarxConfig,
+    empty_root: Option<BlockId>,
+    analyzers:  ZRegistry,
+}
+
+impl<'a, S: BlockStore> FsImporter<'a, S> {
+    pub fn new(store: &'a mut S, cfg: &QuarxConfig) -> Self {
+        Self {
+            store,
+            cfg: cfg.clone(),
+            empty_root: None,
+            analyzers: ZRegistry::from_config(cfg),
+        }
+    }
+
+    /// Анализаторы содержимого файлов (по умолчанию — `ZRegistry::from_config`).
+    pub fn set_analyzers(&mut self, analyzers: &ZRegistry) {
+        self.analyzers = analyzers.clone();
+    }
+
+    /// Импортировать каталог `path` со всем содержимым.
+    pub fn import_path(&mut self, path: &Path) -> StoreResult<FsImportResult> {
+        let md = fs::metadata(path)?;
+        if !md.is_dir() {
+            return Err(StoreError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("{} is not a directory", path.display()),
+            )));
+        }
+
+        let mut summary = FsImportSummary::default();
+        let root = self.import_dir(path, &mut summary)?;
+        Ok(FsImportResult { root, summary })
+    }
+
+    fn import_dir(&mut self, dir: &Path, summary: &mut FsImportSummary) -> StoreResult<ObjectId> {
+        let mut names: Vec<_> = fs::read_dir(dir)?
+            .map(|e| e.map(|e| e.file_name()))
+            .collect::<Result<_, _>>()?;
+        names.sort();
+
+        let mut entries = Vec::new();
+        for os_name in names {
+            let path = dir.join(&os_name);
+            let Some(name) = os_name.to_str().map(str::to_owned) else {
+                summary.skipped.push(Skipped { path, reason: SkipReason::BadName });
+                continue;
+            };
+            match self.import_entry(&path, name, summary) {
+                Ok(Some(e)) => entries.push(e),
+                Ok(None) => {}
+                Err(e) => summary.skipped.push(Skipped { path, reason: SkipReason::Error(e.to_string()) }),
+            }
+        }
+
+        let root = BlockRef::Multi(self.empty_root()?);
+        let id = self.store.put_object(&ObjectPayload {
+            root,
+            obj_type: OBJ_TYPE_DIR,
+            meta: encode_dir_entries(&entries),
+        })?;
+        summary.dirs += 1;
+        summary.stats.blocks_written += 1;
+        Ok(id)
+    }
+
+    /// Одна запись каталога: Some — импортирована, None — пропущена (в summary).
+    fn import_entry(
+        &mut self,
+        path: &Path,
+        name: String,
+        summary: &mut FsImportSummary,
+    ) -> StoreResult<Option<DirEntry>> {
+        let skip = |summary: &mut FsImportSummary, reason| {
+            summary.skipped.push(Skipped { path: path.to_path_buf(), reason });
+            Ok(None)
+        };
+
+        if self.cfg.import_skip_hidden && name.starts_with('.') {
+            return skip(summary, SkipReason::Hidden);
+        }
+
+        let md = fs::symlink_metadata(path)?;
+        let ft = md.file_type();
+
+        if ft.is_symlink() {
+            if self.cfg.import_skip_symlink {
+                return skip(summary, SkipReason::Symlink);
+            }
+            let target = link_target_bytes(&fs::read_link(path)?);
+            let id = match self.store.find_hash(&hash_l0(&target))? {
+                Some(id) => id,
+                None => {
+                    summary.stats.blocks_written += 1;
+                    self.store.put_l0(&target)?
+                }
+            };
+            return Ok(Some(entry(name, DirEntryKind::Symlink, &md, target.len() as u64, BlockRef::L0(id))));
+        }
+        if ft.is_dir() {
+            let id = self.import_dir(path, summary)?;
+            return Ok(Some(entry(name, DirEntryKind::Dir, &md, 0, BlockRef::Object(id))));
+        }
+        if !ft.is_file() {
+            let reason = match (is_device(&ft), self.cfg.import_skip_devices, self.cfg.import_skip_special) {
+                (true, true, _) => SkipReason::Device,
+                (false, _, true) => SkipReason::Special,
+                _ => SkipReason::Unsupported,
+            };
+            return skip(summary, reason);
+        }
+
+        if let Some(reason) = self.size_policy(&md) {
+            return skip(summary, reason);
+        }
+
+        let file = File::open(path)?;
+        let mut imp = Importer::new(&mut *self.store, &self.cfg);
+        imp.set_analyzers(&self.analyzers);
+        let r = imp.import_reader(file)?;
+        add_stats(&mut summary.stats, &r.stats);
+        summary.files += 1;
+
+        let mut child = r.object_id;
+        if self.use_znode(r.stats.chunks) {
+            let meta = ZNodeMeta {
+                size_bytes: r.stats.bytes_in,
+                blocks:     r.stats.chunks.min(u32::MAX as u64) as u32,
+            };
+            child = put_znode(&mut *self.store, r.object_id, &meta)?;
+            summary.znodes += 1;
+            summary.stats.blocks_written += 1;
+        }
+        Ok(Some(entry(name, DirEntryKind::File, &md, r.stats.bytes_in, BlockRef::Object(child))))
+    }
+
+    /// Z-node для файла из `chunks` L0: включён анализ и импорт Z,
+    /// и файл не меньше порога `fs_import_z_threshold` (в блоках).
+    fn use_znode(&self, chunks: u64) -> bool {
+        self.cfg.analysis_enable_znode && self.cfg.fs_import_use_z && chunks >= self.cfg.fs_import_z_threshold
+    }
+
+    fn size_policy(&self, md: &Metadata) -> Option<SkipReason> {
+        let len = md.len();
+        if len == 0 && self.cfg.import_skip_zero {
+            return Some(SkipReason::Zero);
+        }
+        if len < self.cfg.import_min_file_size {
+            return Some(SkipReason::TooSmall);
+        }
+        None
+    }
+
+    /// Пустое дерево данных для каталогов: одно на store (dedup по hash).
+    fn empty_root(&mut self) -> StoreResult<BlockId> {
+        if let Some(id) = self.empty_root {
+            return Ok(id);
+        }
+        let recipe = MultiRecipe::Indexed {
+            blocks: SmallVec::new(),
+            ends:   Vec::new(),
+        };
+        let info = MultiInfo {
+            logical_len: 0,
+            hash:        *blake3::hash(&[]).as_bytes(),
+        };
+        let id = match self.store.find_hash(&hash_multi_info(&recipe, &info)?)? {
+            Some(id) => id,
+            None => self.store.put_multi_info(&recipe, &info)?,
+        };
+        self.empty_root = Some(id);
+        Ok(id)
+    }
+}
+
+/// Запись каталога с атрибутами из метаданных host FS.
+fn entry(name: String, kind: DirEntryKind, md: &Metadata, size: u64, child: BlockRef) -> DirEntry {
+    let (mtime_sec, mtime_nsec) = match md.modified() {
+        Ok(t) => match t.duration_since(UNIX_EPOCH) {
+            Ok(d) => (d.as_secs() as i64, d.subsec_nanos()),
+            // до epoch: секунды вниз, наносекунды вверх (как st_mtim)
+            Err(e) => {
+                let d = e.duration();
+                match d.subsec_nanos() {
+                    0 => (-(d.as_secs() as i64), 0),
+                    n => (-(d.as_secs() as i64) - 1, 1_000_000_000 - n),
+                }
+            }
+        },
+        Err(_) => (0, 0),
+    };
+    let (mode, uid, gid) = owner_mode(md);
+    DirEntry { name, kind, mode, uid, gid, mtime_sec, mtime_nsec, size, child }
+}
+
+#[cfg(unix)]
+fn owner_mode(md: &Metadata) -> (u32, u32, u32) {
+    use std::os::unix::fs::MetadataExt;
+    (md.mode(), md.uid(), md.gid())
+}
+
+#[cfg(not(unix))]
+fn owner_mode(md: &Metadata) -> (u32, u32, u32) {
+    let mode = match (md.is_dir(), md.permissions().readonly()) {
+        (true, _) => 0o040755,
+        (false, true) => 0o100444,
+        (false, false) => 0o100644,
+    };
+    (mode, 0, 0)
+}
+
+#[cfg(unix)]
+fn link_target_bytes(target: &Path) -> Vec<u8> {
+    use std::os::unix::ffi::OsStrExt;
+    target.as_os_str().as_bytes().to_vec()
+}
+
+#[cfg(not(unix))]
+fn link_target_bytes(target: &Path) -> Vec<u8> {
+    target.to_string_lossy().into_owned().into_bytes()
+}
+
+fn add_stats(acc: &mut ImportStats, s: &ImportStats) {
+    acc.bytes_in += s.bytes_in;
+    acc.chunks += s.chunks;
+    acc.dedup_hits += s.dedup_hits;
+    acc.blocks_written += s.blocks_written;
+    acc.bytes_written += s.bytes_written;
+    acc.zblocks += s.zblocks;
+    acc.zero_chunks += s.zero_chunks;
+    acc.analyzers_dropped += s.analyzers_dropped;
+}
+
+#[cfg(unix)]
+fn is_device(ft: &FileType) -> bool {
+    use std::os::unix::fs::FileTypeExt;
+    ft.is_block_device() || ft.is_char_device()
+}
+
+#[cfg(not(unix))]
+fn is_device(_ft: &FileType) -> bool {
+    false
+}
@@ -0,0 +1,474 @@
+use std::collections::BTreeMap;
+use std::io::{self, Read};
+use std::sync::{mpsc, Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::types::{BlockId, BlockRef, ObjectId, OBJ_TYPE_FILE};
+use crate::codec::{ObjectPayload, encode_z_index};
+use crate::config::QuarxConfig;
+use crate::store::blockstore::{BlockStore, StoreError, StoreResult, PreparedL0, zero_l0_id};
+use crate::import::chunker::{Chunker, chunker_from_config};
+use crate::import::tree::TreeBuilder;
+use crate::analysis::analyzer::{ZRegistry, ZScanSet};
+
+/// Статистика одного импорта.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ImportStats {
+    /// Прочитано байт входа.
+    pub bytes_in: u64,
+    /// Нарезано L0-чанков.
+    pub chunks: u64,
+    /// Чанков, уже лежавших в store (dedup по hash).
+    pub dedup_hits: u64,
+    /// Записано новых блоков (L0 + Multi + Object).
+    pub blocks_written: u64,
+    /// Записано новых байт данных (сырые L0).
+    pub bytes_written: u64,
+    /// Зон, найденных анализаторами (Z-блоков в meta объекта).
+    pub zblocks: u64,
+    /// Нулевых чанков: в рецепте дыра (`zero_block_id`), frame не пишется.
+    pub zero_chunks: u64,
+    /// Анализаторов, снятых с объекта по лимиту памяти (`import_mem_limit`).
+    pub analyzers_dropped: u64,
+}
+
+/// Результат импорта: Object, его корень и статистика.
+#[derive(Debug, Clone)]
+pub struct ImportResult {
+    pub object_id: ObjectId,
+    pub root:      BlockRef,
+    pub stats:     ImportStats,
+}
+
+/// Импорт байтов в store: нарезка на L0, dedup, дерево Multi, Object.
+///
+/// Чанкер берётся из конфига (`chunker_from_config`): фиксированный
+/// по `l0_chunk` или FastCDC по `cdc_min/avg/max`. L0 собираются
+/// в сбалансированное дерево Indexed-узлов с fanout `multi_fanout`.
+///
+/// Содержимое проходит через анализаторы из `set_analyzers`; их зоны
+/// пишутся Z-блоками, индексы которых добавляются в meta Object'а
+/// (`codec::encode_z_index`).
+///
+/// Вход читается потоком: в памяти окно в два максимальных чанка,
+/// открытые уровни дерева и состояние анализаторов — не сам вход и не
+/// список его L0. Анализаторы, вылезшие за `import_mem_limit`, снимаются.
+/// Ход импорта виден через `set_progress`, прервать — `set_cancel`.
+pub struct Importer<'a, S: BlockStore> {
+    store:    &'a mut S,
+    chunker:  Box<dyn Chunker>,
+    fanout:   usize,
+    obj_type: u32,
+    meta:     Vec<u8>,
+    analyzers: ZRegistry,
+    mem_limit: u64,
+    workers:  usize,
+    progress: Option<Progress>,
+    cancel:   Option<Arc<AtomicBool>>,
+}
+
+/// Колбэк прогресса и порог следующего вызова (по `bytes_in`).
+struct Progress {
+    every: u64,
+    next:  u64,
+    f:     Box<dyn FnMut(&ImportStats) + Send>,
+}
+
+impl<'a, S: BlockStore> Importer<'a, S> {
+    pub fn new(store: &'a mut S, cfg: &QuarxConfig) -> Self {
+        let mut imp = Self::with_chunker(store, chunker_from_config(cfg));
+        imp.set_fanout(cfg.multi_fanout);
+        imp.analyzers = ZRegistry::from_config(cfg);
+        imp.mem_limit = cfg.import_mem_limit;
+        imp.set_workers(cfg.import_workers);
+        imp
+    }
+
+    pub fn with_chunker(store: &'a mut S, chunker: Box<dyn Chunker>) -> Self {
+        Self {
+            store,
+            chunker,
+            fanout: QuarxConfig::default().multi_fanout,
+            obj_type: OBJ_TYPE_FILE,
+            meta: Vec::new(),
+            analyzers: ZRegistry::default(),
+            mem_limit: QuarxConfig::default().import_mem_limit,
+            workers: 1,
+            progress: None,
+            cancel: None,
+        }
+    }
+
+    /// Fanout дерева Multi (минимум 2).
+    pub fn set_fanout(&mut self, fanout: usize) {
+        self.fanout = fanout.max(2);
+    }
+
+    /// obj_type создаваемого Object (по умолчанию OBJ_TYPE_FILE).
+    pub fn set_obj_type(&mut self, obj_type: u32) {
+        self.obj_type = obj_type;
+    }
+
+    /// meta создаваемого Object (по умолчанию пусто).
+    /// Z-ссылки анализаторов дописываются после неё TLV-записями.
+    pub fn set_meta(&mut self, meta: Vec<u8>) {
+        self.meta = meta;
+    }
+
+    /// Анализаторы содержимого (`new` — встроенные из конфига,
+    /// `with_chunker` — нет).
+    pub fn set_analyzers(&mut self, analyzers: &ZRegistry) {
+        self.analyzers = analyzers.clone();
+    }
+
+    /// Лимит памяти импорта (байт, 0 — без лимита); см. `import_mem_limit`.
+    ///
+    /// Окно чтения больше лимита — ошибка импорта; очередь пула рабочих
+    /// (`set_workers`) укорачивается под лимит, остаток делят анализаторы.
+    /// Проход анализатора, не уложившийся в остаток, снимается целиком:
+    /// у объекта не будет ни одной зоны этого анализатора, найденные до
+    /// снятия теряются (`ImportStats::analyzers_dropped`).
+    pub fn set_mem_limit(&mut self, bytes: u64) {
+        self.mem_limit = bytes;
+    }
+
+    /// Рабочих потоков для hash и кодирования L0 (0 — по числу ядер).
+    ///
+    /// Чанки режутся и пишутся в store по порядку в вызывающем потоке,
+    /// поэтому результат (id, frame'ы, статистика) тот же, что при 1.
+    /// `new` берёт `import_workers`, `with_chunker` — 1.
+    pub fn set_workers(&mut self, workers: usize) {
+        self.workers = match workers {
+            0 => std::thread::available_parallelism().map_or(1, |n| n.get()),
+            n => n,
+        };
+    }
+
+    /// Колбэк прогресса: вызывается со статистикой на текущий момент,
+    /// когда прочитано очередные `every` байт входа (0 — на каждом чанке),
+    /// и один раз в конце импорта.
+    pub fn set_progress(&mut self, every: u64, f: impl FnMut(&ImportStats) + Send + 'static) {
+        self.progress = Some(Progress { every, next: every, f: Box::new(f) });
+    }
+
+    /// Флаг отмены: проверяется перед каждым чанком, импорт завершится
+    /// `StoreError::Cancelled`. Уже записанные блоки остаются в store
+    /// без ссылок на них.
+    pub fn set_cancel(&mut self, flag: Arc<AtomicBool>) {
+        self.cancel = Some(flag);
+    }
+
+    pub fn import_bytes(&mut self, data: &[u8]) -> StoreResult<ImportResult> {
+        self.import_reader(data)
+    }
+
+    pub fn import_reader<R: Read>(&mut self, r: R) -> StoreResult<ImportResult> {
+        let mut stats = ImportStats::default();
+        let mut tree = TreeBuilder::new(self.fanout);
+
+        let max = self.chunker.max_chunk().max(1);
+        // чанков в работе у пула: по паре на поток, сколько влезает в лимит;
+        // каждый ещё раз копируется в очереди hash дерева и анализаторов
+        let mut depth = if self.workers > 1 { self.workers * 2 } else { 0 };
+        if self.mem_limit > 0 {
+            let room = self.mem_limit.saturating_sub(max as u64 * 2) / (max as u64 * 2);
+            depth = depth.min(room.min(usize::MAX as u64) as usize);
+        }
+        if depth < 2 {
+            depth = 0;
+        }
+        let window = max as u64 * (2 + 2 * depth as u64);
+        let budget = match self.mem_limit {
+            0 => None,
+            limit if limit < window => {
+                return Err(StoreError::Io(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("import window of {} bytes exceeds memory limit of {} bytes", window, limit),
+                )));
+            }
+            limit => Some(limit - window),
+        };
+        let mut zscan = ZScanSet::new(&self.analyzers, budget);
+        let mut input = Input { r, buf: Vec::with_capacity(max * 2), eof: false, max };
+
+        // первые чанки — в своём потоке: на мелких входах пул не поднимаем
+        let mut cut = self.next_cut(&mut input, &mut stats)?;
+        while let Some(n) = cut {
+            if depth > 0 && stats.chunks >= depth as u64 {
+                break;
+            }
+            let id = self.put_chunk(&input.buf[..n], &mut stats)?;
+            self.commit(&mut tree, &mut zscan, id, &input.buf[..n], &mut stats)?;
+            input.buf.drain(..n);
+            cut = self.next_cut(&mut input, &mut stats)?;
+        }
+        if let Some(n) = cut {
+            self.pipeline(&mut input, n, depth, &mut tree, &mut zscan, &mut stats)?;
+        }
+
+        let top = tree.finish(&mut *self.store)?;
+        stats.blocks_written += top.multis;
+
+        let zidx = zscan.finish(&mut *self.store, top.root, &mut stats)?;
+        let mut meta = self.meta.clone();
+        meta.extend_from_slice(&encode_z_index(&zidx));
+
+        let root = BlockRef::Multi(top.root);
+        let object_id = self.store.put_object(&ObjectPayload {
+            root,
+            obj_type: self.obj_type,
+            meta,
+        })?;
+        stats.blocks_written += 1;
+
+        if let Some(p) = &mut self.progress {
+            (p.f)(&stats);
+        }
+        Ok(ImportResult { object_id, root, stats })
+    }
+
+    /// Длина следующего чанка в начале `input.buf` (None — вход кончился).
+    fn next_cut<R: Read>(&mut self, input: &mut Input<R>, stats: &mut ImportStats) -> StoreResult<Option<usize>> {
+        if self.cancel.as_ref().is_some_and(|c| c.load(Ordering::Relaxed)) {
+            return Err(StoreError::Cancelled);
+        }
+        let max = input.max;
+        let buf = &mut input.buf;
+
+        // добираем окно до max (или до EOF)
+        while !input.eof && buf.len() < max {
+            let old = buf.len();
+            buf.resize(max * 2, 0);
+            match input.r.read(&mut buf[old..]) {
+                Ok(0) => {
+                    buf.truncate(old);
+                    input.eof = true;
+                }
+                Ok(n) => {
+                    buf.truncate(old + n);
+                    stats.bytes_in += n as u64;
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => buf.truncate(old),
+                Err(e) => return Err(StoreError::Io(e)),
+            }
+        }
+
+        if buf.is_empty() {
+            return Ok(None);
+        }
+
+        let window = &buf[..buf.len().min(max)];
+        let cut = self.chunker.next_cut(window, input.eof && buf.len() <= max);
+        if cut == 0 || cut > window.len() {
+            return Err(StoreError::Corrupt(format!("chunker returned bad cut {}", cut)));
+        }
+        Ok(Some(cut))
+    }
+
+    /// Остаток входа через пул: рабочие считают hash и собирают frame'ы
+    /// (`PreparedL0`), здесь — нарезка и запись в store строго по порядку.
+    /// Hash узлов дерева (`TreeHasher`) и анализаторы получают байты чанков
+    /// в своих потоках; поток записи ждёт только hash закрываемого узла.
+    /// `first` — длина уже найденного очередного чанка.
+    fn pipeline<R: Read>(
+        &mut self,
+        input: &mut Input<R>,
+        first: usize,
+        depth: usize,
+        tree: &mut TreeBuilder,
+        zscan: &mut ZScanSet,
+        stats: &mut ImportStats,
+    ) -> StoreResult<()> {
+        let (job_tx, job_rx) = mpsc::sync_channel::<(u64, Vec<u8>)>(depth);
+        let (done_tx, done_rx) = mpsc::channel::<(u64, Prepared)>();
+        let job_rx = Mutex::new(job_rx);
+        let mut hasher = tree
+            .take_hasher()
+            .ok_or(StoreError::Corrupt("tree hasher is already taken".into()))?;
+
+        std::thread::scope(|scope| {
+            let (data_tx, data_rx) = mpsc::sync_channel::<Arc<[u8]>>(depth);
+            let (node_tx, node_rx) = mpsc::channel::<[u8; 32]>();
+            let hashing = scope.spawn(move || {
+                let mut closed = Vec::new();
+                for data in data_rx {
+                    hasher.push(&data, &mut closed);
+                    for h in closed.drain(..) {
+                        let _ = node_tx.send(h);
+                    }
+                }
+                hasher
+            });
+            let scan_tx = zscan.is_active().then(|| {
+                let (tx, rx) = mpsc::sync_channel::<Arc<[u8]>>(depth);
+                scope.spawn(move || {
+                    for data in rx {
+                        zscan.push(&data);
+                    }
+                });
+                tx
+            });
+
+            for _ in 0..self.workers {
+                let (jobs, done) = (&job_rx, done_tx.clone());
+                scope.spawn(move || loop {
+                    let job = match jobs.lock() {
+                        Ok(rx) => rx.recv(),
+                        Err(_) => return,
+                    };
+                    let Ok((seq, chunk)) = job else {
+                        return;
+                    };
+                    if done.send((seq, Prepared::new(chunk))).is_err() {
+                        return;
+                    }
+                });
+            }
+            drop(done_tx);
+            // job_tx и очереди стадий уходят внутрь: на любом выходе
+            // рабочие и стадии получат конец очереди
+            let side = Side { data: data_tx, scan: scan_tx, nodes: node_rx };
+            let res = self.drive(input, first, job_tx, &done_rx, depth, tree, side, stats);
+            match hashing.join() {
+                Ok(h) => tree.put_hasher(h),
+                Err(_) => return Err(StoreError::Corrupt("tree hasher thread panicked".into())),
+            }
+            res
+        })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn drive<R: Read>(
+        &mut self,
+        input: &mut Input<R>,
+        first: usize,
+        jobs: mpsc::SyncSender<(u64, Vec<u8>)>,
+        done: &mpsc::Receiver<(u64, Prepared)>,
+        depth: usize,
+        tree: &mut TreeBuilder,
+        side: Side,
+        stats: &mut ImportStats,
+    ) -> StoreResult<()> {
+        let stopped = || StoreError::Corrupt("import worker pool stopped".into());
+        let mut cut = Some(first);
+        let (mut sent, mut next) = (0u64, 0u64);
+        let mut ready: BTreeMap<u64, Prepared> = BTreeMap::new();
+
+        loop {
+            while sent - next < depth as u64 {
+                let Some(n) = cut else {
+                    break;
+                };
+                jobs.send((sent, input.buf[..n].to_vec())).map_err(|_| stopped())?;
+                input.buf.drain(..n);
+                sent += 1;
+                cut = self.next_cut(input, stats)?;
+            }
+            if sent == next {
+                return Ok(());
+            }
+
+            let (seq, p) = done.recv().map_err(|_| stopped())?;
+            ready.insert(seq, p);
+            while let Some(p) = ready.remove(&next) {
+                next += 1;
+                let (id, data): (BlockId, Arc<[u8]>) = match p {
+                    Prepared::Zero(id, chunk) => {
+                        stats.chunks += 1;
+                        stats.zero_chunks += 1;
+                        (id, chunk.into())
+                    }
+                    Prepared::L0(mut l0) => (self.put_prepared(&mut l0, stats)?, l0.raw().into()),
+                };
+                if let Some(scan) = &side.scan {
+                    scan.send(data.clone()).map_err(|_| stopped())?;
+                }
+                let len = data.len() as u64;
+                side.data.send(data).map_err(|_| stopped())?;
+                tree.push_leaf(&mut *self.store, id, len, &mut || side.nodes.recv().map_err(|_| stopped()))?;
+                self.report(stats);
+            }
+        }
+    }
+
+    /// Записанный (или найденный) L0 — в дерево, анализаторам, в прогресс.
+    fn commit(
+        &mut self,
+        tree: &mut TreeBuilder,
+        zscan: &mut ZScanSet,
+        id: BlockId,
+        data: &[u8],
+        stats: &mut ImportStats,
+    ) -> StoreResult<()> {
+        tree.push(&mut *self.store, id, data)?;
+        zscan.push(data);
+        self.report(stats);
+        Ok(())
+    }
+
+    /// Прогресс, если подошёл очередной шаг.
+    fn report(&mut self, stats: &ImportStats) {
+        if let Some(p) = &mut self.progress {
+            if stats.bytes_in >= p.next {
+                (p.f)(stats);
+                p.next = stats.bytes_in.saturating_add(p.every);
+            }
+        }
+    }
+
+    /// L0-чанк: переиспользуем существующий блок с тем же hash или пишем новый.
+    fn put_chunk(&mut self, chunk: &[u8], stats: &mut ImportStats) -> StoreResult<BlockId> {
+        if let Some(id) = zero_l0_id(chunk) {
+            stats.chunks += 1;
+            stats.zero_chunks += 1;
+            return Ok(id);
+        }
+        // hash считаем один раз: при сборке frame'а, он же идёт в dedup
+        self.put_prepared(&mut PreparedL0::new(chunk), stats)
+    }
+
+    /// То же для frame'а, собранного рабочим потоком.
+    fn put_prepared(&mut self, p: &mut PreparedL0, stats: &mut ImportStats) -> StoreResult<BlockId> {
+        stats.chunks += 1;
+        if let Some(id) = self.store.find_hash(&p.hash())? {
+            stats.dedup_hits += 1;
+            return Ok(id);
+        }
+        let id = self.store.put_l0_prepared(p)?;
+        stats.blocks_written += 1;
+        stats.bytes_written += p.raw().len() as u64;
+        Ok(id)
+    }
+}
+
+/// Вход импорта: источник и окно непорезанных байт.
+struct Input<R> {
+    r:   R,
+    buf: Vec<u8>,
+    eof: bool,
+    max: usize,
+}
+
+/// Очереди стадий пула: байты чанков по порядку — hasher дерева и
+/// анализаторам, обратно — hash закрытых узлов дерева.
+struct Side {
+    data:  mpsc::SyncSender<Arc<[u8]>>,
+    scan:  Option<mpsc::SyncSender<Arc<[u8]>>>,
+    nodes: mpsc::Receiver<[u8; 32]>,
+}
+
+/// Чанк после рабочего потока.
+enum Prepared {
+    /// Нулевой: hash не нужен, id — `zero_block_id`.
+    Zero(BlockId, Vec<u8>),
+    L0(PreparedL0),
+}
+
+impl Prepared {
+    fn new(chunk: Vec<u8>) -> Self {
+        match zero_l0_id(&chunk) {
+            Some(id) => Prepared::Zero(id, chunk),
+            None => Prepared::L0(PreparedL0::new(&chunk)),
+        }
+    }
+}
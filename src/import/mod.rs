@@ -0,0 +1,11 @@
+pub mod chunker;
+pub mod cdc;
+pub mod importer;
+pub mod tree;
+pub mod fs;
+
+pub use chunker::*;
+pub use cdc::*;
+pub use importer::*;
+pub use tree::*;
+pub use fs::*;
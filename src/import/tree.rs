@@ -0,0 +1,252 @@
+use smallvec::SmallVec;
+
+use crate::types::BlockId;
+use crate::block::multi::{MultiRecipe, MultiInfo};
+use crate::store::blockstore::{BlockStore, StoreResult};
+
+/// Один открытый уровень дерева: дети будущего узла и их накопленные концы.
+#[derive(Debug, Default)]
+struct Level {
+    blocks: SmallVec<[BlockId; 8]>,
+    ends:   Vec<u64>,
+}
+
+impl Level {
+    fn len(&self) -> u64 {
+        self.ends.last().copied().unwrap_or(0)
+    }
+}
+
+/// Построитель сбалансированного дерева Multi-of-Multi.
+///
+/// Листья (L0) подаются по одному в порядке данных. Когда на уровне
+/// набирается `fanout` детей, уровень закрывается в `MultiRecipe::Indexed`
+/// и становится ребёнком уровня выше. В памяти — не больше `fanout`
+/// детей на уровень, т.е. O(fanout * log n) при любом размере объекта.
+///
+/// Каждый узел пишется с `MultiInfo` (длина + blake3 его данных). Hash
+/// узлов считает `TreeHasher`: свой (`push`) или отданный в другой поток
+/// (`take_hasher` + `push_leaf`).
+#[derive(Debug)]
+pub struct TreeBuilder {
+    fanout: usize,
+    levels: Vec<Level>,
+    multis_written: u64,
+    hasher: Option<TreeHasher>,
+}
+
+/// Результат построения дерева.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TreeRoot {
+    /// Корневой Multi-блок.
+    pub root:  BlockId,
+    /// Логическая длина всего дерева.
+    pub len:   u64,
+    /// blake3 всех данных дерева (совпадает с MultiInfo корня).
+    pub hash:  [u8; 32],
+    /// Высота (1 — корень ссылается прямо на L0).
+    pub depth: u32,
+    /// Сколько Multi-блоков записано при построении.
+    pub multis: u64,
+}
+
+impl TreeBuilder {
+    /// `fanout` приводится к минимуму 2.
+    pub fn new(fanout: usize) -> Self {
+        let fanout = fanout.max(2);
+        Self {
+            fanout,
+            levels: vec![Level::default()],
+            multis_written: 0,
+            hasher: Some(TreeHasher::new(fanout)),
+        }
+    }
+
+    pub fn fanout(&self) -> usize {
+        self.fanout
+    }
+
+    /// Добавить очередной лист: L0 `id` с байтами `data`.
+    pub fn push<S: BlockStore + ?Sized>(&mut self, store: &mut S, id: BlockId, data: &[u8]) -> StoreResult<()> {
+        let mut closed = Vec::new();
+        self.own_hasher().push(data, &mut closed);
+        let mut hashes = closed.into_iter();
+        self.push_leaf(store, id, data.len() as u64, &mut || Ok(hashes.next().expect("hash per closed node")))
+    }
+
+    /// Отдать hasher (например, в отдельный поток): дальше листья подаются
+    /// через `push_leaf`, а байты — в `TreeHasher::push` в том же порядке.
+    /// Перед `finish` hasher возвращается через `put_hasher`.
+    pub fn take_hasher(&mut self) -> Option<TreeHasher> {
+        self.hasher.take()
+    }
+
+    pub fn put_hasher(&mut self, hasher: TreeHasher) {
+        self.hasher = Some(hasher);
+    }
+
+    /// Лист без байт: `hashes` отдаёт hash очередного закрытого узла
+    /// (в порядке, в каком их выдаёт `TreeHasher::push`).
+    pub fn push_leaf<S: BlockStore + ?Sized>(
+        &mut self,
+        store: &mut S,
+        id: BlockId,
+        len: u64,
+        hashes: &mut dyn FnMut() -> StoreResult<[u8; 32]>,
+    ) -> StoreResult<()> {
+        self.push_at(store, 0, id, len, hashes)
+    }
+
+    fn own_hasher(&mut self) -> &mut TreeHasher {
+        self.hasher.as_mut().expect("TreeBuilder hasher is taken")
+    }
+
+    fn push_at<S: BlockStore + ?Sized>(
+        &mut self,
+        store: &mut S,
+        level: usize,
+        id: BlockId,
+        len: u64,
+        hashes: &mut dyn FnMut() -> StoreResult<[u8; 32]>,
+    ) -> StoreResult<()> {
+        let lv = &mut self.levels[level];
+        let end = lv.len() + len;
+        lv.blocks.push(id);
+        lv.ends.push(end);
+
+        if lv.blocks.len() >= self.fanout {
+            if self.levels.len() == level + 1 {
+                self.levels.push(Level::default());
+            }
+            let (mid, info) = self.flush(store, level, hashes()?)?;
+            self.push_at(store, level + 1, mid, info.logical_len, hashes)?;
+        }
+        Ok(())
+    }
+
+    /// Закрыть уровень в Indexed-узел: (id, MultiInfo узла).
+    fn flush<S: BlockStore + ?Sized>(
+        &mut self,
+        store: &mut S,
+        level: usize,
+        hash: [u8; 32],
+    ) -> StoreResult<(BlockId, MultiInfo)> {
+        let lv = std::mem::take(&mut self.levels[level]);
+        let info = MultiInfo { logical_len: lv.len(), hash };
+        let id = store.put_multi_info(
+            &MultiRecipe::Indexed {
+                blocks: lv.blocks,
+                ends:   lv.ends,
+            },
+            &info,
+        )?;
+        self.multis_written += 1;
+        Ok((id, info))
+    }
+
+    /// Закрыть все уровни снизу вверх и вернуть корень.
+    ///
+    /// Корень — всегда Multi (в т.ч. для пустого входа и одного листа).
+    pub fn finish<S: BlockStore + ?Sized>(mut self, store: &mut S) -> StoreResult<TreeRoot> {
+        let mut closed = Vec::new();
+        self.hasher.take().expect("TreeBuilder hasher is taken").finish(&mut closed);
+        let mut hashes = closed.into_iter();
+        let mut next = || hashes.next().expect("hash per closed node");
+
+        let mut level = 0;
+        loop {
+            let top = self.levels[level + 1..].iter().all(|l| l.blocks.is_empty());
+            if top {
+                let lv = &self.levels[level];
+                // единственный ребёнок верхнего уровня — уже готовый Multi
+                if level > 0 && lv.blocks.len() == 1 {
+                    return Ok(TreeRoot {
+                        root:  lv.blocks[0],
+                        len:   lv.len(),
+                        hash:  next(),
+                        depth: level as u32,
+                        multis: self.multis_written,
+                    });
+                }
+                let (root, info) = self.flush(store, level, next())?;
+                return Ok(TreeRoot {
+                    root,
+                    len:   info.logical_len,
+                    hash:  info.hash,
+                    depth: level as u32 + 1,
+                    multis: self.multis_written,
+                });
+            }
+            if !self.levels[level].blocks.is_empty() {
+                let (mid, info) = self.flush(store, level, next())?;
+                let up = &mut self.levels[level + 1];
+                let end = up.len() + info.logical_len;
+                up.blocks.push(mid);
+                up.ends.push(end);
+            }
+            level += 1;
+        }
+    }
+}
+
+/// Hash узлов `TreeBuilder` по байтам листьев.
+///
+/// Уровни закрываются по числу детей так же, как в `TreeBuilder`, поэтому
+/// hash выдаются в порядке закрытия узлов без знания id. Байт листа
+/// хэшируется на каждом открытом уровне: O(глубина) на байт — поэтому
+/// параллельный импорт ведёт hasher в своём потоке.
+#[derive(Debug, Clone)]
+pub struct TreeHasher {
+    fanout: usize,
+    /// (детей на уровне, hash данных открытого узла)
+    levels: Vec<(usize, blake3::Hasher)>,
+}
+
+impl TreeHasher {
+    pub fn new(fanout: usize) -> Self {
+        Self { fanout: fanout.max(2), levels: vec![(0, blake3::Hasher::new())] }
+    }
+
+    /// Очередной лист; hash закрытых им узлов — в `closed`, снизу вверх.
+    pub fn push(&mut self, data: &[u8], closed: &mut Vec<[u8; 32]>) {
+        for (_, h) in self.levels.iter_mut() {
+            h.update(data);
+        }
+        let mut level = 0;
+        loop {
+            self.levels[level].0 += 1;
+            if self.levels[level].0 < self.fanout {
+                return;
+            }
+            if self.levels.len() == level + 1 {
+                // новый верхний уровень: всё, что было до сих пор, лежит
+                // в закрываемом узле, поэтому hasher начинается с его копии
+                let h = self.levels[level].1.clone();
+                self.levels.push((0, h));
+            }
+            let (_, h) = std::mem::replace(&mut self.levels[level], (0, blake3::Hasher::new()));
+            closed.push(*h.finalize().as_bytes());
+            level += 1;
+        }
+    }
+
+    /// Конец данных: hash узлов, закрываемых `TreeBuilder::finish`, в том
+    /// же порядке; последний — hash корня (всех данных).
+    pub fn finish(mut self, closed: &mut Vec<[u8; 32]>) {
+        let mut level = 0;
+        loop {
+            let top = self.levels[level + 1..].iter().all(|(n, _)| *n == 0);
+            let (n, h) = &self.levels[level];
+            if top {
+                closed.push(*h.finalize().as_bytes());
+                return;
+            }
+            if *n > 0 {
+                let h = std::mem::replace(&mut self.levels[level].1, blake3::Hasher::new());
+                closed.push(*h.finalize().as_bytes());
+                self.levels[level + 1].0 += 1;
+            }
+            level += 1;
+        }
+    }
+}
@@ -5,5 +5,9 @@ pub mod store;
 pub mod codec;
 pub mod net_core;
 pub mod ffi;
+pub mod import;
+pub mod reader;
+pub mod export;
+pub mod analysis;
 
 pub mod config;
@@ -1,5 +1,3 @@
-use crate::types::{BlockId, ObjectId, CodecId, DictId, ClusterId};
-
 /// NodeId: абстрактный идентификатор узла в сети.
 pub type NodeId = u64;
 
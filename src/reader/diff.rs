@@ -0,0 +1,366 @@
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
+use std::sync::Arc;
+
+use crate::types::{BlockId, BlockKind, BlockRef, ObjectId, OBJ_TYPE_DIR};
+use crate::codec::{DirEntry, DirEntryKind, decode_dir_meta};
+use crate::store::blockstore::{BlockStore, StoreError, StoreResult, zero_block_len};
+use crate::store::typed::TypedStore;
+use crate::reader::object_reader::{Child, Node, ObjectReader};
+
+/// Результат сравнения двух Object'ов по деревьям блоков.
+///
+/// Изменённые диапазоны ищутся по логическим смещениям: блоки (L0 или
+/// целые поддеревья) совпадают, если начинаются в одном месте, одной длины
+/// и с одним содержимым по hash. Совпавшее поддерево считается одним общим
+/// блоком и не раскрывается; отличающиеся части раскрываются до L0.
+///
+/// Счётчики L0 в отличающихся частях — по hash, без учёта места: L0,
+/// сдвинутый вставкой (CDC), общий. Одинаковые L0 считаются попарно,
+/// поэтому `shared_bytes + only_a_bytes == len_a` и то же для `b`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ObjectDiff {
+    pub len_a: u64,
+    pub len_b: u64,
+    /// Изменённые диапазоны (по возрастанию, смежные слиты). Точность —
+    /// граница L0; хвост длиннее другого объекта — тоже изменение.
+    pub changed: Vec<Range<u64>>,
+
+    /// Общие блоки: совпавшие на месте поддеревья и L0 изменённых
+    /// диапазонов, чей hash есть и у другого объекта.
+    pub shared_blocks: u64,
+    pub shared_bytes:  u64,
+    /// L0 только в `a` (в изменённых диапазонах, hash нет у `b`).
+    pub only_a_blocks: u64,
+    pub only_a_bytes:  u64,
+    /// L0 только в `b`.
+    pub only_b_blocks: u64,
+    pub only_b_bytes:  u64,
+}
+
+impl ObjectDiff {
+    /// Данные объектов совпадают.
+    pub fn is_identical(&self) -> bool {
+        self.len_a == self.len_b && self.changed.is_empty()
+    }
+
+    /// Сколько байт в изменённых диапазонах.
+    pub fn changed_bytes(&self) -> u64 {
+        self.changed.iter().map(|r| r.end - r.start).sum()
+    }
+
+    fn mark(&mut self, r: Range<u64>) {
+        if r.is_empty() {
+            return;
+        }
+        match self.changed.last_mut() {
+            Some(last) if last.end >= r.start => last.end = last.end.max(r.end),
+            _ => self.changed.push(r),
+        }
+    }
+}
+
+/// Сравнить данные Object'ов `a` и `b` (или Multi/L0/Z-блоков).
+///
+/// Совпадающие поддеревья отсекаются по id, MultiInfo или hash frame'а,
+/// L0-данные не читаются: длины и hash берутся из заголовков и рецептов.
+pub fn diff_objects<S: BlockStore + ?Sized>(store: &S, a: ObjectId, b: ObjectId) -> StoreResult<ObjectDiff> {
+    let mut ra = ObjectReader::open(store, a)?;
+    let mut rb = ObjectReader::open(store, b)?;
+    let mut d = ObjectDiff { len_a: ra.len()?, len_b: rb.len()?, ..ObjectDiff::default() };
+
+    // одинаковый hash корней — дальше не спускаемся
+    if let (Some(ia), Some(ib)) = (ra.content_info(), rb.content_info()) {
+        if ia == ib && ia.logical_len == d.len_a {
+            d.shared_blocks = 1;
+            d.shared_bytes = d.len_a;
+            return Ok(d);
+        }
+    }
+
+    let mut ca = Cursor::new(ra)?;
+    let mut cb = Cursor::new(rb)?;
+    // всё до `done` уже разобрано
+    let mut done = 0u64;
+    let mut un = Unmatched::new();
+
+    while let (Some(ia), Some(ib)) = (ca.peek()?, cb.peek()?) {
+        if ca.pos == cb.pos && ia.len == ib.len && same(store, ia.child, ib.child)? {
+            d.shared_blocks += 1;
+            d.shared_bytes += ia.len;
+            done = ca.pos + ia.len;
+            ca.skip()?;
+            cb.skip()?;
+            continue;
+        }
+
+        // раскрываем поддеревья, пока с обеих сторон не останутся L0
+        let a_leaf = ca.is_leaf(store, ia.child)?;
+        let b_leaf = cb.is_leaf(store, ib.child)?;
+        if !a_leaf || !b_leaf {
+            if !a_leaf && (b_leaf || ca.pos + ia.len >= cb.pos + ib.len) {
+                ca.descend(ia.child)?;
+            }
+            if !b_leaf && (a_leaf || cb.pos + ib.len >= ca.pos + ia.len) {
+                cb.descend(ib.child)?;
+            }
+            continue;
+        }
+
+        // два разных L0: изменено до ближайшего конца
+        let (ea, eb) = (ca.pos + ia.len, cb.pos + ib.len);
+        let end = ea.min(eb);
+        d.mark(done.max(ca.pos.min(cb.pos))..end);
+        done = done.max(end);
+        if ea == end {
+            tally(store, &mut un, ia, false)?;
+            ca.skip()?;
+        }
+        if eb == end {
+            tally(store, &mut un, ib, true)?;
+            cb.skip()?;
+        }
+    }
+
+    // хвост: L0 текущего блока частично уже в `changed`, но ещё не посчитаны
+    for (c, side_b) in [(&mut ca, false), (&mut cb, true)] {
+        while let Some(it) = c.peek()? {
+            if c.is_leaf(store, it.child)? {
+                tally(store, &mut un, it, side_b)?;
+                c.skip()?;
+            } else {
+                c.descend(it.child)?;
+            }
+        }
+    }
+    let longer = d.len_a.max(d.len_b);
+    d.mark(done..longer);
+
+    for (len, na, nb) in un.into_values() {
+        let both = na.min(nb);
+        d.shared_blocks += both;
+        d.shared_bytes += both * len;
+        d.only_a_blocks += na - both;
+        d.only_a_bytes += (na - both) * len;
+        d.only_b_blocks += nb - both;
+        d.only_b_bytes += (nb - both) * len;
+    }
+    Ok(d)
+}
+
+/// L0 вне совпавших на месте поддеревьев: hash -> (длина, сколько в a, в b).
+type Unmatched = HashMap<[u8; 32], (u64, u64, u64)>;
+
+/// Учесть L0 стороны `a` или `b` (hash — из заголовка, данные не читаются).
+fn tally<S: BlockStore + ?Sized>(store: &S, un: &mut Unmatched, it: Item, side_b: bool) -> StoreResult<()> {
+    let hash = store.get_header(child_id(it.child))?.hash;
+    let e = un.entry(hash).or_insert((it.len, 0, 0));
+    if side_b {
+        e.2 += 1;
+    } else {
+        e.1 += 1;
+    }
+    Ok(())
+}
+
+/// Отличие двух деревьев каталогов; `path` — через '/' от корня.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DirChange {
+    /// Запись есть только во втором каталоге (подкаталог — целиком).
+    Added(String),
+    /// Запись есть только в первом.
+    Removed(String),
+    /// Файл с другими данными: diff содержимого.
+    Modified(String, ObjectDiff),
+    /// Сменились вид записи, цель симлинка или атрибуты.
+    Changed(String),
+}
+
+/// Сравнить каталоги `a` и `b` (Object'ы OBJ_TYPE_DIR) по именам записей.
+///
+/// Подкаталоги обходятся рекурсивно, файлы сравниваются `diff_objects`.
+/// Изменения — по возрастанию пути.
+pub fn diff_dirs<S: BlockStore + ?Sized>(store: &S, a: ObjectId, b: ObjectId) -> StoreResult<Vec<DirChange>> {
+    let mut out = Vec::new();
+    diff_dir_at(store, "", a, b, &mut out)?;
+    Ok(out)
+}
+
+fn dir_entries<S: BlockStore + ?Sized>(store: &S, id: ObjectId) -> StoreResult<BTreeMap<String, DirEntry>> {
+    let o = store.get_object(id)?;
+    if o.obj_type != OBJ_TYPE_DIR {
+        return Err(StoreError::Corrupt(format!("object {} is not a directory", id)));
+    }
+    let entries = decode_dir_meta(&o.meta).ok_or(StoreError::Corrupt(format!("directory {} has bad meta", id)))?;
+    Ok(entries.into_iter().map(|e| (e.name.clone(), e)).collect())
+}
+
+fn diff_dir_at<S: BlockStore + ?Sized>(
+    store: &S,
+    prefix: &str,
+    a: ObjectId,
+    b: ObjectId,
+    out: &mut Vec<DirChange>,
+) -> StoreResult<()> {
+    if a == b {
+        return Ok(());
+    }
+    let (ea, eb) = (dir_entries(store, a)?, dir_entries(store, b)?);
+    let mut names: Vec<&String> = ea.keys().chain(eb.keys()).collect();
+    names.sort();
+    names.dedup();
+
+    for name in names {
+        let path = format!("{}{}", prefix, name);
+        let (x, y) = match (ea.get(name), eb.get(name)) {
+            (Some(x), Some(y)) => (x, y),
+            (Some(_), None) => {
+                out.push(DirChange::Removed(path));
+                continue;
+            }
+            (None, _) => {
+                out.push(DirChange::Added(path));
+                continue;
+            }
+        };
+        let attrs = (x.mode, x.uid, x.gid, x.mtime_sec, x.mtime_nsec) == (y.mode, y.uid, y.gid, y.mtime_sec, y.mtime_nsec);
+        match (x.kind, x.child, y.kind, y.child) {
+            (DirEntryKind::Dir, BlockRef::Object(ca), DirEntryKind::Dir, BlockRef::Object(cb)) => {
+                if !attrs {
+                    out.push(DirChange::Changed(path.clone()));
+                }
+                diff_dir_at(store, &format!("{}/", path), ca, cb, out)?;
+            }
+            (DirEntryKind::File, BlockRef::Object(ca), DirEntryKind::File, BlockRef::Object(cb)) => {
+                let d = diff_objects(store, ca, cb)?;
+                if !d.is_identical() {
+                    out.push(DirChange::Modified(path, d));
+                } else if !attrs {
+                    out.push(DirChange::Changed(path));
+                }
+            }
+            (DirEntryKind::Symlink, ca, DirEntryKind::Symlink, cb) => {
+                let same_target = ca == cb || store.get_header(ref_id(ca))?.hash == store.get_header(ref_id(cb))?.hash;
+                if !same_target || !attrs {
+                    out.push(DirChange::Changed(path));
+                }
+            }
+            _ => out.push(DirChange::Changed(path)),
+        }
+    }
+    Ok(())
+}
+
+fn ref_id(r: BlockRef) -> BlockId {
+    match r {
+        BlockRef::L0(id) | BlockRef::Multi(id) | BlockRef::Z(id) | BlockRef::Object(id) => id,
+    }
+}
+
+/// Содержимое двух блоков совпадает (без чтения L0-данных).
+fn same<S: BlockStore + ?Sized>(store: &S, a: Child, b: Child) -> StoreResult<bool> {
+    let (ia, ib) = (child_id(a), child_id(b));
+    if ia == ib {
+        return Ok(true);
+    }
+    // разная длина нулей уже отсечена, а одинаковая дала бы один id
+    if zero_block_len(ia).is_some() && zero_block_len(ib).is_some() {
+        return Ok(false);
+    }
+    let (ha, hb) = (store.get_header(ia)?, store.get_header(ib)?);
+    if ha.kind != hb.kind {
+        return Ok(false);
+    }
+    if ha.hash == hb.hash {
+        return Ok(true);
+    }
+    // разные рецепты могут описывать одни и те же данные
+    if ha.kind == BlockKind::Multi {
+        if let (Some(ma), Some(mb)) = (store.get_multi_info(ia)?, store.get_multi_info(ib)?) {
+            return Ok(ma == mb);
+        }
+    }
+    Ok(false)
+}
+
+fn child_id(c: Child) -> BlockId {
+    match c {
+        Child::L0(id) | Child::Any(id) => id,
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Item {
+    child: Child,
+    len:   u64,
+}
+
+/// Обход дерева слева направо с раскрытием узлов по требованию.
+struct Cursor<'a, S: BlockStore + ?Sized> {
+    rd: ObjectReader<'a, S>,
+    /// Открытые узлы и индекс следующего ребёнка в каждом.
+    stack: Vec<(Arc<Node>, usize)>,
+    /// Логическое начало текущего ребёнка.
+    pos: u64,
+}
+
+impl<'a, S: BlockStore + ?Sized> Cursor<'a, S> {
+    fn new(mut rd: ObjectReader<'a, S>) -> StoreResult<Self> {
+        let root = rd.root_node()?;
+        Ok(Self { rd, stack: vec![(root, 0)], pos: 0 })
+    }
+
+    /// Текущий ребёнок (пустые пропускаются).
+    fn peek(&mut self) -> StoreResult<Option<Item>> {
+        loop {
+            let Some((node, idx)) = self.stack.last().cloned() else {
+                return Ok(None);
+            };
+            let Some(r) = self.rd.child_range(&node, idx)? else {
+                self.stack.pop();
+                continue;
+            };
+            if r.is_empty() {
+                if let Some(top) = self.stack.last_mut() {
+                    top.1 += 1;
+                }
+                continue;
+            }
+            return Ok(Some(Item { child: node.children[idx], len: r.end - r.start }));
+        }
+    }
+
+    fn skip(&mut self) -> StoreResult<()> {
+        if let Some(it) = self.peek()? {
+            self.pos += it.len;
+            if let Some(top) = self.stack.last_mut() {
+                top.1 += 1;
+            }
+        }
+        Ok(())
+    }
+
+    fn is_leaf(&self, store: &S, c: Child) -> StoreResult<bool> {
+        Ok(match c {
+            Child::L0(_) => true,
+            Child::Any(id) if zero_block_len(id).is_some() => true,
+            Child::Any(id) => match store.get_header(id)?.kind {
+                BlockKind::L0 => true,
+                BlockKind::Multi => false,
+                other => {
+                    return Err(StoreError::Corrupt(format!("block {} ({:?}) inside multi recipe", id, other)));
+                }
+            },
+        })
+    }
+
+    /// Заменить текущего ребёнка (поддерево) его детьми; `pos` не меняется.
+    fn descend(&mut self, c: Child) -> StoreResult<()> {
+        let node = self.rd.node(child_id(c))?;
+        if let Some(top) = self.stack.last_mut() {
+            top.1 += 1;
+        }
+        self.stack.push((node, 0));
+        Ok(())
+    }
+}
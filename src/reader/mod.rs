@@ -0,0 +1,5 @@
+pub mod object_reader;
+pub mod diff;
+
+pub use object_reader::*;
+pub use diff::*;
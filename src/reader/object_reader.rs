@@ -0,0 +1,482 @@
+use std::collections::HashMap;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::ops::Range;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::types::{BlockId, BlockKind, BlockRef};
+use crate::block::multi::{MultiRecipe, MultiInfo};
+use crate::codec::decode_z_span;
+use crate::store::blockstore::{BlockStore, StoreError, StoreResult, zero_block_len};
+use crate::store::decode::BlockBody;
+
+/// Сколько разобранных Multi-узлов держим в памяти.
+const NODE_CACHE_MAX: usize = 64;
+
+/// Ребёнок узла дерева данных.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Child {
+    L0(BlockId),
+    /// Ребёнок Multi-узла: вид узнаём по заголовку только при спуске.
+    Any(BlockId),
+}
+
+/// Разобранный Multi-узел: дети + их накопленные концы (логические смещения).
+///
+/// Концы Aggregate/Z-узлов не хранятся в рецепте и достраиваются
+/// по заголовкам детей лениво — ровно до того ребёнка, что нужен
+/// для seek/чтения.
+#[derive(Debug)]
+pub(crate) struct Node {
+    pub(crate) children: Vec<Child>,
+    /// Известный префикс: ends[i] — смещение конца i-го ребёнка
+    /// относительно начала узла.
+    ends: Mutex<Vec<u64>>,
+    /// Длина узла, если известна без обхода детей (Indexed, MultiInfo).
+    len: Option<u64>,
+}
+
+impl Node {
+    fn new(children: Vec<Child>, ends: Vec<u64>, len: Option<u64>) -> Arc<Self> {
+        Arc::new(Self { children, ends: Mutex::new(ends), len })
+    }
+
+    fn ends(&self) -> MutexGuard<'_, Vec<u64>> {
+        self.ends.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Корень данных после разворачивания Object/Z.
+#[derive(Debug, Clone)]
+enum Root {
+    Leaf(BlockId),
+    /// Multi-блок или Z-блок (последовательность L0 first..=last).
+    Node(Arc<Node>),
+}
+
+/// ObjectReader — `Read + Seek` поверх дерева блоков Object/Multi.
+///
+/// Данные не материализуются целиком: в памяти только текущий L0
+/// и небольшой кэш разобранных Multi-узлов. Длины детей берутся
+/// из заголовков frame'ов (`BlockStore::get_header`), без чтения L0-данных,
+/// и только по мере надобности: open читает лишь корень, seek — спуск
+/// от корня по накопленным смещениям. Для Indexed-узлов смещения уже
+/// лежат в рецепте, и спуск стоит O(глубина) чтений.
+pub struct ObjectReader<'a, S: BlockStore + ?Sized> {
+    store: &'a S,
+    root:  Root,
+    /// Длина данных, если уже известна (см. `len`).
+    len:   Option<u64>,
+    pos:   u64,
+
+    /// MultiInfo корня, если записан (длина + blake3 данных).
+    info: Option<MultiInfo>,
+
+    /// Текущий L0: (логическое начало, байты).
+    cur: Option<(u64, Vec<u8>)>,
+    nodes: HashMap<BlockId, Arc<Node>>,
+}
+
+impl<'a, S: BlockStore + ?Sized> ObjectReader<'a, S> {
+    /// Открыть чтение данных Object'а или Multi/L0/Z-блока.
+    ///
+    /// Цепочки Object -> Object разворачиваются до первого не-Object корня.
+    /// Дети корня не читаются.
+    pub fn open(store: &'a S, id: BlockId) -> StoreResult<Self> {
+        let mut reader = Self {
+            store,
+            root: Root::Leaf(id),
+            len: None,
+            pos: 0,
+            info: None,
+            cur: None,
+            nodes: HashMap::new(),
+        };
+
+        let mut cur = id;
+        // защита от циклов Object -> Object
+        for _ in 0..64 {
+            let (kind, _hash, body) = store.get_typed(cur)?;
+            match (kind, body) {
+                (BlockKind::Object, BlockBody::Object(o)) => {
+                    cur = match o.root {
+                        BlockRef::L0(b) | BlockRef::Multi(b) | BlockRef::Z(b) | BlockRef::Object(b) => b,
+                    };
+                    continue;
+                }
+                (BlockKind::L0, BlockBody::L0(raw)) => {
+                    reader.root = Root::Leaf(cur);
+                    reader.len = Some(raw.len() as u64);
+                    reader.cur = Some((0, raw));
+                }
+                (BlockKind::Multi, BlockBody::Multi(recipe)) => {
+                    reader.info = store.get_multi_info(cur)?;
+                    let node = build_node(&recipe, reader.info.map(|i| i.logical_len))?;
+                    reader.len = node.len;
+                    reader.root = Root::Node(node);
+                }
+                (BlockKind::Z, BlockBody::Z(z)) => {
+                    let ids: Vec<Child> = match decode_z_span(&z.meta) {
+                        Some(span) => span.l0s.into_iter().map(Child::L0).collect(),
+                        None if z.last_l0 < z.first_l0 => {
+                            return Err(StoreError::Corrupt(format!("Z block {} has empty range", cur)));
+                        }
+                        None => (z.first_l0..=z.last_l0).map(Child::L0).collect(),
+                    };
+                    reader.root = Root::Node(Node::new(ids, Vec::new(), None));
+                }
+                (kind, _) => {
+                    return Err(StoreError::Corrupt(format!("block {} kind/body mismatch ({:?})", cur, kind)));
+                }
+            }
+            return Ok(reader);
+        }
+
+        Err(StoreError::Corrupt(format!("object chain from {} is too deep", id)))
+    }
+
+    /// Логическая длина данных.
+    ///
+    /// Без MultiInfo у корня (Z-блок, старый Aggregate) при первом вызове
+    /// читаются заголовки всех детей корня.
+    pub fn len(&mut self) -> StoreResult<u64> {
+        if let Some(len) = self.len {
+            return Ok(len);
+        }
+        let len = match &self.root {
+            Root::Leaf(id) => {
+                return Err(StoreError::Corrupt(format!("L0 root {} without length", id)));
+            }
+            Root::Node(n) => {
+                let n = n.clone();
+                self.node_len(&n)?
+            }
+        };
+        self.len = Some(len);
+        Ok(len)
+    }
+
+    pub fn is_empty(&mut self) -> StoreResult<bool> {
+        Ok(self.len()? == 0)
+    }
+
+    /// Текущая позиция.
+    pub fn position(&self) -> u64 {
+        self.pos
+    }
+
+    /// Сколько байт дыры (нулевого L0 без frame'а) начинается с текущей позиции.
+    ///
+    /// 0 — позиция в обычных данных или за концом. Дыру можно пропустить
+    /// seek'ом на это число байт, не читая нули.
+    pub fn hole_len(&mut self) -> StoreResult<u64> {
+        if self.past_end(self.pos) {
+            return Ok(0);
+        }
+        let Some((id, start)) = self.locate(self.pos)? else {
+            return Ok(0);
+        };
+        Ok(zero_block_len(id).map_or(0, |len| start + len - self.pos))
+    }
+
+    /// Длина и blake3 данных из frame корневого Multi (если записаны).
+    pub fn content_info(&self) -> Option<MultiInfo> {
+        self.info
+    }
+
+    /// Прочитать данные целиком и сверить с hash из `content_info`.
+    ///
+    /// Позиция после проверки — конец данных. Ошибка, если hash не записан.
+    pub fn verify(&mut self) -> StoreResult<bool> {
+        let info = self
+            .info
+            .ok_or(StoreError::Corrupt("object root has no stored content hash".into()))?;
+
+        let mut hasher = blake3::Hasher::new();
+        let mut buf = vec![0u8; 64 * 1024];
+        let mut total = 0u64;
+        self.pos = 0;
+        loop {
+            let n = self.read(&mut buf)?;
+            if n == 0 {
+                break;
+            }
+            hasher.update(&buf[..n]);
+            total += n as u64;
+        }
+        Ok(total == info.logical_len && hasher.finalize().as_bytes() == &info.hash)
+    }
+
+    fn child_len(&mut self, c: Child) -> StoreResult<u64> {
+        let id = match c {
+            Child::L0(id) | Child::Any(id) => id,
+        };
+        if let Some(len) = zero_block_len(id) {
+            return Ok(len);
+        }
+        if let Some(n) = self.nodes.get(&id) {
+            let n = n.clone();
+            return self.node_len(&n);
+        }
+        let h = self.store.get_header(id)?;
+        match h.kind {
+            BlockKind::L0 => h
+                .l0_raw_len()
+                .ok_or(StoreError::Corrupt(format!("block {} has bad L0 payload", id))),
+            BlockKind::Multi if matches!(c, Child::Any(_)) => {
+                // длина из MultiInfo — без спуска в поддерево
+                match self.store.get_multi_info(id)? {
+                    Some(info) => Ok(info.logical_len),
+                    None => {
+                        let n = self.node(id)?;
+                        self.node_len(&n)
+                    }
+                }
+            }
+            other => Err(StoreError::Corrupt(format!("block {} ({:?}) inside multi recipe", id, other))),
+        }
+    }
+
+    /// Достроить концы детей `node`, пока `done` не скажет хватит
+    /// (или пока не кончатся дети).
+    fn extend_ends(&mut self, node: &Node, mut done: impl FnMut(&[u64]) -> bool) -> StoreResult<()> {
+        loop {
+            let (idx, acc) = {
+                let ends = node.ends();
+                if ends.len() >= node.children.len() || done(&ends) {
+                    return Ok(());
+                }
+                (ends.len(), ends.last().copied().unwrap_or(0))
+            };
+            let end = acc
+                .checked_add(self.child_len(node.children[idx])?)
+                .ok_or(StoreError::Corrupt("multi node length overflows u64".into()))?;
+            node.ends().push(end);
+        }
+    }
+
+    /// Длина узла (без MultiInfo — по заголовкам всех детей).
+    pub(crate) fn node_len(&mut self, node: &Node) -> StoreResult<u64> {
+        if let Some(len) = node.len {
+            return Ok(len);
+        }
+        self.extend_ends(node, |_| false)?;
+        Ok(node.ends().last().copied().unwrap_or(0))
+    }
+
+    /// Смещения `idx`-го ребёнка внутри узла (None — детей меньше).
+    pub(crate) fn child_range(&mut self, node: &Node, idx: usize) -> StoreResult<Option<Range<u64>>> {
+        if idx >= node.children.len() {
+            return Ok(None);
+        }
+        self.extend_ends(node, |ends| ends.len() > idx)?;
+        let ends = node.ends();
+        let start = if idx == 0 { 0 } else { ends[idx - 1] };
+        Ok(Some(start..ends[idx]))
+    }
+
+    /// Ребёнок, покрывающий смещение `rel` внутри узла: (индекс, начало).
+    fn child_at(&mut self, node: &Node, rel: u64) -> StoreResult<Option<(usize, u64)>> {
+        self.extend_ends(node, |ends| ends.last().is_some_and(|e| *e > rel))?;
+        let ends = node.ends();
+        let idx = ends.partition_point(|e| *e <= rel);
+        if idx >= ends.len() {
+            return Ok(None);
+        }
+        Ok(Some((idx, if idx == 0 { 0 } else { ends[idx - 1] })))
+    }
+
+    pub(crate) fn node(&mut self, id: BlockId) -> StoreResult<Arc<Node>> {
+        if let Some(n) = self.nodes.get(&id) {
+            return Ok(n.clone());
+        }
+        let (kind, _hash, body) = self.store.get_typed(id)?;
+        let BlockBody::Multi(recipe) = body else {
+            return Err(StoreError::Corrupt(format!("block {} is {:?}, expected Multi", id, kind)));
+        };
+        let node = build_node(&recipe, None)?;
+
+        if self.nodes.len() >= NODE_CACHE_MAX {
+            // корень живёт отдельно, кэш внутренних узлов сбрасываем целиком
+            self.nodes.clear();
+        }
+        self.nodes.insert(id, node.clone());
+        Ok(node)
+    }
+
+    /// Корень как узел (лист — узел из одного L0), для обхода дерева.
+    pub(crate) fn root_node(&mut self) -> StoreResult<Arc<Node>> {
+        Ok(match &self.root {
+            Root::Leaf(id) => {
+                let id = *id;
+                let len = self.len()?;
+                Node::new(vec![Child::L0(id)], vec![len], Some(len))
+            }
+            Root::Node(n) => n.clone(),
+        })
+    }
+
+    /// Позиция точно за концом данных (по уже известной длине).
+    fn past_end(&self, pos: u64) -> bool {
+        self.len.is_some_and(|len| pos >= len)
+    }
+
+    /// Найти L0, покрывающий `pos`: (id, логическое начало). None — за концом.
+    fn locate(&mut self, pos: u64) -> StoreResult<Option<(BlockId, u64)>> {
+        let mut node = match &self.root {
+            Root::Leaf(id) => return Ok((!self.past_end(pos)).then_some((*id, 0))),
+            Root::Node(n) => n.clone(),
+        };
+        let mut base = 0u64;
+        let mut at_root = true;
+
+        loop {
+            let Some((idx, start)) = self.child_at(&node, pos - base)? else {
+                // за концом корня — конец данных, внутри — поддерево короче записанного
+                if at_root {
+                    return Ok(None);
+                }
+                return Err(StoreError::Corrupt(format!("offset {} past end of node", pos)));
+            };
+            base += start;
+            at_root = false;
+            match node.children[idx] {
+                Child::L0(id) => return Ok(Some((id, base))),
+                Child::Any(id) if zero_block_len(id).is_some() => return Ok(Some((id, base))),
+                Child::Any(id) => match self.store.get_header(id)?.kind {
+                    BlockKind::L0 => return Ok(Some((id, base))),
+                    BlockKind::Multi => node = self.node(id)?,
+                    other => {
+                        return Err(StoreError::Corrupt(format!("block {} ({:?}) inside multi recipe", id, other)));
+                    }
+                },
+            }
+        }
+    }
+
+    /// L0, покрывающие `[offset, offset + len)` (len > 0), по порядку,
+    /// и логическое начало первого из них.
+    pub(crate) fn l0_span(&mut self, offset: u64, len: u64) -> StoreResult<(u64, Vec<BlockId>)> {
+        let total = self.len()?;
+        let end = offset
+            .checked_add(len)
+            .filter(|&e| len > 0 && e <= total)
+            .ok_or(StoreError::Corrupt(format!("span {}+{} outside of {} bytes", offset, len, total)))?;
+        let mut l0s = Vec::new();
+        let mut first = None;
+        let mut pos = offset;
+        while pos < end {
+            let (id, start) = self
+                .locate(pos)?
+                .ok_or(StoreError::Corrupt(format!("offset {} past end of data", pos)))?;
+            first.get_or_insert(start);
+            l0s.push(id);
+            pos = start + self.child_len(Child::L0(id))?;
+        }
+        Ok((first.unwrap_or(offset), l0s))
+    }
+
+    /// Подгрузить L0 под `pos`; false — `pos` за концом данных.
+    fn load_at(&mut self, pos: u64) -> StoreResult<bool> {
+        if let Some((start, data)) = &self.cur {
+            if pos >= *start && pos < *start + data.len() as u64 {
+                return Ok(true);
+            }
+        }
+        let Some((id, start)) = self.locate(pos)? else {
+            return Ok(false);
+        };
+        // дыра: нули без обращения к store
+        if let Some(len) = zero_block_len(id) {
+            self.cur = Some((start, vec![0u8; len as usize]));
+            return Ok(true);
+        }
+        let (_kind, _hash, body) = self.store.get_typed(id)?;
+        let BlockBody::L0(raw) = body else {
+            return Err(StoreError::Corrupt(format!("block {} is not L0", id)));
+        };
+        self.cur = Some((start, raw));
+        Ok(true)
+    }
+}
+
+/// Узел по рецепту. `len` — длина из MultiInfo, если записана.
+fn build_node(recipe: &MultiRecipe, len: Option<u64>) -> StoreResult<Arc<Node>> {
+    let ids: &[BlockId] = match recipe {
+        MultiRecipe::Indexed { blocks, ends } => {
+            if blocks.len() != ends.len() || ends.windows(2).any(|w| w[1] < w[0]) {
+                return Err(StoreError::Corrupt("indexed multi has inconsistent offsets".into()));
+            }
+            let len = ends.last().copied().unwrap_or(0);
+            return Ok(Node::new(blocks.iter().map(|b| Child::Any(*b)).collect(), ends.clone(), Some(len)));
+        }
+        MultiRecipe::Aggregate { blocks } => blocks,
+        MultiRecipe::CodecRecipe { blocks: Some(blocks), .. } => blocks,
+        MultiRecipe::CodecRecipe { blocks: None, .. } | MultiRecipe::Custom { .. } => {
+            return Err(StoreError::Corrupt("multi recipe without L0 fallback is not readable".into()));
+        }
+    };
+    Ok(Node::new(ids.iter().map(|b| Child::Any(*b)).collect(), Vec::new(), len))
+}
+
+impl<S: BlockStore + ?Sized> Read for ObjectReader<'_, S> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() || self.past_end(self.pos) || !self.load_at(self.pos)? {
+            return Ok(0);
+        }
+
+        let (start, data) = self.cur.as_ref().expect("current L0 loaded");
+        let off = (self.pos - start) as usize;
+        let mut n = buf.len().min(data.len() - off);
+        if let Some(len) = self.len {
+            n = n.min((len - self.pos) as usize);
+        }
+        buf[..n].copy_from_slice(&data[off..off + n]);
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl<S: BlockStore + ?Sized> Seek for ObjectReader<'_, S> {
+    fn seek(&mut self, from: SeekFrom) -> io::Result<u64> {
+        let target = match from {
+            SeekFrom::Start(p) => Some(p),
+            SeekFrom::End(d) => self.len()?.checked_add_signed(d),
+            SeekFrom::Current(d) => self.pos.checked_add_signed(d),
+        };
+        match target {
+            Some(p) => {
+                // позиция лениво: L0 подгрузится при следующем read
+                self.pos = p;
+                Ok(p)
+            }
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "seek to a negative or overflowing position",
+            )),
+        }
+    }
+}
+
+/// Длина и blake3 данных Object'а (или Multi-блока) без чтения L0.
+///
+/// Разворачивает цепочку Object -> ... до корня и берёт MultiInfo
+/// из его frame. None — корень не Multi или info не записан.
+pub fn object_info<S: BlockStore + ?Sized>(store: &S, id: BlockId) -> StoreResult<Option<MultiInfo>> {
+    let mut cur = id;
+    for _ in 0..64 {
+        match store.get_header(cur)?.kind {
+            BlockKind::Object => {
+                let (_kind, _hash, body) = store.get_typed(cur)?;
+                let BlockBody::Object(o) = body else {
+                    return Err(StoreError::Corrupt(format!("block {} kind/body mismatch", cur)));
+                };
+                cur = match o.root {
+                    BlockRef::L0(b) | BlockRef::Multi(b) | BlockRef::Z(b) | BlockRef::Object(b) => b,
+                };
+            }
+            BlockKind::Multi => return store.get_multi_info(cur),
+            _ => return Ok(None),
+        }
+    }
+    Err(StoreError::Corrupt(format!("object chain from {} is too deep", id)))
+}
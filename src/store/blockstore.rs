@@ -1,15 +1,20 @@
+use std::sync::Mutex;
+
 use crate::types::{BlockId, BlockKind};
 use crate::codec::{
     ZPayload,
     ObjectPayload,
     encode_l0_raw,
     encode_multi_recipe,
+    encode_multi_info,
     encode_z_payload,
     encode_object_payload,
 };
-use crate::block::multi::MultiRecipe;
-use crate::store::decode::{decode_block_typed, BlockBody};
-use crate::store::encode::encode_block;
+use crate::block::multi::{MultiRecipe, MultiInfo};
+use crate::store::decode::{
+    decode_block_typed, decode_block_header, decode_multi_info_frame, BlockBody, BlockHeader, FRAME_HEADER_LEN,
+};
+use crate::store::encode::{encode_block, encode_l0_frame};
 
 use crate::net_core::error::NetError;
 
@@ -22,6 +27,8 @@ pub enum StoreError {
     Decode(NetError),
     OutOfRange(BlockId),
     Corrupt(String),
+    /// Операция прервана по запросу (флаг отмены).
+    Cancelled,
 }
 
 pub type StoreResult<T> = Result<T, StoreError>;
@@ -38,14 +45,54 @@ impl From<NetError> for StoreError {
     }
 }
 
+impl std::fmt::Display for StoreError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StoreError::Io(e) => write!(f, "store io: {}", e),
+            StoreError::Decode(e) => write!(f, "store decode: {:?}", e),
+            StoreError::OutOfRange(id) => write!(f, "block {} out of range", id),
+            StoreError::Corrupt(msg) => write!(f, "store corrupt: {}", msg),
+            StoreError::Cancelled => write!(f, "operation cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for StoreError {}
+
+/// Для Read/Seek-адаптеров над store (ObjectReader и т.п.).
+impl From<StoreError> for std::io::Error {
+    fn from(e: StoreError) -> Self {
+        match e {
+            StoreError::Io(e) => e,
+            other => std::io::Error::other(other),
+        }
+    }
+}
+
 /// Универсальный API хранилища блоков.
 pub trait BlockStore {
     /// Записать L0-блок (сырые байты) и получить его BlockId.
     fn put_l0(&mut self, raw: &[u8]) -> StoreResult<BlockId>;
 
+    /// Записать L0, frame которого уже собран (`PreparedL0`).
+    ///
+    /// hash и кодирование сделаны заранее (в рабочих потоках импорта);
+    /// store только проставляет id. По умолчанию — обычный `put_l0`.
+    fn put_l0_prepared(&mut self, p: &mut PreparedL0) -> StoreResult<BlockId> {
+        self.put_l0(p.raw())
+    }
+
     /// Записать Multi-блок по рецепту.
     fn put_multi(&mut self, recipe: &MultiRecipe) -> StoreResult<BlockId>;
 
+    /// Записать Multi-блок вместе с длиной и hash восстановленных данных.
+    ///
+    /// По умолчанию info не сохраняется: обычный `put_multi`, и
+    /// `get_multi_info` для такого блока вернёт None.
+    fn put_multi_info(&mut self, recipe: &MultiRecipe, _info: &MultiInfo) -> StoreResult<BlockId> {
+        self.put_multi(recipe)
+    }
+
     /// Записать Z-блок (агрегация диапазона L0).
     fn put_z(&mut self, z: &ZPayload) -> StoreResult<BlockId>;
 
@@ -57,6 +104,47 @@ pub trait BlockStore {
 
     /// Прочитать raw frame как байты.
     fn get_frame(&self, id: BlockId) -> StoreResult<Vec<u8>>;
+
+    /// Прочитать только заголовок frame (kind, hash, длина payload).
+    ///
+    /// Реализации с дешёвым позиционным чтением должны переопределять
+    /// это без чтения payload.
+    fn get_header(&self, id: BlockId) -> StoreResult<BlockHeader> {
+        Ok(decode_block_header(&self.get_frame(id)?)?)
+    }
+
+    /// Длина и hash данных Multi-блока, если они записаны в frame.
+    ///
+    /// None — блок не Multi или записан через `put_multi` без info.
+    fn get_multi_info(&self, id: BlockId) -> StoreResult<Option<MultiInfo>> {
+        Ok(decode_multi_info_frame(&self.get_frame(id)?)?)
+    }
+
+    /// Уровень хранения блока (см. `tiered_store::Tier`).
+    ///
+    /// Одноуровневые store возвращают 0.
+    fn block_tier(&self, _id: BlockId) -> u8 {
+        0
+    }
+
+    /// Есть ли блок с таким id (без чтения frame).
+    ///
+    /// По умолчанию — через `get_header`; store с индексом id переопределяют.
+    fn contains(&self, id: BlockId) -> bool {
+        self.get_header(id).is_ok()
+    }
+
+    /// Найти блок по хэшу frame'а (blake3 от payload, см. `hash_l0` и др.).
+    ///
+    /// Отрицательный ответ не должен требовать IO. Реализации по умолчанию
+    /// нет: на ответе держится dedup при импорте, и обёртки над другим
+    /// store обязаны пробрасывать запрос во внутренний индекс.
+    fn find_hash(&self, hash: &[u8; 32]) -> StoreResult<Option<BlockId>>;
+
+    /// Есть ли уже блок с таким хэшем (dedup, have/want).
+    fn contains_hash(&self, hash: &[u8; 32]) -> StoreResult<bool> {
+        Ok(self.find_hash(hash)?.is_some())
+    }
 }
 
 /// Вспомогательный хелпер: blake3(payload) -> [u8;32]
@@ -67,6 +155,40 @@ fn hash_payload(payload: &[u8]) -> [u8; 32] {
     out
 }
 
+/// Хэш, который получит frame L0-блока с такими байтами (без записи).
+pub fn hash_l0(raw: &[u8]) -> [u8; 32] {
+    hash_payload(&encode_l0_raw(raw))
+}
+
+/// Хэш frame'а Multi-блока с таким рецептом.
+pub fn hash_multi(recipe: &MultiRecipe) -> StoreResult<[u8; 32]> {
+    Ok(hash_payload(&encode_multi_recipe(recipe)?))
+}
+
+/// Payload Multi-блока: рецепт + (опционально) MultiInfo.
+fn multi_payload(recipe: &MultiRecipe, info: Option<&MultiInfo>) -> StoreResult<Vec<u8>> {
+    let mut payload = encode_multi_recipe(recipe)?;
+    if let Some(info) = info {
+        payload.extend_from_slice(&encode_multi_info(info));
+    }
+    Ok(payload)
+}
+
+/// Хэш frame'а Multi-блока с рецептом и MultiInfo.
+pub fn hash_multi_info(recipe: &MultiRecipe, info: &MultiInfo) -> StoreResult<[u8; 32]> {
+    Ok(hash_payload(&multi_payload(recipe, Some(info))?))
+}
+
+/// Хэш frame'а Z-блока.
+pub fn hash_z(z: &ZPayload) -> [u8; 32] {
+    hash_payload(&encode_z_payload(z))
+}
+
+/// Хэш frame'а Object-блока.
+pub fn hash_object(o: &ObjectPayload) -> [u8; 32] {
+    hash_payload(&encode_object_payload(o))
+}
+
 /// Вспомогательный хелпер для типов, которые делают Frame на месте.
 pub fn make_frame_l0(id: BlockId, raw: &[u8]) -> Vec<u8> {
     let payload = encode_l0_raw(raw);
@@ -74,10 +196,16 @@ pub fn make_frame_l0(id: BlockId, raw: &[u8]) -> Vec<u8> {
     encode_block(BlockKind::L0, id, &h, &payload)
 }
 
-pub fn make_frame_multi(id: BlockId, recipe: &MultiRecipe) -> Vec<u8> {
-    let payload = encode_multi_recipe(recipe);
+pub fn make_frame_multi(id: BlockId, recipe: &MultiRecipe) -> StoreResult<Vec<u8>> {
+    let payload = multi_payload(recipe, None)?;
+    let h = hash_payload(&payload);
+    Ok(encode_block(BlockKind::Multi, id, &h, &payload))
+}
+
+pub fn make_frame_multi_info(id: BlockId, recipe: &MultiRecipe, info: &MultiInfo) -> StoreResult<Vec<u8>> {
+    let payload = multi_payload(recipe, Some(info))?;
     let h = hash_payload(&payload);
-    encode_block(BlockKind::Multi, id, &h, &payload)
+    Ok(encode_block(BlockKind::Multi, id, &h, &payload))
 }
 
 pub fn make_frame_z(id: BlockId, z: &ZPayload) -> Vec<u8> {
@@ -92,6 +220,132 @@ pub fn make_frame_object(id: BlockId, o: &ObjectPayload) -> Vec<u8> {
     encode_block(BlockKind::Object, id, &h, &payload)
 }
 
+// ------------------------------------------------------------
+// Нулевые L0 ("дыры")
+//
+// Импорт не пишет L0 из одних нулей: в рецепт идёт id `ZERO_BLOCK_TAG | len`,
+// и store отдаёт такой блок синтетически (frame, заголовок, тело).
+// Обычные id выдаются подряд с нуля и с этим диапазоном не пересекаются.
+// Сам `put_l0` нули не распознаёт: что писать дырой, решает импорт.
+
+/// Старшие биты id нулевого L0; длина — в младших 32 битах.
+pub const ZERO_BLOCK_TAG: BlockId = 0xFF00_0000_0000_0000;
+
+/// Максимальная длина нулевого L0 — с запасом больше чанка импорта
+/// (`l0_chunk`, `cdc_max`). Более длинный нулевой чанк пишется обычным
+/// L0; синтез блока по id не выделяет больше этого.
+pub const ZERO_BLOCK_MAX: u64 = 16 * 1024 * 1024;
+
+/// Нули для hash нулевых L0 без выделения памяти под весь блок.
+static ZEROS: [u8; 64 * 1024] = [0; 64 * 1024];
+
+/// Hash нулевых L0 по длине: длин обычно одна-две (размер чанка).
+static ZERO_HASHES: Mutex<Vec<(u64, [u8; 32])>> = Mutex::new(Vec::new());
+const ZERO_HASHES_MAX: usize = 64;
+
+/// id нулевого L0 длины `len` (None — длина 0 или больше `ZERO_BLOCK_MAX`).
+pub fn zero_block_id(len: u64) -> Option<BlockId> {
+    (1..=ZERO_BLOCK_MAX).contains(&len).then_some(ZERO_BLOCK_TAG | len)
+}
+
+/// Длина нулевого L0 по его id; None — обычный блок.
+pub fn zero_block_len(id: BlockId) -> Option<u64> {
+    let len = id & !ZERO_BLOCK_TAG;
+    (id & ZERO_BLOCK_TAG == ZERO_BLOCK_TAG && (1..=ZERO_BLOCK_MAX).contains(&len)).then_some(len)
+}
+
+/// Нулевой ли это L0 (дыра без frame'а в store).
+pub fn is_zero_block(id: BlockId) -> bool {
+    zero_block_len(id).is_some()
+}
+
+/// id нулевого L0 для `raw`, если он непустой и весь из нулей.
+pub fn zero_l0_id(raw: &[u8]) -> Option<BlockId> {
+    if raw.iter().any(|b| *b != 0) {
+        return None;
+    }
+    zero_block_id(raw.len() as u64)
+}
+
+/// `hash_l0` от `len` нулей: считается потоком по `ZEROS`, запоминается.
+fn zero_l0_hash(len: u64) -> [u8; 32] {
+    let mut cache = ZERO_HASHES.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((_, h)) = cache.iter().find(|(l, _)| *l == len) {
+        return *h;
+    }
+    // payload L0 — TLV 0x01: tag, len:u32, данные
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(&[0x01]);
+    hasher.update(&(len as u32).to_be_bytes());
+    let mut left = len;
+    while left > 0 {
+        let n = left.min(ZEROS.len() as u64);
+        hasher.update(&ZEROS[..n as usize]);
+        left -= n;
+    }
+    let h = *hasher.finalize().as_bytes();
+    if cache.len() >= ZERO_HASHES_MAX {
+        cache.clear();
+    }
+    cache.push((len, h));
+    h
+}
+
+/// Синтетический frame нулевого L0 (как если бы он был записан).
+pub fn zero_l0_frame(id: BlockId) -> Option<Vec<u8>> {
+    let len = zero_block_len(id)?;
+    Some(encode_l0_frame(id, &zero_l0_hash(len), &vec![0u8; len as usize]))
+}
+
+/// Синтетическое тело нулевого L0 для `get_typed`.
+pub fn zero_l0_typed(id: BlockId) -> Option<(BlockKind, [u8; 32], BlockBody)> {
+    let len = zero_block_len(id)?;
+    Some((BlockKind::L0, zero_l0_hash(len), BlockBody::L0(vec![0u8; len as usize])))
+}
+
+/// Синтетический заголовок нулевого L0 для `get_header` (без данных).
+pub fn zero_l0_header(id: BlockId) -> Option<BlockHeader> {
+    let len = zero_block_len(id)?;
+    Some(BlockHeader {
+        kind: BlockKind::L0,
+        id,
+        hash: zero_l0_hash(len),
+        payload_len: (len + 1 + 4) as u32,
+    })
+}
+
+/// L0-frame, собранный вне store: id в заголовке проставляется при записи
+/// (`BlockStore::put_l0_prepared`), hash и payload уже готовы.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreparedL0 {
+    frame: Vec<u8>,
+}
+
+impl PreparedL0 {
+    pub fn new(raw: &[u8]) -> Self {
+        Self { frame: make_frame_l0(0, raw) }
+    }
+
+    /// hash frame'а (как `hash_l0(raw)`).
+    pub fn hash(&self) -> [u8; 32] {
+        let mut h = [0u8; 32];
+        h.copy_from_slice(&self.frame[12..44]);
+        h
+    }
+
+    /// Сырые байты блока.
+    pub fn raw(&self) -> &[u8] {
+        // payload = TLV 0x01: tag + u32 длина + байты
+        &self.frame[FRAME_HEADER_LEN + 1 + 4..]
+    }
+
+    /// frame с проставленным id (байты 44..52 заголовка).
+    pub fn frame_with_id(&mut self, id: BlockId) -> &[u8] {
+        self.frame[44..52].copy_from_slice(&id.to_be_bytes());
+        &self.frame
+    }
+}
+
 /// Универсальный decode из raw frame в типизированное тело блока.
 pub fn decode_frame_typed(buf: &[u8]) -> StoreResult<(BlockKind, BlockId, [u8; 32], BlockBody)> {
     let (kind, id, hash, body) = decode_block_typed(buf)?;
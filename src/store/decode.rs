@@ -1,28 +1,51 @@
-use crate::types::{ZNodeMeta, OBJ_TYPE_ZNODE, BlockId, BlockKind};
+use crate::types::{BlockId, BlockKind};
 use crate::net_core::error::{NetError, NetResult};
 use crate::codec::{
     tlv_iter,
     decode_l0_raw,
     decode_multi_recipe,
+    decode_multi_info,
     decode_z_payload,
     decode_object_payload,
     ZPayload,
     ObjectPayload,
 };
-use crate::block::multi::MultiRecipe;
+use crate::block::multi::{MultiRecipe, MultiInfo};
 
-pub const MAGIC: [u8;4] = *b"QBLK";
+pub use crate::store::encode::MAGIC;
 
 fn u16_from(b: &[u8]) -> u16 { u16::from_be_bytes([b[0],b[1]]) }
 fn u32_from(b: &[u8]) -> u32 { u32::from_be_bytes([b[0],b[1],b[2],b[3]]) }
 fn u64_from(b: &[u8]) -> u64 { u64::from_be_bytes([b[0],b[1],b[2],b[3],b[4],b[5],b[6],b[7]]) }
 
-/// Низкоуровневый разбор frame: header + raw payload.
-pub fn decode_block_frame(buf: &[u8]) -> NetResult<(BlockKind, BlockId, [u8;32], Vec<u8>)> {
-    if buf.len() < 4+1+1+2+4+32+8 {
+/// Длина заголовка frame: MAGIC, kind, flags, reserved, len, hash, id.
+pub const FRAME_HEADER_LEN: usize = 4+1+1+2+4+32+8;
+
+/// Заголовок frame (без payload).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockHeader {
+    pub kind:        BlockKind,
+    pub id:          BlockId,
+    pub hash:        [u8;32],
+    pub payload_len: u32,
+}
+
+impl BlockHeader {
+    /// Длина сырых байт L0-блока по заголовку (payload = один TLV 0x01).
+    pub fn l0_raw_len(&self) -> Option<u64> {
+        match self.kind {
+            BlockKind::L0 => (self.payload_len as u64).checked_sub(1 + 4),
+            _ => None,
+        }
+    }
+}
+
+/// Разбор только заголовка frame (достаточно первых FRAME_HEADER_LEN байт).
+pub fn decode_block_header(buf: &[u8]) -> NetResult<BlockHeader> {
+    if buf.len() < FRAME_HEADER_LEN {
         return Err(NetError::DecodeError);
     }
-    if &buf[0..4] != MAGIC {
+    if buf[0..4] != MAGIC {
         return Err(NetError::DecodeError);
     }
 
@@ -42,12 +65,19 @@ pub fn decode_block_frame(buf: &[u8]) -> NetResult<(BlockKind, BlockId, [u8;32],
     hash.copy_from_slice(&buf[12..44]);
     let id = u64_from(&buf[44..52]);
 
-    let want = 52 + payload_len as usize;
+    Ok(BlockHeader { kind, id, hash, payload_len })
+}
+
+/// Низкоуровневый разбор frame: header + raw payload.
+pub fn decode_block_frame(buf: &[u8]) -> NetResult<(BlockKind, BlockId, [u8;32], Vec<u8>)> {
+    let h = decode_block_header(buf)?;
+
+    let want = FRAME_HEADER_LEN + h.payload_len as usize;
     if buf.len() < want {
         return Err(NetError::DecodeError);
     }
 
-    Ok((kind, id, hash, buf[52..want].to_vec()))
+    Ok((h.kind, h.id, h.hash, buf[FRAME_HEADER_LEN..want].to_vec()))
 }
 
 /// Типизированное содержимое блока (без id/hash/kind).
@@ -76,6 +106,15 @@ pub fn decode_multi_payload(payload: &[u8]) -> NetResult<MultiRecipe> {
     }
 }
 
+/// MultiInfo из frame'а (None — не Multi или info не записан).
+pub fn decode_multi_info_frame(buf: &[u8]) -> NetResult<Option<MultiInfo>> {
+    let (kind, _id, _hash, payload) = decode_block_frame(buf)?;
+    if kind != BlockKind::Multi {
+        return Ok(None);
+    }
+    Ok(decode_multi_info(&tlv_iter(&payload)?))
+}
+
 pub fn decode_z_payload_from_bytes(payload: &[u8]) -> NetResult<ZPayload> {
     let tlvs = tlv_iter(payload)?;
     match decode_z_payload(&tlvs) {
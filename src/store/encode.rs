@@ -1,4 +1,4 @@
-use crate::types::{ZNodeMeta, OBJ_TYPE_ZNODE, BlockId, BlockKind};
+use crate::types::{BlockId, BlockKind};
 use crate::codec::{
     encode_l0_raw,
     encode_multi_recipe,
@@ -8,6 +8,7 @@ use crate::codec::{
     ObjectPayload,
 };
 use crate::block::multi::MultiRecipe;
+use crate::net_core::error::NetResult;
 
 /// Magic for block frame
 pub const MAGIC: [u8;4] = *b"QBLK";
@@ -35,9 +36,9 @@ pub fn encode_block(kind: BlockKind, id: BlockId, hash: &[u8;32], payload: &[u8]
     v
 }
 
-/// ------------------------
-/// Typed helpers (frames)
-/// ------------------------
+// ------------------------
+// Typed helpers (frames)
+// ------------------------
 
 /// L0: сырые байты блока + id/hash -> полноценный frame.
 pub fn encode_l0_frame(id: BlockId, hash: &[u8;32], raw: &[u8]) -> Vec<u8> {
@@ -46,9 +47,9 @@ pub fn encode_l0_frame(id: BlockId, hash: &[u8;32], raw: &[u8]) -> Vec<u8> {
 }
 
 /// Multi: рецепт MultiRecipe -> frame.
-pub fn encode_multi_frame(id: BlockId, hash: &[u8;32], recipe: &MultiRecipe) -> Vec<u8> {
-    let payload = encode_multi_recipe(recipe);
-    encode_block(BlockKind::Multi, id, hash, &payload)
+pub fn encode_multi_frame(id: BlockId, hash: &[u8;32], recipe: &MultiRecipe) -> NetResult<Vec<u8>> {
+    let payload = encode_multi_recipe(recipe)?;
+    Ok(encode_block(BlockKind::Multi, id, hash, &payload))
 }
 
 /// Z: ZPayload (без id/hash) -> frame.
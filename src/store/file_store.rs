@@ -1,28 +1,35 @@
 use std::fs::{File, OpenOptions};
 use std::io::{Read, Seek, SeekFrom, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 
 use crate::types::{BlockId, BlockKind};
 use crate::store::blockstore::{
-    BlockStore, StoreError, StoreResult,
-    make_frame_l0, make_frame_multi, make_frame_z, make_frame_object,
+    BlockStore, StoreError, StoreResult, PreparedL0,
+    make_frame_l0, make_frame_multi, make_frame_multi_info, make_frame_z, make_frame_object,
     decode_frame_typed,
+    is_zero_block, zero_l0_typed, zero_l0_frame, zero_l0_header,
 };
-use crate::store::decode::BlockBody;
+use crate::store::decode::{BlockBody, BlockHeader, FRAME_HEADER_LEN, decode_block_header};
 use crate::store::encode::MAGIC;
+use crate::store::hash_index::HashIndex;
 
-use crate::codec::{ZPayload, ObjectPayload};
-use crate::block::multi::MultiRecipe;
+use crate::codec::{ZPayload, ObjectPayload, tlv_iter, decode_multi_info};
+use crate::block::multi::{MultiRecipe, MultiInfo};
+
+/// Предел хвоста Multi-payload'а после рецепта (MultiInfo — 45 байт).
+const MULTI_TAIL_MAX: u64 = 4096;
 
 /// Простейшее reference-хранилище:
-/// append-only файл + in-memory индекс id -> offset.
+/// append-only файл + in-memory индекс id -> offset
+/// и индекс хэшей (по префиксу) для contains_hash/find_hash.
 ///
 /// Формат frame см. в store::encode.
 pub struct FileBlockStore {
     path: PathBuf,
     file: Mutex<File>,
     index: Vec<u64>, // offset для каждого BlockId
+    hashes: HashIndex,
 }
 
 impl FileBlockStore {
@@ -33,24 +40,33 @@ impl FileBlockStore {
             .read(true)
             .write(true)
             .create(true)
+            .truncate(false)
             .open(&path)?;
 
         let mut store = Self {
             path,
             file: Mutex::new(file),
             index: Vec::new(),
+            hashes: HashIndex::new(),
         };
 
         store.rebuild_index()?;
         Ok(store)
     }
 
+    /// Путь к файлу-хранилищу.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
     fn rebuild_index(&mut self) -> StoreResult<()> {
         self.index.clear();
+        self.hashes.clear();
 
         let mut offset: u64 = 0;
         loop {
-            let mut hdr = [0u8; 12];
+            // заголовок до конца hash: MAGIC/kind/flags/len + hash
+            let mut hdr = [0u8; 12 + 32];
 
             {
                 let mut f = self
@@ -69,7 +85,7 @@ impl FileBlockStore {
                 }
             }
 
-            if &hdr[0..4] != &MAGIC {
+            if hdr[0..4] != MAGIC {
                 // считаем дальше файл поврежденным/не нашим; останавливаемся
                 break;
             }
@@ -77,6 +93,8 @@ impl FileBlockStore {
             let payload_len = u32_from(&hdr[8..12]) as u64;
             let frame_len = 12 + 32 + 8 + payload_len;
 
+            let id = self.index.len() as BlockId;
+            self.hashes.insert(&frame_hash(&hdr), id);
             self.index.push(offset);
             offset = offset
                 .checked_add(frame_len)
@@ -102,7 +120,7 @@ impl FileBlockStore {
             f.read_exact(&mut hdr)?;
         }
 
-        if &hdr[0..4] != &MAGIC {
+        if hdr[0..4] != MAGIC {
             return Err(StoreError::Corrupt("bad MAGIC".into()));
         }
 
@@ -138,15 +156,46 @@ impl FileBlockStore {
         };
 
         let id = self.index.len() as BlockId;
+        self.hashes.insert(&frame_hash(frame), id);
         self.index.push(offset);
         Ok(id)
     }
+
+    /// Прочитать `buf.len()` байт файла с `offset`.
+    fn read_exact_at(&self, offset: u64, buf: &mut [u8]) -> StoreResult<()> {
+        let mut f = self
+            .file
+            .lock()
+            .map_err(|_| StoreError::Corrupt("file lock poisoned".into()))?;
+        f.seek(SeekFrom::Start(offset))?;
+        f.read_exact(buf)?;
+        Ok(())
+    }
+
+    /// Прочитать с диска только hash из заголовка frame'а.
+    fn read_hash_at(&self, offset: u64) -> StoreResult<[u8; 32]> {
+        let mut h = [0u8; 32];
+        let mut f = self
+            .file
+            .lock()
+            .map_err(|_| StoreError::Corrupt("file lock poisoned".into()))?;
+        f.seek(SeekFrom::Start(offset + 12))?;
+        f.read_exact(&mut h)?;
+        Ok(h)
+    }
 }
 
 fn u32_from(b: &[u8]) -> u32 {
     u32::from_be_bytes([b[0], b[1], b[2], b[3]])
 }
 
+/// hash из заголовка frame'а (байты 12..44).
+fn frame_hash(frame: &[u8]) -> [u8; 32] {
+    let mut h = [0u8; 32];
+    h.copy_from_slice(&frame[12..44]);
+    h
+}
+
 impl BlockStore for FileBlockStore {
     fn put_l0(&mut self, raw: &[u8]) -> StoreResult<BlockId> {
         let id = self.next_id();
@@ -154,9 +203,20 @@ impl BlockStore for FileBlockStore {
         self.append_frame(&frame)
     }
 
+    fn put_l0_prepared(&mut self, p: &mut PreparedL0) -> StoreResult<BlockId> {
+        let id = self.next_id();
+        self.append_frame(p.frame_with_id(id))
+    }
+
     fn put_multi(&mut self, recipe: &MultiRecipe) -> StoreResult<BlockId> {
         let id = self.next_id();
-        let frame = make_frame_multi(id, recipe);
+        let frame = make_frame_multi(id, recipe)?;
+        self.append_frame(&frame)
+    }
+
+    fn put_multi_info(&mut self, recipe: &MultiRecipe, info: &MultiInfo) -> StoreResult<BlockId> {
+        let id = self.next_id();
+        let frame = make_frame_multi_info(id, recipe, info)?;
         self.append_frame(&frame)
     }
 
@@ -173,6 +233,9 @@ impl BlockStore for FileBlockStore {
     }
 
     fn get_typed(&self, id: BlockId) -> StoreResult<(BlockKind, [u8; 32], BlockBody)> {
+        if let Some(typed) = zero_l0_typed(id) {
+            return Ok(typed);
+        }
         if (id as usize) >= self.index.len() {
             return Err(StoreError::OutOfRange(id));
         }
@@ -191,10 +254,74 @@ impl BlockStore for FileBlockStore {
     }
 
     fn get_frame(&self, id: BlockId) -> StoreResult<Vec<u8>> {
+        if let Some(frame) = zero_l0_frame(id) {
+            return Ok(frame);
+        }
         if (id as usize) >= self.index.len() {
             return Err(StoreError::OutOfRange(id));
         }
         let offset = self.index[id as usize];
         self.read_frame_at(offset)
     }
+
+    fn get_header(&self, id: BlockId) -> StoreResult<BlockHeader> {
+        if let Some(h) = zero_l0_header(id) {
+            return Ok(h);
+        }
+        if (id as usize) >= self.index.len() {
+            return Err(StoreError::OutOfRange(id));
+        }
+        let mut hdr = [0u8; FRAME_HEADER_LEN];
+        {
+            let mut f = self
+                .file
+                .lock()
+                .map_err(|_| StoreError::Corrupt("file lock poisoned".into()))?;
+            f.seek(SeekFrom::Start(self.index[id as usize]))?;
+            f.read_exact(&mut hdr)?;
+        }
+        Ok(decode_block_header(&hdr)?)
+    }
+
+    /// Рецепт не читается: после заголовка frame'а — только длина TLV
+    /// рецепта и хвост payload'а за ним (там лежит MultiInfo).
+    fn get_multi_info(&self, id: BlockId) -> StoreResult<Option<MultiInfo>> {
+        let h = self.get_header(id)?;
+        if h.kind != BlockKind::Multi {
+            return Ok(None);
+        }
+        let payload = self.index[id as usize] + FRAME_HEADER_LEN as u64;
+        let mut tl = [0u8; 5];
+        self.read_exact_at(payload, &mut tl)?;
+        let recipe_len = 5 + u32_from(&tl[1..5]) as u64;
+        let tail = (h.payload_len as u64)
+            .checked_sub(recipe_len)
+            .ok_or(StoreError::Corrupt(format!("multi {} recipe overruns payload", id)))?;
+        if tail == 0 {
+            return Ok(None);
+        }
+        if tail > MULTI_TAIL_MAX {
+            return Err(StoreError::Corrupt(format!("multi {} has {} bytes after recipe", id, tail)));
+        }
+        let mut buf = vec![0u8; tail as usize];
+        self.read_exact_at(payload + recipe_len, &mut buf)?;
+        Ok(decode_multi_info(&tlv_iter(&buf)?))
+    }
+
+    fn contains(&self, id: BlockId) -> bool {
+        (id as usize) < self.index.len() || is_zero_block(id)
+    }
+
+    /// При нескольких блоках с одним hash возвращается последний записанный
+    /// (наибольший id): TieredStore при миграции пишет в файл уровня новую
+    /// копию, а старые остаются в нём без владельца.
+    fn find_hash(&self, hash: &[u8; 32]) -> StoreResult<Option<BlockId>> {
+        // кандидаты по префиксу подтверждаем полным hash с диска, с конца
+        for &id in self.hashes.candidates(hash).iter().rev() {
+            if self.read_hash_at(self.index[id as usize])? == *hash {
+                return Ok(Some(id));
+            }
+        }
+        Ok(None)
+    }
 }
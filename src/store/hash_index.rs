@@ -0,0 +1,58 @@
+use std::collections::HashMap;
+
+use smallvec::SmallVec;
+
+use crate::types::BlockId;
+
+fn prefix_of(hash: &[u8; 32]) -> u64 {
+    u64::from_be_bytes([hash[0], hash[1], hash[2], hash[3], hash[4], hash[5], hash[6], hash[7]])
+}
+
+/// In-memory индекс хэшей frame'ов: префикс (u64) -> BlockId.
+///
+/// Полные 32-байтные хэши в памяти не держим: кандидаты по префиксу
+/// подтверждаются чтением заголовка frame'а. Отрицательный ответ
+/// (самый частый при dedup/have-want) — один поиск в map, без IO.
+///
+/// Bloom-фильтр перед индексом не нужен: запись здесь — 8 байт префикса
+/// плюс BlockId, а ответ сразу даёт кандидата. Ложный кандидат возможен
+/// лишь при совпадении 64 бит префикса и отсекается сверкой заголовка;
+/// у Bloom ложные «может быть» шли бы в IO на каждом промахе, и id всё
+/// равно пришлось бы хранить отдельно.
+#[derive(Debug, Clone, Default)]
+pub struct HashIndex {
+    map: HashMap<u64, SmallVec<[BlockId; 1]>>,
+    len: usize,
+}
+
+impl HashIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    pub fn insert(&mut self, hash: &[u8; 32], id: BlockId) {
+        self.map.entry(prefix_of(hash)).or_default().push(id);
+        self.len += 1;
+    }
+
+    /// BlockId, чей хэш совпадает с `hash` по префиксу (в порядке вставки).
+    /// Пустой срез — блока с таким хэшем точно нет.
+    pub fn candidates(&self, hash: &[u8; 32]) -> &[BlockId] {
+        match self.map.get(&prefix_of(hash)) {
+            Some(ids) => ids.as_slice(),
+            None => &[],
+        }
+    }
+}
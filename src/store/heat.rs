@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+use crate::types::BlockId;
+use crate::store::ram_cache::shard_index;
+
+const HEAT_SHARDS: usize = 16;
+
+/// Статистика обращений к одному блоку.
+///
+/// Время — логическое: `HeatTracker` считает каждое чтение одним тиком,
+/// поэтому recency не зависит от часов и нагрузки.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BlockHeat {
+    /// Тик последнего чтения.
+    pub last_access: u64,
+    /// Частота: чтения с последнего `age()` (+ затухающий остаток).
+    pub hits: u64,
+    /// Всего прочитано байт (frame'ы) за всё время.
+    pub bytes_read: u64,
+    /// Размер frame'а при последнем чтении.
+    pub frame_len: u64,
+}
+
+/// Сбор access-heat на пути чтения.
+///
+/// Шардирован по BlockId так же, как кэш RamStore, чтобы не становиться
+/// глобальным локом для параллельных читателей.
+pub struct HeatTracker {
+    shards: Box<[Mutex<HashMap<BlockId, BlockHeat>>]>,
+    shift: u32,
+    clock: AtomicU64,
+}
+
+impl Default for HeatTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HeatTracker {
+    pub fn new() -> Self {
+        Self {
+            shards: (0..HEAT_SHARDS).map(|_| Mutex::new(HashMap::new())).collect(),
+            shift: 64 - HEAT_SHARDS.trailing_zeros(),
+            clock: AtomicU64::new(0),
+        }
+    }
+
+    fn shard(&self, id: BlockId) -> MutexGuard<'_, HashMap<BlockId, BlockHeat>> {
+        self.shards[shard_index(id, self.shift)]
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Текущий логический тик.
+    pub fn now(&self) -> u64 {
+        self.clock.load(Ordering::Relaxed)
+    }
+
+    /// Учесть чтение блока (frame длиной `frame_len`).
+    pub fn record(&self, id: BlockId, frame_len: u64) {
+        let tick = self.clock.fetch_add(1, Ordering::Relaxed) + 1;
+        let mut shard = self.shard(id);
+        let h = shard.entry(id).or_default();
+        h.last_access = tick;
+        h.hits += 1;
+        h.bytes_read += frame_len;
+        h.frame_len = frame_len;
+    }
+
+    pub fn get(&self, id: BlockId) -> Option<BlockHeat> {
+        self.shard(id).get(&id).copied()
+    }
+
+    /// Снимок всех отслеживаемых блоков.
+    pub fn snapshot(&self) -> Vec<(BlockId, BlockHeat)> {
+        let mut out = Vec::new();
+        for s in self.shards.iter() {
+            let s = s.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            out.extend(s.iter().map(|(id, h)| (*id, *h)));
+        }
+        out
+    }
+
+    /// Затухание частоты: hits делится пополам, блоки без чтений
+    /// старше `forget_after` тиков забываются.
+    pub fn age(&self, forget_after: u64) {
+        let now = self.now();
+        for s in self.shards.iter() {
+            let mut s = s.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            s.retain(|_, h| {
+                h.hits /= 2;
+                now.saturating_sub(h.last_access) < forget_after
+            });
+        }
+    }
+
+    pub fn forget(&self, id: BlockId) {
+        self.shard(id).remove(&id);
+    }
+}
+
+impl std::fmt::Debug for HeatTracker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HeatTracker")
+            .field("clock", &self.now())
+            .finish()
+    }
+}
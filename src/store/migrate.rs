@@ -0,0 +1,307 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use crate::types::{BlockId, BlockKind};
+use crate::store::blockstore::{BlockStore, StoreError, StoreResult};
+use crate::store::decode::{BlockBody, FRAME_HEADER_LEN};
+use crate::store::tiered_store::{TieredStore, Tier};
+use crate::store::heat::BlockHeat;
+use crate::graph::object_graph::ObjectGraph;
+
+/// Правило размещения по типу объекта: всё замыкание Object'а
+/// с `obj_type` держим на уровне `tier` (vm-image -> SSD, snapshot -> HDD, ...).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TierRule {
+    pub obj_type: u32,
+    pub tier:     Tier,
+}
+
+/// Политика миграции между уровнями TieredStore.
+#[derive(Debug, Clone)]
+pub struct MigrationPolicy {
+    /// Куда поднимаем горячие блоки.
+    pub hot_tier:  Tier,
+    /// Куда опускаем холодные блоки.
+    pub cold_tier: Tier,
+
+    /// Минимум чтений (с последнего прогона, с затуханием) для promote.
+    pub promote_min_hits: u64,
+    /// Сколько тиков (чтений по всему store) без обращений до demote.
+    pub demote_idle_ticks: u64,
+    /// Через сколько тиков простоя блок забывается трекером.
+    pub forget_after_ticks: u64,
+
+    /// Бюджет одного прогона: кол-во перемещений.
+    pub max_moves: usize,
+    /// Бюджет одного прогона: байт frame'ов.
+    pub max_bytes: u64,
+
+    /// Правила по obj_type; применяются к Object'ам, которые читались,
+    /// и имеют приоритет над heat-решениями.
+    pub rules: Vec<TierRule>,
+}
+
+impl MigrationPolicy {
+    pub fn new(hot_tier: Tier, cold_tier: Tier) -> Self {
+        Self {
+            hot_tier,
+            cold_tier,
+            promote_min_hits: 4,
+            demote_idle_ticks: 100_000,
+            forget_after_ticks: 1_000_000,
+            max_moves: 1024,
+            max_bytes: 256 * 1024 * 1024,
+            rules: Vec::new(),
+        }
+    }
+}
+
+/// Итог прогона мигратора.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MigrationReport {
+    pub promoted:  u64,
+    pub demoted:   u64,
+    /// Перемещено по правилам obj_type.
+    pub pinned:    u64,
+    pub bytes_moved: u64,
+    /// Кандидаты, не влезшие в бюджет прогона.
+    pub deferred:  u64,
+}
+
+impl MigrationReport {
+    fn add(&mut self, o: &MigrationReport) {
+        self.promoted += o.promoted;
+        self.demoted += o.demoted;
+        self.pinned += o.pinned;
+        self.bytes_moved += o.bytes_moved;
+        self.deferred += o.deferred;
+    }
+}
+
+/// Мигратор: по heat из `TieredStore::heat()` поднимает горячие блоки,
+/// опускает холодные и раскладывает объекты по правилам obj_type.
+#[derive(Debug)]
+pub struct Migrator {
+    pub policy: MigrationPolicy,
+    cache: Mutex<RuleCache>,
+}
+
+/// Сколько Object'ов помним между прогонами (дальше кэш сбрасывается).
+const RULE_CACHE_MAX: usize = 4096;
+
+/// Кэш правил между прогонами: блоки неизменяемы, поэтому вид/obj_type
+/// блока и замыкание Object'а вычисляются один раз.
+#[derive(Debug, Default)]
+struct RuleCache {
+    /// None — блок не Object.
+    obj_types: HashMap<BlockId, Option<u32>>,
+    closures:  HashMap<BlockId, Arc<[BlockId]>>,
+}
+
+impl Clone for Migrator {
+    /// Копия политики; кэш правил у копии свой.
+    fn clone(&self) -> Self {
+        Self::new(self.policy.clone())
+    }
+}
+
+struct Budget {
+    moves: usize,
+    bytes: u64,
+}
+
+impl Migrator {
+    pub fn new(policy: MigrationPolicy) -> Self {
+        Self { policy, cache: Mutex::default() }
+    }
+
+    /// Один прогон миграции в пределах бюджета.
+    pub fn run_once(&self, store: &mut TieredStore) -> StoreResult<MigrationReport> {
+        let p = &self.policy;
+        let now = store.heat().now();
+        let snapshot = store.heat().snapshot();
+
+        let mut report = MigrationReport::default();
+        let mut budget = Budget {
+            moves: p.max_moves,
+            bytes: p.max_bytes,
+        };
+
+        // 1) правила по obj_type — служебные чтения в heat не попадают
+        let tracking = store.heat_tracking();
+        store.set_heat_tracking(false);
+        let pinned = self.apply_rules(store, &snapshot, &mut budget, &mut report);
+        store.set_heat_tracking(tracking);
+        let pinned = pinned?;
+
+        // 2) promote: самые частые вперёд
+        let mut hot: Vec<_> = snapshot
+            .iter()
+            .filter(|(id, h)| {
+                !pinned.contains_key(id)
+                    && h.hits >= p.promote_min_hits
+                    && store.tier_of(*id).is_some_and(|t| t != p.hot_tier)
+            })
+            .collect();
+        hot.sort_by(|a, b| b.1.hits.cmp(&a.1.hits).then(a.0.cmp(&b.0)));
+        for (id, h) in hot {
+            if Self::try_move(store, *id, p.hot_tier, h.frame_len, &mut budget, &mut report)? {
+                report.promoted += 1;
+            }
+        }
+
+        // 3) demote: самые давние вперёд
+        let mut cold: Vec<_> = snapshot
+            .iter()
+            .filter(|(id, h)| {
+                !pinned.contains_key(id)
+                    && now.saturating_sub(h.last_access) >= p.demote_idle_ticks
+                    && store.tier_of(*id).is_some_and(|t| t != p.cold_tier)
+            })
+            .collect();
+        cold.sort_by(|a, b| a.1.last_access.cmp(&b.1.last_access).then(a.0.cmp(&b.0)));
+        for (id, h) in cold {
+            if Self::try_move(store, *id, p.cold_tier, h.frame_len, &mut budget, &mut report)? {
+                report.demoted += 1;
+            }
+        }
+
+        store.heat().age(p.forget_after_ticks);
+        Ok(report)
+    }
+
+    fn try_move(
+        store: &mut TieredStore,
+        id: BlockId,
+        to: Tier,
+        bytes: u64,
+        budget: &mut Budget,
+        report: &mut MigrationReport,
+    ) -> StoreResult<bool> {
+        if budget.moves == 0 || bytes > budget.bytes {
+            report.deferred += 1;
+            return Ok(false);
+        }
+        store.move_block(id, to)?;
+        budget.moves -= 1;
+        budget.bytes -= bytes;
+        report.bytes_moved += bytes;
+        Ok(true)
+    }
+
+    /// Разложить по правилам замыкания прочитанных Object'ов.
+    /// Возвращает все блоки, подпадающие под правила (их heat не двигает).
+    fn apply_rules(
+        &self,
+        store: &mut TieredStore,
+        snapshot: &[(BlockId, BlockHeat)],
+        budget: &mut Budget,
+        report: &mut MigrationReport,
+    ) -> StoreResult<HashMap<BlockId, Tier>> {
+        let mut pinned = HashMap::new();
+        if self.policy.rules.is_empty() {
+            return Ok(pinned);
+        }
+
+        let mut cache = self
+            .cache
+            .lock()
+            .map_err(|_| StoreError::Corrupt("migrator cache lock poisoned".into()))?;
+        if cache.obj_types.len() > RULE_CACHE_MAX {
+            *cache = RuleCache::default();
+        }
+
+        for (id, _) in snapshot {
+            let obj_type = match cache.obj_types.get(id) {
+                Some(t) => *t,
+                None => {
+                    // frame читаем только у Object'ов: у L0 он может быть большим
+                    let t = match store.get_header(*id)?.kind {
+                        BlockKind::Object => match store.get_typed(*id)?.2 {
+                            BlockBody::Object(o) => Some(o.obj_type),
+                            _ => None,
+                        },
+                        _ => None,
+                    };
+                    cache.obj_types.insert(*id, t);
+                    t
+                }
+            };
+            let Some(obj_type) = obj_type else {
+                continue;
+            };
+            let Some(rule) = self.policy.rules.iter().find(|r| r.obj_type == obj_type) else {
+                continue;
+            };
+            let closure = match cache.closures.get(id) {
+                Some(c) => c.clone(),
+                None => {
+                    let c: Arc<[BlockId]> = ObjectGraph::new(&*store).compute_closure_from_block(*id)?.blocks.into();
+                    cache.closures.insert(*id, c.clone());
+                    c
+                }
+            };
+            for b in closure.iter() {
+                pinned.entry(*b).or_insert(rule.tier);
+            }
+        }
+        drop(cache);
+
+        let mut ids: Vec<(BlockId, Tier)> = pinned.iter().map(|(b, t)| (*b, *t)).collect();
+        ids.sort_unstable();
+        for (id, tier) in ids {
+            if store.tier_of(id) == Some(tier) {
+                continue;
+            }
+            let bytes = store.get_header(id)?.payload_len as u64 + FRAME_HEADER_LEN as u64;
+            if Self::try_move(store, id, tier, bytes, budget, report)? {
+                report.pinned += 1;
+            }
+        }
+        Ok(pinned)
+    }
+
+    /// Запустить мигратор в фоне: `run_once` каждые `interval`
+    /// под локом store, до `MigratorHandle::stop`.
+    pub fn spawn(self, store: Arc<Mutex<TieredStore>>, interval: Duration) -> MigratorHandle {
+        let stop = Arc::new(AtomicBool::new(false));
+        let flag = stop.clone();
+
+        let thread = std::thread::spawn(move || {
+            let mut total = MigrationReport::default();
+            while !flag.load(Ordering::Relaxed) {
+                {
+                    let mut s = store
+                        .lock()
+                        .map_err(|_| StoreError::Corrupt("tiered store lock poisoned".into()))?;
+                    total.add(&self.run_once(&mut s)?);
+                }
+                std::thread::park_timeout(interval);
+            }
+            Ok(total)
+        });
+
+        MigratorHandle { stop, thread }
+    }
+}
+
+/// Хэндл фонового мигратора.
+pub struct MigratorHandle {
+    stop:   Arc<AtomicBool>,
+    thread: JoinHandle<StoreResult<MigrationReport>>,
+}
+
+impl MigratorHandle {
+    /// Остановить мигратор и получить суммарный отчёт
+    /// (или первую ошибку, на которой он остановился).
+    pub fn stop(self) -> StoreResult<MigrationReport> {
+        self.stop.store(true, Ordering::Relaxed);
+        self.thread.thread().unpark();
+        self.thread
+            .join()
+            .map_err(|_| StoreError::Corrupt("migrator thread panicked".into()))?
+    }
+}
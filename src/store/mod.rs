@@ -2,6 +2,7 @@ pub mod encode;
 pub mod decode;
 pub mod blockstore;
 pub mod file_store;
+pub mod hash_index;
 
 pub use encode::*;
 pub use decode::*;
@@ -9,3 +10,9 @@ pub use blockstore::*;
 pub use file_store::*;
 
 pub mod ram_store;
+pub mod tiered_store;
+pub mod heat;
+pub mod migrate;
+pub mod typed;
+pub mod refs;
+mod ram_cache;
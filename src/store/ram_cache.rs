@@ -0,0 +1,232 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::types::BlockId;
+
+/// Оценка накладных расходов на одну запись кэша (map + очередь + Arc).
+const ENTRY_OVERHEAD: u64 = 64;
+
+/// Шардов по умолчанию: с запасом под 32+ читающих потоков.
+pub const DEFAULT_SHARDS: usize = 64;
+
+/// Минимальная доля лимита на шард: frame крупнее доли не кэшируется,
+/// поэтому при маленьком лимите шардов меньше.
+const MIN_SHARD_BYTES: u64 = 4 * 1024 * 1024;
+
+/// Запись кэша: готовый frame + бит "второго шанса" для CLOCK.
+struct Entry {
+    frame: Arc<[u8]>,
+    referenced: bool,
+    /// Загружен prefetch'ем и ещё ни разу не читался.
+    prefetched: bool,
+}
+
+impl Entry {
+    fn cost(&self) -> u64 {
+        self.frame.len() as u64 + ENTRY_OVERHEAD
+    }
+}
+
+/// Один шард: свой map, своя CLOCK-очередь, свой лимит и свои счётчики.
+///
+/// Счётчики живут под тем же локом, что и данные, чтобы потоки
+/// не дрались за общие атомики.
+#[derive(Default)]
+struct Shard {
+    map: HashMap<BlockId, Entry>,
+    clock: VecDeque<BlockId>,
+    limit_bytes: u64,
+    used_bytes: u64,
+
+    hits: u64,
+    misses: u64,
+    inserts: u64,
+    evictions: u64,
+    prefetched: u64,
+    prefetch_hits: u64,
+}
+
+impl Shard {
+    fn get(&mut self, id: BlockId) -> Option<Arc<[u8]>> {
+        match self.map.get_mut(&id) {
+            Some(e) => {
+                e.referenced = true;
+                self.hits += 1;
+                if e.prefetched {
+                    e.prefetched = false;
+                    self.prefetch_hits += 1;
+                }
+                Some(e.frame.clone())
+            }
+            None => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    fn insert(&mut self, id: BlockId, frame: Arc<[u8]>, prefetched: bool) {
+        if self.map.contains_key(&id) {
+            return;
+        }
+        let entry = Entry { frame, referenced: false, prefetched };
+        let cost = entry.cost();
+        if cost > self.limit_bytes {
+            // блок больше всего шарда — не кэшируем
+            return;
+        }
+
+        while self.used_bytes + cost > self.limit_bytes {
+            if !self.evict_one() {
+                break;
+            }
+        }
+
+        self.used_bytes += cost;
+        self.map.insert(id, entry);
+        self.clock.push_back(id);
+        self.inserts += 1;
+        if prefetched {
+            self.prefetched += 1;
+        }
+    }
+
+    /// CLOCK / second chance: блоки с referenced=true получают ещё круг.
+    fn evict_one(&mut self) -> bool {
+        while let Some(id) = self.clock.pop_front() {
+            let Some(e) = self.map.get_mut(&id) else {
+                continue;
+            };
+            if e.referenced {
+                e.referenced = false;
+                self.clock.push_back(id);
+                continue;
+            }
+            let cost = e.cost();
+            self.map.remove(&id);
+            self.used_bytes -= cost;
+            self.evictions += 1;
+            return true;
+        }
+        false
+    }
+}
+
+/// Индекс шарда для `id` при `64 - shift` битах шардов.
+///
+/// BlockId обычно идут подряд — перемешиваем (fibonacci hashing).
+pub(crate) fn shard_index(id: BlockId, shift: u32) -> usize {
+    if shift >= 64 {
+        0
+    } else {
+        (id.wrapping_mul(0x9E37_79B9_7F4A_7C15) >> shift) as usize
+    }
+}
+
+/// Агрегированные счётчики по всем шардам.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct CacheCounters {
+    pub used_bytes: u64,
+    pub blocks: u64,
+    pub hits: u64,
+    pub misses: u64,
+    pub inserts: u64,
+    pub evictions: u64,
+    pub prefetched: u64,
+    pub prefetch_hits: u64,
+}
+
+/// Шардированный кэш frame'ов: BlockId -> Arc<[u8]>.
+///
+/// Шард выбирается по (перемешанному) BlockId, у каждого шарда свой Mutex,
+/// поэтому читатели разных блоков почти не пересекаются по локам.
+pub(crate) struct ShardedCache {
+    shards: Box<[Mutex<Shard>]>,
+    shift: u32,
+}
+
+impl ShardedCache {
+    /// `shards` округляется вверх до степени двойки и урезается так, чтобы
+    /// на шард приходилось не меньше `MIN_SHARD_BYTES`; лимит делится поровну.
+    pub fn new(limit_bytes: u64, shards: usize) -> Self {
+        let fit = (limit_bytes / MIN_SHARD_BYTES).max(1);
+        // наибольшая степень двойки <= fit
+        let fit = 1usize << (63 - fit.leading_zeros()).min(usize::BITS - 2);
+        let n = shards.max(1).next_power_of_two().min(fit);
+        let per_shard = if limit_bytes == u64::MAX {
+            u64::MAX
+        } else {
+            limit_bytes / n as u64
+        };
+
+        let shards = (0..n)
+            .map(|_| {
+                Mutex::new(Shard {
+                    limit_bytes: per_shard,
+                    ..Shard::default()
+                })
+            })
+            .collect::<Vec<_>>()
+            .into_boxed_slice();
+
+        Self {
+            shards,
+            shift: 64 - n.trailing_zeros(),
+        }
+    }
+
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+
+    fn shard(&self, id: BlockId) -> MutexGuard<'_, Shard> {
+        let idx = shard_index(id, self.shift);
+        // отравленный лок не фатален для кэша: данные внутри консистентны
+        self.shards[idx]
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub fn get(&self, id: BlockId) -> Option<Arc<[u8]>> {
+        self.shard(id).get(id)
+    }
+
+    pub fn insert(&self, id: BlockId, frame: Arc<[u8]>) {
+        self.shard(id).insert(id, frame, false)
+    }
+
+    /// Вставка от prefetch'а: первое последующее попадание
+    /// считается в prefetch_hits.
+    pub fn insert_prefetched(&self, id: BlockId, frame: Arc<[u8]>) {
+        self.shard(id).insert(id, frame, true)
+    }
+
+    /// Есть ли блок в кэше (без учёта в hits/misses и без CLOCK-бита).
+    pub fn contains(&self, id: BlockId) -> bool {
+        self.shard(id).map.contains_key(&id)
+    }
+
+    pub fn counters(&self) -> CacheCounters {
+        let mut c = CacheCounters::default();
+        for s in self.shards.iter() {
+            let s = s.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            c.used_bytes += s.used_bytes;
+            c.blocks += s.map.len() as u64;
+            c.hits += s.hits;
+            c.misses += s.misses;
+            c.inserts += s.inserts;
+            c.evictions += s.evictions;
+            c.prefetched += s.prefetched;
+            c.prefetch_hits += s.prefetch_hits;
+        }
+        c
+    }
+}
+
+impl std::fmt::Debug for ShardedCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ShardedCache")
+            .field("shards", &self.shards.len())
+            .finish()
+    }
+}
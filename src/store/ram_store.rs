@@ -1,5 +1,16 @@
-use crate::store::blockstore::BlockStore;
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use crate::types::{BlockId, BlockKind};
+use crate::store::blockstore::{
+    BlockStore, StoreError, StoreResult, PreparedL0, decode_frame_typed,
+    is_zero_block, zero_l0_typed, zero_l0_frame, zero_l0_header,
+};
+use crate::store::decode::{BlockBody, BlockHeader, decode_block_header};
+use crate::store::ram_cache::{ShardedCache, DEFAULT_SHARDS};
+use crate::codec::{ZPayload, ObjectPayload};
+use crate::block::multi::{MultiRecipe, MultiInfo};
+use crate::graph::object_graph::children_from_body;
+use crate::config::QuarxConfig;
 
 /// Snapshot статистики RAM-tier.
 #[derive(Debug, Clone, Copy)]
@@ -19,51 +30,110 @@ pub struct RamStats {
     pub misses: u64,
     pub inserts: u64,
     pub evictions: u64,
+
+    /// Блоков загружено prefetch'ем.
+    pub prefetched: u64,
+    /// Попаданий в блоки, загруженные prefetch'ем (каждый считается один раз).
+    pub prefetch_hits: u64,
 }
 
-/// RamStore — будущий RAM-tier / кэш над любым BlockStore.
+/// Настройки graph-aware prefetch (см. `RamStore::prefetch`).
 ///
-/// Сейчас:
-///   - это точка расширения (тип присутствует во внешнем API),
-///   - счётчики/лимит есть, но реального кэша ещё нет,
-///   - BlockStore для RamStore не реализован, чтобы не ломать существующий код.
+/// Дети Multi/Z/Object-блока (см. `graph::children_from_body`)
+/// подгружаются в кэш заранее, до их чтения.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrefetchConfig {
+    /// Глубина: 0 — выключено, 1 — только прямые дети, 2 — дети детей, ...
+    pub depth: u32,
+    /// Сколько байт frame'ов максимум подгрузить за один вызов.
+    pub byte_budget: u64,
+}
+
+impl PrefetchConfig {
+    pub const fn disabled() -> Self {
+        Self { depth: 0, byte_budget: 0 }
+    }
+
+    pub fn from_config(cfg: &QuarxConfig) -> Self {
+        Self {
+            depth: cfg.ram_prefetch_depth,
+            byte_budget: cfg.ram_prefetch_bytes,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.depth != 0 && self.byte_budget != 0
+    }
+}
+
+impl Default for PrefetchConfig {
+    fn default() -> Self {
+        Self::disabled()
+    }
+}
+
+/// RamStore — RAM-tier / кэш frame'ов над любым BlockStore.
+///
+/// Кэш шардирован по BlockId (см. `store::ram_cache`): у каждого шарда
+/// свой лок, своя CLOCK-очередь и свои счётчики, поэтому много потоков
+/// читают параллельно, не упираясь в один глобальный Mutex.
+/// `stats()` собирает счётчики всех шардов в один `RamStats`.
 ///
-/// Дальше сюда добавится:
-///   - внутренняя LRU / ARC-структура для блоков,
-///   - impl BlockStore для прозрачного кэширования get/put.
+/// Запись (put_*) идёт сквозь кэш в inner; кэш наполняется на чтении
+/// и явным `prefetch(id)` (см. `PrefetchConfig`). Сам `get_typed` граф
+/// не обходит: чтение не платит за подгрузку, которую никто не просил.
 #[derive(Debug)]
 pub struct RamStore<S: BlockStore> {
     inner: S,
     /// Конфигурированный лимит RAM (байт).
     pub limit_bytes: u64,
 
-    /// Счётчики и оценки (для будущего кэша).
-    used_bytes: AtomicU64,
-    blocks: AtomicU64,
-
-    hits: AtomicU64,
-    misses: AtomicU64,
-    inserts: AtomicU64,
-    evictions: AtomicU64,
+    cache: ShardedCache,
+    prefetch: PrefetchConfig,
 }
 
 impl<S: BlockStore> RamStore<S> {
-    /// Создать RAM-обёртку над существующим BlockStore.
-    ///
-    /// Сейчас это просто прокладка с лимитом и счётчиками.
+    /// Создать RAM-обёртку над существующим BlockStore
+    /// с числом шардов по умолчанию.
     pub fn new(inner: S, limit_bytes: u64) -> Self {
+        Self::with_shards(inner, limit_bytes, DEFAULT_SHARDS)
+    }
+
+    /// Создать RAM-обёртку с явным числом шардов
+    /// (см. `ShardedCache::new`: степень двойки, не мельче доли лимита).
+    pub fn with_shards(inner: S, limit_bytes: u64, shards: usize) -> Self {
         Self {
             inner,
             limit_bytes,
-            used_bytes: AtomicU64::new(0),
-            blocks: AtomicU64::new(0),
-            hits: AtomicU64::new(0),
-            misses: AtomicU64::new(0),
-            inserts: AtomicU64::new(0),
-            evictions: AtomicU64::new(0),
+            cache: ShardedCache::new(limit_bytes, shards),
+            prefetch: PrefetchConfig::disabled(),
         }
     }
 
+    /// Включить/перенастроить prefetch (по умолчанию выключен).
+    pub fn set_prefetch(&mut self, prefetch: PrefetchConfig) {
+        self.prefetch = prefetch;
+    }
+
+    pub fn prefetch_config(&self) -> PrefetchConfig {
+        self.prefetch
+    }
+
+    /// Подгрузить в кэш блок `id` и его потомков: обход в ширину до
+    /// `depth` уровней, в пределах `byte_budget` (сам `id` в бюджет не
+    /// входит). Вызывается тем, кто знает, что скоро пойдёт по графу
+    /// (чтение объекта, restore) — можно и из отдельного потока, кэш
+    /// общий. Ошибки чтения потомков не всплывают.
+    ///
+    /// Возвращает, сколько блоков загружено prefetch'ем.
+    pub fn prefetch(&self, id: BlockId) -> StoreResult<u64> {
+        if !self.is_enabled() || !self.prefetch.is_enabled() || is_zero_block(id) {
+            return Ok(0);
+        }
+        let (kind, _, _, body) = decode_frame_typed(&self.cached_frame(id)?)?;
+        Ok(self.prefetch_children(kind, &body))
+    }
+
     /// Доступ к базовому BlockStore (read-only).
     pub fn inner(&self) -> &S {
         &self.inner
@@ -94,57 +164,147 @@ impl<S: BlockStore> RamStore<S> {
         self.limit_bytes == u64::MAX
     }
 
-    /// Snapshot статистики RAM-tier.
-    ///
-    /// Пока все поля будут нулевыми (used_bytes/blocks/hits/...),
-    /// т.к. кэширующая логика ещё не реализована.
+    /// Кол-во шардов кэша.
+    pub fn shards(&self) -> usize {
+        self.cache.shard_count()
+    }
+
+    /// Snapshot статистики RAM-tier (сумма по всем шардам).
     pub fn stats(&self) -> RamStats {
+        let c = self.cache.counters();
         RamStats {
             limit_bytes: self.limit_bytes,
-            used_bytes: self.used_bytes.load(Ordering::Relaxed),
-            blocks: self.blocks.load(Ordering::Relaxed),
-            hits: self.hits.load(Ordering::Relaxed),
-            misses: self.misses.load(Ordering::Relaxed),
-            inserts: self.inserts.load(Ordering::Relaxed),
-            evictions: self.evictions.load(Ordering::Relaxed),
+            used_bytes: c.used_bytes,
+            blocks: c.blocks,
+            hits: c.hits,
+            misses: c.misses,
+            inserts: c.inserts,
+            evictions: c.evictions,
+            prefetched: c.prefetched,
+            prefetch_hits: c.prefetch_hits,
         }
     }
 
-    /// Вспомогательные методы для будущего кэша.
-    ///
-    /// Сейчас не используются снаружи, но оставлены как контракт.
-    pub(crate) fn add_used_bytes(&self, delta: i64) {
-        if delta >= 0 {
-            self.used_bytes
-                .fetch_add(delta as u64, Ordering::Relaxed);
-        } else {
-            self.used_bytes
-                .fetch_sub((-delta) as u64, Ordering::Relaxed);
+    /// Frame из кэша или из inner (с последующей вставкой в кэш).
+    fn cached_frame(&self, id: BlockId) -> StoreResult<Arc<[u8]>> {
+        if !self.is_enabled() {
+            return Ok(self.inner.get_frame(id)?.into());
+        }
+        if let Some(frame) = self.cache.get(id) {
+            return Ok(frame);
+        }
+        let frame: Arc<[u8]> = self.inner.get_frame(id)?.into();
+        self.cache.insert(id, frame.clone());
+        Ok(frame)
+    }
+
+    fn prefetch_children(&self, kind: BlockKind, body: &BlockBody) -> u64 {
+        let mut loaded = 0;
+        let mut budget = self.prefetch.byte_budget;
+        let mut level = children_from_body(kind, body);
+
+        for depth in 1..=self.prefetch.depth {
+            let mut next = Vec::new();
+            for id in level {
+                if self.cache.contains(id) || !self.inner.contains(id) {
+                    continue;
+                }
+                let Ok(frame) = self.inner.get_frame(id) else {
+                    continue;
+                };
+                if frame.len() as u64 > budget {
+                    return loaded;
+                }
+                budget -= frame.len() as u64;
+
+                if depth < self.prefetch.depth {
+                    if let Ok((k, _, _, b)) = decode_frame_typed(&frame) {
+                        next.extend(children_from_body(k, &b));
+                    }
+                }
+                self.cache.insert_prefetched(id, frame.into());
+                loaded += 1;
+            }
+            if next.is_empty() {
+                break;
+            }
+            level = next;
+        }
+        loaded
+    }
+}
+
+impl<S: BlockStore> BlockStore for RamStore<S> {
+    fn put_l0(&mut self, raw: &[u8]) -> StoreResult<BlockId> {
+        self.inner.put_l0(raw)
+    }
+
+    fn put_l0_prepared(&mut self, p: &mut PreparedL0) -> StoreResult<BlockId> {
+        self.inner.put_l0_prepared(p)
+    }
+
+    fn put_multi(&mut self, recipe: &MultiRecipe) -> StoreResult<BlockId> {
+        self.inner.put_multi(recipe)
+    }
+
+    fn put_multi_info(&mut self, recipe: &MultiRecipe, info: &MultiInfo) -> StoreResult<BlockId> {
+        self.inner.put_multi_info(recipe, info)
+    }
+
+    fn put_z(&mut self, z: &ZPayload) -> StoreResult<BlockId> {
+        self.inner.put_z(z)
+    }
+
+    fn put_object(&mut self, o: &ObjectPayload) -> StoreResult<BlockId> {
+        self.inner.put_object(o)
+    }
+
+    fn get_typed(&self, id: BlockId) -> StoreResult<(BlockKind, [u8; 32], BlockBody)> {
+        // нулевые L0 не кэшируем: они синтезируются без IO
+        if let Some(typed) = zero_l0_typed(id) {
+            return Ok(typed);
+        }
+        let frame = self.cached_frame(id)?;
+        let (kind, decoded_id, hash, body) = decode_frame_typed(&frame)?;
+
+        if decoded_id != id {
+            return Err(StoreError::Corrupt(format!(
+                "id mismatch: requested {}, frame {}",
+                id, decoded_id
+            )));
         }
+
+        Ok((kind, hash, body))
     }
 
-    pub(crate) fn inc_blocks(&self, delta: i64) {
-        if delta >= 0 {
-            self.blocks.fetch_add(delta as u64, Ordering::Relaxed);
-        } else {
-            self.blocks.fetch_sub((-delta) as u64, Ordering::Relaxed);
+    fn get_frame(&self, id: BlockId) -> StoreResult<Vec<u8>> {
+        if let Some(frame) = zero_l0_frame(id) {
+            return Ok(frame);
         }
+        Ok(self.cached_frame(id)?.to_vec())
     }
 
-    pub(crate) fn inc_hit(&self) {
-        self.hits.fetch_add(1, Ordering::Relaxed);
+    fn get_header(&self, id: BlockId) -> StoreResult<BlockHeader> {
+        if let Some(h) = zero_l0_header(id) {
+            return Ok(h);
+        }
+        // заголовок из кэша, если frame уже там; иначе дешёвый путь inner
+        if self.is_enabled() && self.cache.contains(id) {
+            return Ok(decode_block_header(&self.cached_frame(id)?)?);
+        }
+        self.inner.get_header(id)
     }
 
-    pub(crate) fn inc_miss(&self) {
-        self.misses.fetch_add(1, Ordering::Relaxed);
+    fn block_tier(&self, id: BlockId) -> u8 {
+        self.inner.block_tier(id)
     }
 
-    pub(crate) fn inc_insert(&self) {
-        self.inserts.fetch_add(1, Ordering::Relaxed);
+    fn contains(&self, id: BlockId) -> bool {
+        is_zero_block(id) || self.inner.contains(id)
     }
 
-    pub(crate) fn inc_eviction(&self) {
-        self.evictions.fetch_add(1, Ordering::Relaxed);
+    fn find_hash(&self, hash: &[u8; 32]) -> StoreResult<Option<BlockId>> {
+        self.inner.find_hash(hash)
     }
 }
 
@@ -0,0 +1,234 @@
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::types::ObjectId;
+use crate::codec::{RefLogEntry, REF_RECORD_TAG, encode_ref_entry, decode_ref_entry};
+use crate::store::blockstore::{BlockStore, StoreError, StoreResult};
+use crate::graph::object_graph::{GraphClosure, ObjectGraph};
+
+/// Предел длины имени ссылки (байт).
+const REF_NAME_MAX: usize = 1024;
+
+/// Путь журнала ссылок для store в файле `store`: `<store>.refs`.
+pub fn refs_path(store: &Path) -> PathBuf {
+    let mut p = store.as_os_str().to_owned();
+    p.push(".refs");
+    PathBuf::from(p)
+}
+
+/// Именованные ссылки (`backups/db/2026-10-01` -> ObjectId).
+///
+/// Хранятся append-only журналом изменений (`codec::refs`); текущие
+/// значения и reflog восстанавливаются проигрыванием журнала при открытии.
+/// Изменение — compare-and-swap под эксклюзивной блокировкой файла:
+/// перед сравнением дочитываются записи других процессов, так что CAS
+/// атомарен и между процессами. Оборванная запись в конце (сбой при
+/// записи: недописанная, с мусором или нулями вместо данных)
+/// отбрасывается и затирается следующим изменением.
+pub struct RefStore {
+    path: PathBuf,
+    file: File,
+    /// Сколько байт журнала проиграно (дальше — чужие дописи или обрыв).
+    len:  u64,
+    refs: BTreeMap<String, ObjectId>,
+    log:  Vec<RefLogEntry>,
+}
+
+impl RefStore {
+    /// Открыть или создать журнал ссылок.
+    pub fn open(path: PathBuf) -> StoreResult<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)?;
+        let mut refs = Self {
+            path,
+            file,
+            len: 0,
+            refs: BTreeMap::new(),
+            log: Vec::new(),
+        };
+        refs.refresh()?;
+        Ok(refs)
+    }
+
+    /// Журнал ссылок рядом с файлом store (см. `refs_path`).
+    pub fn open_for_store(store: &Path) -> StoreResult<Self> {
+        Self::open(refs_path(store))
+    }
+
+    /// Путь к журналу.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Дочитать изменения, дописанные другими процессами.
+    ///
+    /// Битая запись в самом конце журнала (или перед одними нулями) —
+    /// оборванный хвост: разбор останавливается на ней. Битая запись, за
+    /// которой есть данные, — `Corrupt`.
+    pub fn refresh(&mut self) -> StoreResult<()> {
+        let mut tail = Vec::new();
+        self.file.seek(SeekFrom::Start(self.len))?;
+        self.file.read_to_end(&mut tail)?;
+
+        let mut off = 0usize;
+        while tail.len() - off >= 5 {
+            let len = u32::from_be_bytes([tail[off + 1], tail[off + 2], tail[off + 3], tail[off + 4]]) as usize;
+            let Some(val) = tail.get(off + 5..off + 5 + len) else {
+                break;
+            };
+            let e = if tail[off] == REF_RECORD_TAG { decode_ref_entry(val) } else { None };
+            let Some(e) = e else {
+                if tail[off + 5 + len..].iter().all(|&b| b == 0) {
+                    break;
+                }
+                return Err(StoreError::Corrupt(format!("refs log: bad record at {}", self.len + off as u64)));
+            };
+            self.apply(e);
+            off += 5 + len;
+        }
+        self.len += off as u64;
+        Ok(())
+    }
+
+    /// Текущее значение ссылки.
+    pub fn get(&self, name: &str) -> Option<ObjectId> {
+        self.refs.get(name).copied()
+    }
+
+    /// Ссылки с именем, начинающимся на `prefix`, по алфавиту.
+    pub fn list(&self, prefix: &str) -> Vec<(String, ObjectId)> {
+        self.refs
+            .range::<str, _>((std::ops::Bound::Included(prefix), std::ops::Bound::Unbounded))
+            .take_while(|(k, _)| k.starts_with(prefix))
+            .map(|(k, v)| (k.clone(), *v))
+            .collect()
+    }
+
+    /// Все изменения ссылки `name`, от старых к новым.
+    pub fn reflog(&self, name: &str) -> Vec<RefLogEntry> {
+        self.log.iter().filter(|e| e.name == name).cloned().collect()
+    }
+
+    /// Атомарно заменить `expected` на `new` (None — ссылки нет / удалить).
+    ///
+    /// false — текущее значение не `expected`, журнал не тронут.
+    pub fn compare_and_swap(
+        &mut self,
+        name: &str,
+        expected: Option<ObjectId>,
+        new: Option<ObjectId>,
+        message: &str,
+    ) -> StoreResult<bool> {
+        self.update(name, Some(expected), new, message)
+    }
+
+    /// Установить ссылку безусловно.
+    pub fn set(&mut self, name: &str, id: ObjectId, message: &str) -> StoreResult<()> {
+        self.update(name, None, Some(id), message).map(|_| ())
+    }
+
+    /// Удалить ссылку, если она указывает на `expected`.
+    pub fn delete(&mut self, name: &str, expected: ObjectId, message: &str) -> StoreResult<bool> {
+        self.update(name, Some(Some(expected)), None, message)
+    }
+
+    /// Корни GC: текущие значения ссылок, с `reflog` — и все прошлые.
+    pub fn gc_roots(&self, reflog: bool) -> Vec<ObjectId> {
+        let mut roots: Vec<ObjectId> = self.refs.values().copied().collect();
+        if reflog {
+            roots.extend(self.log.iter().flat_map(|e| e.old.into_iter().chain(e.new)));
+        }
+        roots.sort_unstable();
+        roots.dedup();
+        roots
+    }
+
+    /// Живые блоки `store`: замыкание корней GC (см. `gc_roots`).
+    pub fn live_closure<S: BlockStore>(&self, store: &S, reflog: bool) -> StoreResult<GraphClosure> {
+        ObjectGraph::new(store).compute_closure_from_roots(&self.gc_roots(reflog))
+    }
+
+    /// `expected`: None — без проверки, Some(v) — CAS против v.
+    fn update(
+        &mut self,
+        name: &str,
+        expected: Option<Option<ObjectId>>,
+        new: Option<ObjectId>,
+        message: &str,
+    ) -> StoreResult<bool> {
+        check_name(name)?;
+        self.file.lock()?;
+        let res = self.update_locked(name, expected, new, message);
+        let unlocked = self.file.unlock();
+        let done = res?;
+        unlocked?;
+        Ok(done)
+    }
+
+    fn update_locked(
+        &mut self,
+        name: &str,
+        expected: Option<Option<ObjectId>>,
+        new: Option<ObjectId>,
+        message: &str,
+    ) -> StoreResult<bool> {
+        self.refresh()?;
+        let old = self.get(name);
+        if expected.is_some_and(|e| e != old) {
+            return Ok(false);
+        }
+        if old == new {
+            return Ok(true);
+        }
+
+        let e = RefLogEntry {
+            name: name.to_owned(),
+            old,
+            new,
+            time_sec: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs() as i64),
+            message: message.to_owned(),
+        };
+        let rec = encode_ref_entry(&e);
+        // оборванный хвост прошлой записи затираем
+        self.file.set_len(self.len)?;
+        self.file.seek(SeekFrom::Start(self.len))?;
+        self.file.write_all(&rec)?;
+        self.file.sync_data()?;
+        self.len += rec.len() as u64;
+        self.apply(e);
+        Ok(true)
+    }
+
+    fn apply(&mut self, e: RefLogEntry) {
+        match e.new {
+            Some(id) => self.refs.insert(e.name.clone(), id),
+            None => self.refs.remove(&e.name),
+        };
+        self.log.push(e);
+    }
+}
+
+/// Имя: сегменты через '/', непустые, не "." / "..", без управляющих символов.
+fn check_name(name: &str) -> StoreResult<()> {
+    let ok = !name.is_empty()
+        && name.len() <= REF_NAME_MAX
+        && !name.chars().any(char::is_control)
+        && name.split('/').all(|s| !s.is_empty() && s != "." && s != "..");
+    if ok {
+        Ok(())
+    } else {
+        Err(StoreError::Io(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("bad ref name {:?}", name),
+        )))
+    }
+}
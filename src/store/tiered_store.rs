@@ -0,0 +1,387 @@
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::Path;
+
+use crate::types::{BlockId, BlockKind};
+use crate::store::blockstore::{
+    BlockStore, StoreError, StoreResult, PreparedL0,
+    is_zero_block, zero_l0_typed, zero_l0_frame, zero_l0_header,
+};
+use crate::store::decode::{BlockBody, BlockHeader};
+use crate::store::heat::HeatTracker;
+use crate::codec::{ZPayload, ObjectPayload};
+use crate::block::multi::{MultiRecipe, MultiInfo};
+
+/// Номер уровня хранения (см. `L0Block::tier`).
+pub type Tier = u8;
+
+pub const TIER_RAM: Tier = 0;
+pub const TIER_SSD: Tier = 1;
+pub const TIER_HDD: Tier = 2;
+
+/// Где физически лежит блок: уровень + id внутри backend'а этого уровня.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Placement {
+    pub tier:  Tier,
+    pub inner: BlockId,
+}
+
+/// Backend одного уровня.
+pub type TierBackend = Box<dyn BlockStore + Send + Sync>;
+
+struct TierSlot {
+    tier:  Tier,
+    store: TierBackend,
+    /// inner id -> глобальный id (только актуальные копии).
+    owners: HashMap<BlockId, BlockId>,
+}
+
+/// Размер записи журнала размещения: global:u64, tier:u8, inner:u64.
+const JOURNAL_REC: usize = 8 + 1 + 8;
+
+/// TieredStore — несколько BlockStore (по одному на уровень) за одним
+/// пространством BlockId.
+///
+/// Глобальные id выдаются подряд; для каждого запоминается Placement.
+/// Frame'ы внутри backend'ов несут свои inner id, наружу `get_frame`
+/// отдаёт frame с глобальным id. Ссылки внутри рецептов/объектов —
+/// всегда глобальные id, backend'ы хранят их как есть.
+///
+/// Размещение можно сделать персистентным через `attach_journal`:
+/// append-only файл записей (global, tier, inner), переигрываемый при открытии.
+///
+/// Чтения учитываются в `HeatTracker` (см. `heat()`), на нём работает
+/// `store::migrate::Migrator`.
+pub struct TieredStore {
+    tiers: Vec<TierSlot>,
+    placements: Vec<Placement>,
+    write_tier: Option<Tier>,
+    journal: Option<File>,
+    heat: HeatTracker,
+    track_heat: bool,
+}
+
+impl Default for TieredStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TieredStore {
+    pub fn new() -> Self {
+        Self {
+            tiers: Vec::new(),
+            placements: Vec::new(),
+            write_tier: None,
+            journal: None,
+            heat: HeatTracker::new(),
+            track_heat: true,
+        }
+    }
+
+    /// Access-heat по прочитанным блокам.
+    pub fn heat(&self) -> &HeatTracker {
+        &self.heat
+    }
+
+    /// Включить/выключить учёт чтений (по умолчанию включён).
+    pub fn set_heat_tracking(&mut self, on: bool) {
+        self.track_heat = on;
+    }
+
+    pub fn heat_tracking(&self) -> bool {
+        self.track_heat
+    }
+
+    /// Подключить backend как уровень `tier`.
+    /// Первый подключённый уровень становится уровнем записи по умолчанию.
+    pub fn add_tier(&mut self, tier: Tier, store: TierBackend) -> StoreResult<()> {
+        if self.slot_index(tier).is_some() {
+            return Err(StoreError::Corrupt(format!("tier {} already attached", tier)));
+        }
+        self.tiers.push(TierSlot {
+            tier,
+            store,
+            owners: HashMap::new(),
+        });
+        self.tiers.sort_by_key(|s| s.tier);
+        if self.write_tier.is_none() {
+            self.write_tier = Some(tier);
+        }
+        Ok(())
+    }
+
+    /// Уровень, на который пишут put_*.
+    pub fn set_write_tier(&mut self, tier: Tier) -> StoreResult<()> {
+        self.slot_index(tier).ok_or(StoreError::Corrupt(format!("unknown tier {}", tier)))?;
+        self.write_tier = Some(tier);
+        Ok(())
+    }
+
+    pub fn write_tier(&self) -> Option<Tier> {
+        self.write_tier
+    }
+
+    /// Подключённые уровни (по возрастанию).
+    pub fn tiers(&self) -> Vec<Tier> {
+        self.tiers.iter().map(|s| s.tier).collect()
+    }
+
+    /// Backend уровня (read-only).
+    pub fn tier_store(&self, tier: Tier) -> Option<&(dyn BlockStore + Send + Sync)> {
+        self.slot_index(tier).map(|i| self.tiers[i].store.as_ref())
+    }
+
+    /// Подключить журнал размещения и переиграть уже записанное в нём.
+    ///
+    /// Уровни должны быть подключены до вызова, записи — после:
+    /// размещение в памяти заменяется содержимым журнала.
+    pub fn attach_journal(&mut self, path: &Path) -> StoreResult<()> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(path)?;
+
+        let mut buf = Vec::new();
+        file.read_to_end(&mut buf)?;
+
+        self.placements.clear();
+        for slot in self.tiers.iter_mut() {
+            slot.owners.clear();
+        }
+
+        // хвост от недописанной записи игнорируем
+        for rec in buf.chunks_exact(JOURNAL_REC) {
+            let global = u64::from_be_bytes(rec[0..8].try_into().unwrap());
+            let tier = rec[8];
+            let inner = u64::from_be_bytes(rec[9..17].try_into().unwrap());
+            self.apply(global, Placement { tier, inner })?;
+        }
+
+        self.journal = Some(file);
+        Ok(())
+    }
+
+    /// На каком уровне сейчас лежит блок.
+    pub fn tier_of(&self, id: BlockId) -> Option<Tier> {
+        self.placement(id).map(|p| p.tier)
+    }
+
+    pub fn placement(&self, id: BlockId) -> Option<Placement> {
+        self.placements.get(id as usize).copied()
+    }
+
+    /// Кол-во блоков в глобальном пространстве id.
+    pub fn len(&self) -> usize {
+        self.placements.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.placements.is_empty()
+    }
+
+    /// Перенести блок на другой уровень.
+    ///
+    /// Блок перезаписывается в backend уровня `to`, глобальный id не меняется.
+    /// Старая копия остаётся в исходном backend'е (append-only) и больше
+    /// не адресуется.
+    pub fn move_block(&mut self, id: BlockId, to: Tier) -> StoreResult<()> {
+        let from = self.placement(id).ok_or(StoreError::OutOfRange(id))?;
+        if from.tier == to {
+            return Ok(());
+        }
+        let to_idx = self
+            .slot_index(to)
+            .ok_or(StoreError::Corrupt(format!("unknown tier {}", to)))?;
+        let from_idx = self
+            .slot_index(from.tier)
+            .ok_or(StoreError::Corrupt(format!("unknown tier {}", from.tier)))?;
+
+        let src = &self.tiers[from_idx].store;
+        let (_kind, _hash, body) = src.get_typed(from.inner)?;
+        let info = match &body {
+            BlockBody::Multi(_) => src.get_multi_info(from.inner)?,
+            _ => None,
+        };
+        let dst = &mut self.tiers[to_idx].store;
+        let inner = match (&body, info) {
+            (BlockBody::L0(raw), _) => dst.put_l0(raw)?,
+            (BlockBody::Multi(recipe), Some(info)) => dst.put_multi_info(recipe, &info)?,
+            (BlockBody::Multi(recipe), None) => dst.put_multi(recipe)?,
+            (BlockBody::Z(z), _) => dst.put_z(z)?,
+            (BlockBody::Object(o), _) => dst.put_object(o)?,
+        };
+
+        self.record(id, Placement { tier: to, inner })
+    }
+
+    fn slot_index(&self, tier: Tier) -> Option<usize> {
+        self.tiers.iter().position(|s| s.tier == tier)
+    }
+
+    fn locate(&self, id: BlockId) -> StoreResult<(&TierSlot, BlockId)> {
+        let p = self.placement(id).ok_or(StoreError::OutOfRange(id))?;
+        let idx = self
+            .slot_index(p.tier)
+            .ok_or(StoreError::Corrupt(format!("block {} on unknown tier {}", id, p.tier)))?;
+        Ok((&self.tiers[idx], p.inner))
+    }
+
+    /// Применить размещение в памяти (без журнала).
+    fn apply(&mut self, global: BlockId, p: Placement) -> StoreResult<()> {
+        let idx = self
+            .slot_index(p.tier)
+            .ok_or(StoreError::Corrupt(format!("block {} on unknown tier {}", global, p.tier)))?;
+
+        let g = global as usize;
+        if g > self.placements.len() {
+            return Err(StoreError::Corrupt(format!("placement gap at block {}", global)));
+        }
+        if g == self.placements.len() {
+            self.placements.push(p);
+        } else {
+            let old = self.placements[g];
+            if let Some(oi) = self.slot_index(old.tier) {
+                self.tiers[oi].owners.remove(&old.inner);
+            }
+            self.placements[g] = p;
+        }
+        self.tiers[idx].owners.insert(p.inner, global);
+        Ok(())
+    }
+
+    /// Применить размещение и дописать его в журнал (если подключён).
+    fn record(&mut self, global: BlockId, p: Placement) -> StoreResult<()> {
+        if let Some(j) = self.journal.as_mut() {
+            let mut rec = [0u8; JOURNAL_REC];
+            rec[0..8].copy_from_slice(&global.to_be_bytes());
+            rec[8] = p.tier;
+            rec[9..17].copy_from_slice(&p.inner.to_be_bytes());
+            j.write_all(&rec)?;
+            j.flush()?;
+        }
+        self.apply(global, p)
+    }
+
+    fn put_with<F>(&mut self, put: F) -> StoreResult<BlockId>
+    where
+        F: FnOnce(&mut TierBackend) -> StoreResult<BlockId>,
+    {
+        let tier = self
+            .write_tier
+            .ok_or(StoreError::Corrupt("no tiers attached".into()))?;
+        let idx = self
+            .slot_index(tier)
+            .ok_or(StoreError::Corrupt(format!("unknown tier {}", tier)))?;
+
+        let inner = put(&mut self.tiers[idx].store)?;
+        let id = self.placements.len() as BlockId;
+        self.record(id, Placement { tier, inner })?;
+        Ok(id)
+    }
+}
+
+impl BlockStore for TieredStore {
+    fn put_l0(&mut self, raw: &[u8]) -> StoreResult<BlockId> {
+        self.put_with(|s| s.put_l0(raw))
+    }
+
+    fn put_l0_prepared(&mut self, p: &mut PreparedL0) -> StoreResult<BlockId> {
+        self.put_with(|s| s.put_l0_prepared(p))
+    }
+
+    fn put_multi(&mut self, recipe: &MultiRecipe) -> StoreResult<BlockId> {
+        self.put_with(|s| s.put_multi(recipe))
+    }
+
+    fn put_multi_info(&mut self, recipe: &MultiRecipe, info: &MultiInfo) -> StoreResult<BlockId> {
+        self.put_with(|s| s.put_multi_info(recipe, info))
+    }
+
+    fn put_z(&mut self, z: &ZPayload) -> StoreResult<BlockId> {
+        self.put_with(|s| s.put_z(z))
+    }
+
+    fn put_object(&mut self, o: &ObjectPayload) -> StoreResult<BlockId> {
+        self.put_with(|s| s.put_object(o))
+    }
+
+    fn get_typed(&self, id: BlockId) -> StoreResult<(BlockKind, [u8; 32], BlockBody)> {
+        if let Some(typed) = zero_l0_typed(id) {
+            return Ok(typed);
+        }
+        let (slot, inner) = self.locate(id)?;
+        let typed = slot.store.get_typed(inner)?;
+        if self.track_heat {
+            self.heat.record(id, body_len_estimate(&typed.2));
+        }
+        Ok(typed)
+    }
+
+    fn get_frame(&self, id: BlockId) -> StoreResult<Vec<u8>> {
+        if let Some(frame) = zero_l0_frame(id) {
+            return Ok(frame);
+        }
+        let (slot, inner) = self.locate(id)?;
+        let mut frame = slot.store.get_frame(inner)?;
+        if frame.len() < 52 {
+            return Err(StoreError::Corrupt(format!("short frame for block {}", id)));
+        }
+        if self.track_heat {
+            self.heat.record(id, frame.len() as u64);
+        }
+        // в заголовке backend'а inner id — подменяем на глобальный
+        frame[44..52].copy_from_slice(&id.to_be_bytes());
+        Ok(frame)
+    }
+
+    fn get_header(&self, id: BlockId) -> StoreResult<BlockHeader> {
+        if let Some(h) = zero_l0_header(id) {
+            return Ok(h);
+        }
+        let (slot, inner) = self.locate(id)?;
+        let mut h = slot.store.get_header(inner)?;
+        h.id = id;
+        Ok(h)
+    }
+
+    fn block_tier(&self, id: BlockId) -> u8 {
+        self.tier_of(id).unwrap_or(0)
+    }
+
+    fn contains(&self, id: BlockId) -> bool {
+        (id as usize) < self.placements.len() || is_zero_block(id)
+    }
+
+    fn find_hash(&self, hash: &[u8; 32]) -> StoreResult<Option<BlockId>> {
+        for slot in self.tiers.iter() {
+            if let Some(inner) = slot.store.find_hash(hash)? {
+                if let Some(global) = slot.owners.get(&inner) {
+                    return Ok(Some(*global));
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Оценка размера frame'а по телу (без повторного кодирования):
+/// для L0 — точный объём данных, для остальных — порядок величины.
+fn body_len_estimate(body: &BlockBody) -> u64 {
+    const HDR: u64 = 52 + 5;
+    let payload = match body {
+        BlockBody::L0(raw) => raw.len() as u64,
+        BlockBody::Multi(MultiRecipe::Aggregate { blocks }) => blocks.len() as u64 * 8,
+        BlockBody::Multi(MultiRecipe::Indexed { blocks, .. }) => 4 + blocks.len() as u64 * 16,
+        BlockBody::Multi(MultiRecipe::CodecRecipe { recipe_data, blocks, .. }) => {
+            64 + recipe_data.as_ref().map_or(0, |d| d.len() as u64)
+                + blocks.as_ref().map_or(0, |b| b.len() as u64 * 8)
+        }
+        BlockBody::Multi(MultiRecipe::Custom { payload, .. }) => 4 + payload.len() as u64,
+        BlockBody::Z(z) => 4 * 5 + 20 + z.meta.len() as u64,
+        BlockBody::Object(o) => 3 * 5 + 13 + o.meta.len() as u64,
+    };
+    HDR + payload
+}
@@ -0,0 +1,167 @@
+use std::io::Read;
+
+use crate::types::{BlockId, BlockKind, ObjectId};
+use crate::block::{L0Block, MultiBlock, MultiInfo, ZBlock, Object};
+use crate::codec::{ZPayload, ObjectPayload};
+use crate::store::blockstore::{BlockStore, StoreError, StoreResult};
+use crate::store::decode::BlockBody;
+use crate::reader::ObjectReader;
+
+impl From<&ZBlock> for ZPayload {
+    fn from(z: &ZBlock) -> Self {
+        ZPayload {
+            first_l0: z.first_l0,
+            last_l0:  z.last_l0,
+            z_type:   z.z_type,
+            meta:     z.meta.clone(),
+        }
+    }
+}
+
+impl From<&Object> for ObjectPayload {
+    fn from(o: &Object) -> Self {
+        ObjectPayload {
+            root:     o.root,
+            obj_type: o.obj_type,
+            meta:     o.meta.clone(),
+        }
+    }
+}
+
+fn kind_mismatch(id: BlockId, got: BlockKind, want: BlockKind) -> StoreError {
+    StoreError::Corrupt(format!("block {} is {:?}, expected {:?}", id, got, want))
+}
+
+/// Типизированный API поверх любого BlockStore: блоки как структуры
+/// из `crate::block` (с id, hash и размером), а не кортежи `get_typed`.
+///
+/// Put-варианты принимают структуры и возвращают их же в том виде,
+/// в каком они легли в store: с выданным id и hash frame'а.
+/// `id`/`hash` во входной структуре игнорируются.
+///
+/// `hash` у всех блоков — hash frame'а (ключ `find_hash`); hash
+/// восстановленных данных Multi — отдельно, в `MultiBlock::info`.
+pub trait TypedStore: BlockStore {
+    /// L0 по заголовку frame: id, hash, размер, уровень — без чтения данных.
+    fn get_l0(&self, id: BlockId) -> StoreResult<L0Block> {
+        let h = self.get_header(id)?;
+        let size = h.l0_raw_len().ok_or_else(|| kind_mismatch(id, h.kind, BlockKind::L0))?;
+        Ok(L0Block {
+            id,
+            hash: h.hash,
+            size: size as u32,
+            tier: self.block_tier(id),
+        })
+    }
+
+    /// L0 вместе с данными.
+    fn get_l0_data(&self, id: BlockId) -> StoreResult<(L0Block, Vec<u8>)> {
+        let (kind, hash, body) = self.get_typed(id)?;
+        let BlockBody::L0(raw) = body else {
+            return Err(kind_mismatch(id, kind, BlockKind::L0));
+        };
+        let block = L0Block {
+            id,
+            hash,
+            size: raw.len() as u32,
+            tier: self.block_tier(id),
+        };
+        Ok((block, raw))
+    }
+
+    fn put_l0_block(&mut self, raw: &[u8]) -> StoreResult<L0Block> {
+        let id = self.put_l0(raw)?;
+        self.get_l0(id)
+    }
+
+    /// Multi с MultiInfo из frame'а (None — записан без info).
+    fn get_multi(&self, id: BlockId) -> StoreResult<MultiBlock> {
+        let (kind, hash, body) = self.get_typed(id)?;
+        let BlockBody::Multi(recipe) = body else {
+            return Err(kind_mismatch(id, kind, BlockKind::Multi));
+        };
+        let info = self.get_multi_info(id)?;
+        Ok(MultiBlock { id, hash, info, recipe })
+    }
+
+    /// Длина и hash данных Multi: из frame'а, а без info — чтением
+    /// всех данных поддерева (дорого).
+    fn compute_multi_info(&self, id: BlockId) -> StoreResult<MultiInfo> {
+        let kind = self.get_header(id)?.kind;
+        if kind != BlockKind::Multi {
+            return Err(kind_mismatch(id, kind, BlockKind::Multi));
+        }
+        if let Some(info) = self.get_multi_info(id)? {
+            return Ok(info);
+        }
+
+        let mut rd = ObjectReader::open(self, id)?;
+        let mut hasher = blake3::Hasher::new();
+        let mut buf = vec![0u8; 64 * 1024];
+        let mut logical_len = 0u64;
+        loop {
+            let n = rd.read(&mut buf)?;
+            if n == 0 {
+                break;
+            }
+            hasher.update(&buf[..n]);
+            logical_len += n as u64;
+        }
+        Ok(MultiInfo { logical_len, hash: *hasher.finalize().as_bytes() })
+    }
+
+    /// Записать Multi (с MultiInfo, если `info` задан).
+    ///
+    /// info не перепроверяется: за соответствие данным отвечает вызывающий.
+    /// В ответе `info` — то, что store действительно сохранил.
+    fn put_multi_block(&mut self, m: &MultiBlock) -> StoreResult<MultiBlock> {
+        let id = match &m.info {
+            Some(info) => self.put_multi_info(&m.recipe, info)?,
+            None => self.put_multi(&m.recipe)?,
+        };
+        let hash = self.get_header(id)?.hash;
+        let info = self.get_multi_info(id)?;
+        Ok(MultiBlock { id, hash, info, recipe: m.recipe.clone() })
+    }
+
+    fn get_z(&self, id: BlockId) -> StoreResult<ZBlock> {
+        let (kind, hash, body) = self.get_typed(id)?;
+        let BlockBody::Z(z) = body else {
+            return Err(kind_mismatch(id, kind, BlockKind::Z));
+        };
+        Ok(ZBlock {
+            id,
+            hash,
+            first_l0: z.first_l0,
+            last_l0:  z.last_l0,
+            z_type:   z.z_type,
+            meta:     z.meta,
+        })
+    }
+
+    fn put_z_block(&mut self, z: &ZBlock) -> StoreResult<ZBlock> {
+        let id = self.put_z(&ZPayload::from(z))?;
+        let hash = self.get_header(id)?.hash;
+        Ok(ZBlock { id, hash, ..z.clone() })
+    }
+
+    fn get_object(&self, id: ObjectId) -> StoreResult<Object> {
+        let (kind, _hash, body) = self.get_typed(id)?;
+        let BlockBody::Object(o) = body else {
+            return Err(kind_mismatch(id, kind, BlockKind::Object));
+        };
+        Ok(Object {
+            id,
+            root:     o.root,
+            obj_type: o.obj_type,
+            meta:     o.meta,
+        })
+    }
+
+    fn put_object_block(&mut self, o: &Object) -> StoreResult<Object> {
+        let id = self.put_object(&ObjectPayload::from(o))?;
+        Ok(Object { id, ..o.clone() })
+    }
+}
+
+impl<T: BlockStore + ?Sized> TypedStore for T {}
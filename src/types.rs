@@ -1,15 +1,38 @@
 
+// ------------------------------------------------------------
+// Стандартные obj_type (Object::obj_type)
+
+/// Обычный файл: root -> дерево данных (Multi/L0).
+pub const OBJ_TYPE_FILE: u32 = 1;
+/// Каталог: meta -> записи (codec::dir), root -> пустое дерево данных.
+pub const OBJ_TYPE_DIR: u32 = 2;
+/// Снапшот: root -> Object версии, meta -> родители/время/сообщение (codec::snapshot).
+pub const OBJ_TYPE_SNAPSHOT: u32 = 4;
+// ------------------------------------------------------------
+
 // ------------------------------------------------------------
 // Z-node metadata (cheap-size / light analytics)
+/// Z-node: root -> Object файла, meta -> ZNodeMeta (codec::znode).
 pub const OBJ_TYPE_ZNODE: u32 = 3;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct ZNodeMeta {
     pub size_bytes: u64,
     pub blocks: u32,
 }
 // ------------------------------------------------------------
 
+// ------------------------------------------------------------
+// Стандартные z_type (ZBlock::z_type, см. analysis::ZRegistry)
+
+/// Член TAR-архива (analysis::tar).
+pub const Z_TYPE_TAR: u32 = 1;
+/// Секция ELF-файла (analysis::elf).
+pub const Z_TYPE_ELF: u32 = 2;
+/// Член/элемент JSON-документа или запись NDJSON (analysis::json).
+pub const Z_TYPE_JSON: u32 = 3;
+// ------------------------------------------------------------
+
 pub type BlockId   = u64;
 pub type ObjectId  = u64;
 pub type CodecId   = u64;
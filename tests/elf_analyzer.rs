@@ -0,0 +1,163 @@
+use std::fs;
+
+use quarxtor_core::store::file_store::FileBlockStore;
+use quarxtor_core::store::typed::TypedStore;
+use quarxtor_core::codec::decode_z_span;
+use quarxtor_core::config::QuarxConfig;
+use quarxtor_core::import::Importer;
+use quarxtor_core::analysis::{elf_sections, elf_section, elf_extract};
+
+const SHT_PROGBITS: u32 = 1;
+const SHT_STRTAB: u32 = 3;
+const SHT_NOBITS: u32 = 8;
+
+struct Sec {
+    name:    &'static str,
+    sh_type: u32,
+    flags:   u64,
+    data:    Vec<u8>,
+}
+
+fn put(out: &mut Vec<u8>, v: u64, width: usize, be: bool) {
+    let b = v.to_le_bytes();
+    let mut b = b[..width].to_vec();
+    if be {
+        b.reverse();
+    }
+    out.extend_from_slice(&b);
+}
+
+/// Минимальный ELF: заголовок, данные секций, .shstrtab, таблица секций.
+fn build_elf(is64: bool, be: bool, secs: &[Sec]) -> Vec<u8> {
+    let w = if is64 { 8 } else { 4 };
+    let ehsize = if is64 { 64 } else { 52 };
+
+    let mut names = vec![0u8];
+    let mut name_off = Vec::new();
+    for s in secs.iter().map(|s| s.name).chain([".shstrtab"]) {
+        name_off.push(names.len() as u64);
+        names.extend_from_slice(s.as_bytes());
+        names.push(0);
+    }
+
+    let mut body = vec![0u8; ehsize];
+    let mut placed = Vec::new();
+    for s in secs {
+        let off = body.len() as u64;
+        if s.sh_type != SHT_NOBITS {
+            body.extend_from_slice(&s.data);
+        }
+        placed.push((off, s.data.len() as u64));
+    }
+    let str_off = body.len() as u64;
+    body.extend_from_slice(&names);
+    body.resize(body.len().div_ceil(8) * 8, 0);
+    let shoff = body.len() as u64;
+
+    let shnum = secs.len() + 2;
+    let shent = if is64 { 64 } else { 40 };
+    let mut sht = vec![0u8; shent]; // SHT_NULL
+    let all = secs
+        .iter()
+        .zip(&placed)
+        .map(|(s, &(off, len))| (s.sh_type, s.flags, off, len))
+        .chain([(SHT_STRTAB, 0, str_off, names.len() as u64)]);
+    for (i, (ty, flags, off, len)) in all.enumerate() {
+        put(&mut sht, name_off[i], 4, be);
+        put(&mut sht, ty as u64, 4, be);
+        put(&mut sht, flags, w, be);
+        put(&mut sht, 0x1000 * (i as u64 + 1), w, be); // addr
+        put(&mut sht, off, w, be);
+        put(&mut sht, len, w, be);
+        put(&mut sht, 0, 4, be); // link
+        put(&mut sht, 0, 4, be); // info
+        put(&mut sht, 1, w, be); // addralign
+        put(&mut sht, 0, w, be); // entsize
+    }
+    body.extend_from_slice(&sht);
+
+    let mut h = Vec::new();
+    h.extend_from_slice(b"\x7fELF");
+    h.extend_from_slice(&[if is64 { 2 } else { 1 }, if be { 2 } else { 1 }, 1, 0]);
+    h.resize(16, 0);
+    put(&mut h, 2, 2, be); // e_type
+    put(&mut h, 62, 2, be); // e_machine
+    put(&mut h, 1, 4, be); // e_version
+    put(&mut h, 0, w, be); // e_entry
+    put(&mut h, 0, w, be); // e_phoff
+    put(&mut h, shoff, w, be);
+    put(&mut h, 0, 4, be); // e_flags
+    put(&mut h, ehsize as u64, 2, be);
+    put(&mut h, 0, 2, be); // e_phentsize
+    put(&mut h, 0, 2, be); // e_phnum
+    put(&mut h, shent as u64, 2, be);
+    put(&mut h, shnum as u64, 2, be);
+    put(&mut h, shnum as u64 - 1, 2, be);
+    body[..ehsize].copy_from_slice(&h);
+    body
+}
+
+fn sections() -> Vec<Sec> {
+    vec![
+        Sec { name: ".text", sh_type: SHT_PROGBITS, flags: 6, data: (0..3000u32).map(|i| (i * 7) as u8).collect() },
+        Sec { name: ".rodata", sh_type: SHT_PROGBITS, flags: 2, data: b"constant strings\0".repeat(40) },
+        Sec { name: ".bss", sh_type: SHT_NOBITS, flags: 3, data: vec![0; 4096] },
+        Sec { name: ".debug_info", sh_type: SHT_PROGBITS, flags: 0, data: vec![0xDB; 1500] },
+    ]
+}
+
+#[test]
+fn elf_sections_listed_and_fetched() {
+    let path = std::env::temp_dir().join("quarxtor_elf_analyzer.qblk");
+    let _ = fs::remove_file(&path);
+    let mut store = FileBlockStore::open(path.clone()).expect("open store");
+    assert!(!QuarxConfig::default().analysis_z_elf);
+    let cfg = QuarxConfig { l0_chunk: 512, analysis_z_elf: true, ..QuarxConfig::default() };
+
+    for (is64, be) in [(true, false), (false, true)] {
+        let elf = build_elf(is64, be, &sections());
+        let r = Importer::new(&mut store, &cfg).import_bytes(&elf).unwrap();
+
+        let secs = elf_sections(&store, r.object_id).unwrap();
+        let got: Vec<_> = secs.iter().map(|s| (s.index, s.name.as_str(), s.flags)).collect();
+        // .bss (NOBITS) без данных в файле — без зоны
+        assert_eq!(got, [(1, ".text", 6), (2, ".rodata", 2), (4, ".debug_info", 0), (5, ".shstrtab", 0)]);
+        assert_eq!(secs[1].addr, 0x2000);
+
+        let src = sections();
+        for (s, i) in secs.iter().zip([0, 1, 3]) {
+            let mut out = Vec::new();
+            assert_eq!(elf_extract(&store, s, &mut out).unwrap(), src[i].data.len() as u64);
+            assert_eq!(out, src[i].data, "section {}", s.name);
+        }
+        let dbg = elf_section(&store, r.object_id, ".debug_info").unwrap().expect("section");
+        assert_eq!(&elf[dbg.offset as usize..][..dbg.size as usize], &[0xDB; 1500][..]);
+    }
+
+    // та же .text в другой сборке: крайние L0 (заголовок, начало .rodata)
+    // другие, внутренние L0 секции общие
+    let mut other = sections();
+    other[1].data = b"other".to_vec();
+    other[1].data.resize(640, 0);
+    let a = Importer::new(&mut store, &cfg).import_bytes(&build_elf(true, false, &sections())).unwrap();
+    let b = Importer::new(&mut store, &cfg).import_bytes(&build_elf(true, false, &other)).unwrap();
+    let ta = elf_section(&store, a.object_id, ".text").unwrap().unwrap();
+    let tb = elf_section(&store, b.object_id, ".text").unwrap().unwrap();
+    let span = |id| decode_z_span(&store.get_z(id).unwrap().meta).unwrap().l0s;
+    let (sa, sb) = (span(ta.zblock), span(tb.zblock));
+    let n = sa.len();
+    assert_ne!((sa[0], sa[n - 1]), (sb[0], sb[n - 1]));
+    assert_eq!(sa[1..n - 1], sb[1..n - 1]);
+
+    // не ELF, усечённая таблица секций, выключенный анализатор — секций нет
+    let r = Importer::new(&mut store, &cfg).import_bytes(b"\x7fELF but not really").unwrap();
+    assert!(elf_sections(&store, r.object_id).unwrap().is_empty());
+    let elf = build_elf(true, false, &sections());
+    let r = Importer::new(&mut store, &cfg).import_bytes(&elf[..elf.len() - 10]).unwrap();
+    assert!(elf_sections(&store, r.object_id).unwrap().is_empty());
+    let off = QuarxConfig { analysis_z_elf: false, ..cfg.clone() };
+    let r = Importer::new(&mut store, &off).import_bytes(&elf).unwrap();
+    assert!(elf_sections(&store, r.object_id).unwrap().is_empty());
+
+    let _ = fs::remove_file(&path);
+}
@@ -0,0 +1,171 @@
+use std::io::Read;
+use std::path::PathBuf;
+use std::fs;
+
+use quarxtor_core::store::file_store::FileBlockStore;
+use quarxtor_core::store::blockstore::BlockStore;
+use quarxtor_core::store::decode::BlockBody;
+use quarxtor_core::codec::{encode_dir_entries, decode_dir_meta, DirEntry, DirEntryKind};
+use quarxtor_core::config::QuarxConfig;
+use quarxtor_core::import::{FsImporter, SkipReason};
+use quarxtor_core::reader::ObjectReader;
+use quarxtor_core::graph::object_graph::ObjectGraph;
+use quarxtor_core::types::{BlockRef, OBJ_TYPE_DIR, OBJ_TYPE_FILE};
+
+fn tmp_dir(name: &str) -> PathBuf {
+    let p = std::env::temp_dir().join(name);
+    let _ = fs::remove_dir_all(&p);
+    fs::create_dir_all(&p).unwrap();
+    p
+}
+
+fn dir_entries<S: BlockStore>(store: &S, id: u64) -> Vec<DirEntry> {
+    let BlockBody::Object(o) = store.get_typed(id).unwrap().2 else {
+        panic!("expected Object body");
+    };
+    assert_eq!(o.obj_type, OBJ_TYPE_DIR);
+    decode_dir_meta(&o.meta).expect("dir entries")
+}
+
+fn read_file<S: BlockStore>(store: &S, child: BlockRef) -> Vec<u8> {
+    let BlockRef::Object(id) = child else {
+        panic!("expected Object ref");
+    };
+    let BlockBody::Object(o) = store.get_typed(id).unwrap().2 else {
+        panic!("expected Object body");
+    };
+    assert_eq!(o.obj_type, OBJ_TYPE_FILE);
+    let mut out = Vec::new();
+    ObjectReader::open(store, id).unwrap().read_to_end(&mut out).unwrap();
+    out
+}
+
+#[test]
+fn fs_import_applies_policy() {
+    let src = tmp_dir("quarxtor_fs_import_src");
+    let store_path = std::env::temp_dir().join("quarxtor_fs_import.qblk");
+    let _ = fs::remove_file(&store_path);
+
+    fs::write(src.join("a.txt"), b"alpha file").unwrap();
+    fs::write(src.join(".hidden"), b"secret").unwrap();
+    fs::write(src.join("empty"), b"").unwrap();
+    fs::write(src.join("tiny"), b"xy").unwrap();
+    fs::create_dir(src.join("sub")).unwrap();
+    fs::write(src.join("sub").join("b.bin"), vec![7u8; 20_000]).unwrap();
+    #[cfg(unix)]
+    std::os::unix::fs::symlink("a.txt", src.join("link")).unwrap();
+
+    let mut store = FileBlockStore::open(store_path.clone()).expect("open store");
+    let cfg = QuarxConfig {
+        import_skip_hidden: true,
+        import_min_file_size: 4,
+        ..QuarxConfig::default()
+    };
+    let res = FsImporter::new(&mut store, &cfg).import_path(&src).expect("import");
+    let s = &res.summary;
+
+    assert_eq!((s.files, s.dirs), (2, 2));
+    assert_eq!(s.stats.bytes_in, 10 + 20_000);
+    assert_eq!(s.skipped_for(&SkipReason::Hidden), 1);
+    assert_eq!(s.skipped_for(&SkipReason::Zero), 1);
+    assert_eq!(s.skipped_for(&SkipReason::TooSmall), 1);
+    #[cfg(unix)]
+    assert_eq!(s.skipped_for(&SkipReason::Symlink), 1);
+
+    let root = dir_entries(&store, res.root);
+    let names: Vec<_> = root.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(names, ["a.txt", "sub"]);
+    assert_eq!(root[0].kind, DirEntryKind::File);
+    assert_eq!(read_file(&store, root[0].child), b"alpha file");
+
+    assert_eq!(root[1].kind, DirEntryKind::Dir);
+    let BlockRef::Object(sub_id) = root[1].child else {
+        panic!("expected Object ref");
+    };
+    let sub = dir_entries(&store, sub_id);
+    assert_eq!(sub.len(), 1);
+    assert_eq!(sub[0].name, "b.bin");
+    assert_eq!(read_file(&store, sub[0].child), vec![7u8; 20_000]);
+
+    // атрибуты записей
+    assert_eq!(root[0].size, 10);
+    assert_eq!(sub[0].size, 20_000);
+    let mtime = fs::metadata(src.join("a.txt")).unwrap().modified().unwrap();
+    let since = mtime.duration_since(std::time::UNIX_EPOCH).unwrap();
+    assert_eq!(root[0].mtime_sec, since.as_secs() as i64);
+    assert_eq!(root[0].mtime_nsec, since.subsec_nanos());
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        let md = fs::metadata(src.join("a.txt")).unwrap();
+        assert_eq!((root[0].mode, root[0].uid, root[0].gid), (md.mode(), md.uid(), md.gid()));
+    }
+
+    // замыкание корня покрывает все файлы и подкаталоги
+    let closure = ObjectGraph::new(&store).compute_closure_from_object(res.root).unwrap();
+    for e in root.iter().chain(sub.iter()) {
+        let BlockRef::Object(id) = e.child else { unreachable!() };
+        assert!(closure.blocks.contains(&id), "{} not in closure", e.name);
+    }
+
+    // без skip: симлинк хранится как запись Symlink, пустой файл импортируется
+    #[cfg(unix)]
+    {
+        let cfg = QuarxConfig {
+            import_skip_symlink: false,
+            import_skip_zero: false,
+            ..QuarxConfig::default()
+        };
+        let res = FsImporter::new(&mut store, &cfg).import_path(&src).expect("import");
+        assert!(res.summary.skipped.is_empty(), "{:?}", res.summary.skipped);
+        let root = dir_entries(&store, res.root);
+        let link = root.iter().find(|e| e.name == "link").expect("link entry");
+        assert_eq!(link.kind, DirEntryKind::Symlink);
+        assert_eq!(link.size, 5);
+        let BlockRef::L0(target) = link.child else {
+            panic!("expected L0 target");
+        };
+        match store.get_typed(target).unwrap().2 {
+            BlockBody::L0(raw) => assert_eq!(raw, b"a.txt"),
+            _ => panic!("expected L0 body"),
+        }
+        let closure = ObjectGraph::new(&store).compute_closure_from_object(res.root).unwrap();
+        assert!(closure.blocks.contains(&target));
+
+        let empty = root.iter().find(|e| e.name == "empty").expect("empty entry");
+        assert!(read_file(&store, empty.child).is_empty());
+    }
+
+    let _ = fs::remove_dir_all(&src);
+    let _ = fs::remove_file(&store_path);
+}
+
+#[test]
+fn dir_entries_codec_roundtrip() {
+    let entries = vec![
+        DirEntry {
+            name:       "файл.txt".into(),
+            kind:       DirEntryKind::File,
+            mode:       0o100644,
+            uid:        1000,
+            gid:        100,
+            mtime_sec:  1_700_000_000,
+            mtime_nsec: 123_456_789,
+            size:       42,
+            child:      BlockRef::Object(7),
+        },
+        DirEntry {
+            name:       "old-link".into(),
+            kind:       DirEntryKind::Symlink,
+            mode:       0o120777,
+            uid:        0,
+            gid:        0,
+            mtime_sec:  -5,
+            mtime_nsec: 0,
+            size:       3,
+            child:      BlockRef::L0(9),
+        },
+    ];
+    assert_eq!(decode_dir_meta(&encode_dir_entries(&entries)), Some(entries));
+    assert_eq!(decode_dir_meta(&[]), Some(Vec::new()));
+}
@@ -0,0 +1,83 @@
+use std::io::Read;
+use std::path::PathBuf;
+use std::fs;
+
+use quarxtor_core::store::file_store::FileBlockStore;
+use quarxtor_core::store::blockstore::BlockStore;
+use quarxtor_core::store::decode::BlockBody;
+use quarxtor_core::codec::decode_dir_meta;
+use quarxtor_core::config::QuarxConfig;
+use quarxtor_core::import::FsImporter;
+use quarxtor_core::analysis::{fs_stats, znode_meta, FsStats};
+use quarxtor_core::reader::ObjectReader;
+use quarxtor_core::types::{BlockRef, ZNodeMeta};
+
+fn tmp_dir(name: &str) -> PathBuf {
+    let p = std::env::temp_dir().join(name);
+    let _ = fs::remove_dir_all(&p);
+    fs::create_dir_all(&p).unwrap();
+    p
+}
+
+#[test]
+fn znodes_created_and_used_by_fs_stats() {
+    let src = tmp_dir("quarxtor_fs_stats_src");
+    let store_path = std::env::temp_dir().join("quarxtor_fs_stats.qblk");
+    let _ = fs::remove_file(&store_path);
+
+    fs::write(src.join("big"), vec![1u8; 5000]).unwrap();
+    fs::write(src.join("small"), vec![2u8; 100]).unwrap();
+    fs::create_dir(src.join("sub")).unwrap();
+    fs::write(src.join("sub").join("big2"), vec![3u8; 3000]).unwrap();
+
+    let mut store = FileBlockStore::open(store_path.clone()).expect("open store");
+    let cfg = QuarxConfig {
+        l0_chunk: 1024,
+        fs_import_z_threshold: 2,
+        ..QuarxConfig::default()
+    };
+    let res = FsImporter::new(&mut store, &cfg).import_path(&src).expect("import");
+    assert_eq!(res.summary.znodes, 2);
+
+    // запись "big" ссылается на Z-node, данные читаются сквозь него
+    let BlockBody::Object(o) = store.get_typed(res.root).unwrap().2 else {
+        panic!("expected Object body");
+    };
+    let entries = decode_dir_meta(&o.meta).unwrap();
+    let BlockRef::Object(big) = entries[0].child else {
+        panic!("expected Object ref");
+    };
+    assert_eq!(znode_meta(&store, big).unwrap(), Some(ZNodeMeta { size_bytes: 5000, blocks: 5 }));
+    let mut data = Vec::new();
+    ObjectReader::open(&store, big).unwrap().read_to_end(&mut data).unwrap();
+    assert_eq!(data, vec![1u8; 5000]);
+
+    // без fallback: small не оценивается
+    let st = fs_stats(&store, res.root, &cfg).unwrap();
+    assert_eq!(
+        st,
+        FsStats {
+            files: 3,
+            dirs: 2,
+            symlinks: 0,
+            bytes: 8000,
+            znode_files: 2,
+            fallback_files: 0,
+            unsized_files: 1,
+        }
+    );
+
+    // с fallback: small читается через дерево данных
+    let fb = QuarxConfig { analysis_fs_stats_fallback: true, ..cfg.clone() };
+    let st = fs_stats(&store, res.root, &fb).unwrap();
+    assert_eq!((st.bytes, st.fallback_files, st.unsized_files), (8100, 1, 0));
+
+    // Z-node выключен — ни одного не создаётся
+    let off = QuarxConfig { analysis_enable_znode: false, ..cfg.clone() };
+    let res = FsImporter::new(&mut store, &off).import_path(&src).expect("import");
+    assert_eq!(res.summary.znodes, 0);
+    assert_eq!(fs_stats(&store, res.root, &cfg).unwrap().unsized_files, 3);
+
+    let _ = fs::remove_dir_all(&src);
+    let _ = fs::remove_file(&store_path);
+}
@@ -0,0 +1,183 @@
+use std::cell::Cell;
+use std::fs;
+
+use quarxtor_core::store::file_store::FileBlockStore;
+use quarxtor_core::store::blockstore::{BlockStore, StoreResult};
+use quarxtor_core::store::decode::{BlockBody, BlockHeader};
+use quarxtor_core::block::multi::{MultiRecipe, MultiInfo};
+use quarxtor_core::codec::{ObjectPayload, ZPayload};
+use quarxtor_core::config::QuarxConfig;
+use quarxtor_core::import::Importer;
+use quarxtor_core::analysis::{json_spans, json_key, json_record, json_extract, JsonPath};
+use quarxtor_core::types::{BlockId, BlockKind};
+
+fn extract<S: BlockStore>(store: &S, span: &quarxtor_core::analysis::JsonSpan) -> String {
+    let mut out = Vec::new();
+    json_extract(store, span, &mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn ndjson_records_fetched_individually() {
+    let path = std::env::temp_dir().join("quarxtor_json_ndjson.qblk");
+    let _ = fs::remove_file(&path);
+    let mut store = FileBlockStore::open(path.clone()).expect("open store");
+    assert!(!QuarxConfig::default().analysis_z_json);
+    let cfg = QuarxConfig { l0_chunk: 64, analysis_z_json: true, ..QuarxConfig::default() };
+
+    let lines: Vec<String> = (0..50)
+        .map(|i| format!(r#"{{"seq":{},"msg":"line {} with \"quotes\" and {{braces}}","tags":[1,2,{{"x":[]}}]}}"#, i, i))
+        .collect();
+    let log = lines.join("\n") + "\n";
+    let r = Importer::new(&mut store, &cfg).import_bytes(log.as_bytes()).unwrap();
+
+    let spans = json_spans(&store, r.object_id).unwrap();
+    assert_eq!(spans.len(), 50);
+    assert!(spans.iter().all(|s| s.record));
+    for (i, s) in spans.iter().enumerate() {
+        assert_eq!(s.path, JsonPath::Index(i as u64));
+    }
+    let rec = json_record(&store, r.object_id, 37).unwrap().expect("record");
+    assert_eq!(extract(&store, &rec), lines[37]);
+    assert!(json_record(&store, r.object_id, 50).unwrap().is_none());
+
+    // усечённая последняя запись и мусор после записей — только целые записи
+    let cut = &log[..log.len() - 10];
+    let r = Importer::new(&mut store, &cfg).import_bytes(cut.as_bytes()).unwrap();
+    assert_eq!(json_spans(&store, r.object_id).unwrap().len(), 49);
+    let junk = format!("{}\n{}\nnot json\n{}\n", lines[0], lines[1], lines[2]);
+    let r = Importer::new(&mut store, &cfg).import_bytes(junk.as_bytes()).unwrap();
+    assert_eq!(json_spans(&store, r.object_id).unwrap().len(), 2);
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn json_document_top_level_keys() {
+    let path = std::env::temp_dir().join("quarxtor_json_doc.qblk");
+    let _ = fs::remove_file(&path);
+    let mut store = FileBlockStore::open(path.clone()).expect("open store");
+    let cfg = QuarxConfig { l0_chunk: 32, analysis_z_json: true, ..QuarxConfig::default() };
+
+    let big: Vec<String> = (0..100).map(|i| format!("{{\"id\": {}}}", i)).collect();
+    let doc = format!(
+        "{{\n  \"name\" : \"quarx\",\n  \"items\": [{}],\n  \"nested\": {{\"a\": {{\"b\": \"}}\"}}}},\n  \"esc\\\"key\\u00e9\": null,\n  \"n\": -1.5e3\n}}\n",
+        big.join(", ")
+    );
+    let r = Importer::new(&mut store, &cfg).import_bytes(doc.as_bytes()).unwrap();
+
+    let spans = json_spans(&store, r.object_id).unwrap();
+    let keys: Vec<_> = spans
+        .iter()
+        .map(|s| match &s.path {
+            JsonPath::Key(k) => k.as_str(),
+            JsonPath::Index(_) => panic!("expected key"),
+        })
+        .collect();
+    assert_eq!(keys, ["name", "items", "nested", "esc\"keyé", "n"]);
+    assert!(spans.iter().all(|s| !s.record));
+
+    assert_eq!(extract(&store, &spans[0]), r#""name" : "quarx""#);
+    let items = json_key(&store, r.object_id, "items").unwrap().expect("items");
+    assert_eq!(extract(&store, &items), format!("\"items\": [{}]", big.join(", ")));
+    let nested = json_key(&store, r.object_id, "nested").unwrap().unwrap();
+    assert_eq!(extract(&store, &nested), r#""nested": {"a": {"b": "}"}}"#);
+    assert_eq!(extract(&store, &spans[4]), r#""n": -1.5e3"#);
+
+    // верхний массив — элементы по номеру
+    let arr = format!("[ {} ]", big.join(" ,\n "));
+    let r = Importer::new(&mut store, &cfg).import_bytes(arr.as_bytes()).unwrap();
+    let el = json_record(&store, r.object_id, 42).unwrap().expect("element");
+    assert!(!el.record);
+    assert_eq!(extract(&store, &el), big[42]);
+
+    // скаляр, незакрытый документ, выключенный анализатор — зон нет
+    for text in [&b"\"just a string\""[..], b"{\"a\": 1, \"b\": [", b"plain text"] {
+        let r = Importer::new(&mut store, &cfg).import_bytes(text).unwrap();
+        assert!(json_spans(&store, r.object_id).unwrap().is_empty());
+    }
+    let off = QuarxConfig { analysis_z_json: false, ..cfg.clone() };
+    let r = Importer::new(&mut store, &off).import_bytes(doc.as_bytes()).unwrap();
+    assert!(json_spans(&store, r.object_id).unwrap().is_empty());
+
+    let _ = fs::remove_file(&path);
+}
+
+/// FileBlockStore со счётчиком чтений блоков.
+struct CountingStore {
+    inner: FileBlockStore,
+    reads: Cell<u64>,
+}
+
+impl BlockStore for CountingStore {
+    fn put_l0(&mut self, raw: &[u8]) -> StoreResult<BlockId> {
+        self.inner.put_l0(raw)
+    }
+    fn put_multi(&mut self, recipe: &MultiRecipe) -> StoreResult<BlockId> {
+        self.inner.put_multi(recipe)
+    }
+    fn put_multi_info(&mut self, recipe: &MultiRecipe, info: &MultiInfo) -> StoreResult<BlockId> {
+        self.inner.put_multi_info(recipe, info)
+    }
+    fn put_z(&mut self, z: &ZPayload) -> StoreResult<BlockId> {
+        self.inner.put_z(z)
+    }
+    fn put_object(&mut self, o: &ObjectPayload) -> StoreResult<BlockId> {
+        self.inner.put_object(o)
+    }
+    fn get_typed(&self, id: BlockId) -> StoreResult<(BlockKind, [u8; 32], BlockBody)> {
+        self.reads.set(self.reads.get() + 1);
+        self.inner.get_typed(id)
+    }
+    fn get_frame(&self, id: BlockId) -> StoreResult<Vec<u8>> {
+        self.reads.set(self.reads.get() + 1);
+        self.inner.get_frame(id)
+    }
+    fn get_header(&self, id: BlockId) -> StoreResult<BlockHeader> {
+        self.reads.set(self.reads.get() + 1);
+        self.inner.get_header(id)
+    }
+    fn contains(&self, id: BlockId) -> bool {
+        self.inner.contains(id)
+    }
+    fn find_hash(&self, hash: &[u8; 32]) -> StoreResult<Option<BlockId>> {
+        self.inner.find_hash(hash)
+    }
+}
+
+#[test]
+fn json_lookups_read_only_indexed_zblocks() {
+    let path = std::env::temp_dir().join("quarxtor_json_index.qblk");
+    let _ = fs::remove_file(&path);
+    let mut store = FileBlockStore::open(path.clone()).expect("open store");
+    let cfg = QuarxConfig { l0_chunk: 256, analysis_z_json: true, ..QuarxConfig::default() };
+
+    let lines: Vec<String> = (0..2000).map(|i| format!(r#"{{"seq":{}}}"#, i)).collect();
+    let log = lines.join("\n") + "\n";
+    let log_id = Importer::new(&mut store, &cfg).import_bytes(log.as_bytes()).unwrap().object_id;
+
+    let members: Vec<String> = (0..2000).map(|i| format!("\"k{}\": {}", i, i)).collect();
+    let doc = format!("{{{}, \"k7\": \"dup\"}}", members.join(", "));
+    let doc_id = Importer::new(&mut store, &cfg).import_bytes(doc.as_bytes()).unwrap().object_id;
+
+    let store = CountingStore { inner: store, reads: Cell::new(0) };
+
+    // Object, индекс и один Z-блок — не 2000 Z-блоков
+    let before = store.reads.get();
+    let rec = json_record(&store, log_id, 1234).unwrap().expect("record");
+    assert!(store.reads.get() - before <= 4, "record lookup read {} blocks", store.reads.get() - before);
+    assert_eq!(extract(&store, &rec), lines[1234]);
+    assert!(json_record(&store, log_id, 2000).unwrap().is_none());
+
+    let before = store.reads.get();
+    let k = json_key(&store, doc_id, "k1999").unwrap().expect("key");
+    assert!(store.reads.get() - before <= 4, "key lookup read {} blocks", store.reads.get() - before);
+    assert_eq!(extract(&store, &k), members[1999]);
+    // одноимённые ключи — последний
+    let dup = json_key(&store, doc_id, "k7").unwrap().expect("dup key");
+    assert_eq!(extract(&store, &dup), r#""k7": "dup""#);
+    assert!(json_key(&store, doc_id, "missing").unwrap().is_none());
+    assert!(json_record(&store, doc_id, 0).unwrap().is_none());
+
+    let _ = fs::remove_file(&path);
+}
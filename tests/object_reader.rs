@@ -0,0 +1,312 @@
+use std::cell::Cell;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::PathBuf;
+use std::fs;
+
+use smallvec::smallvec;
+
+use quarxtor_core::store::file_store::FileBlockStore;
+use quarxtor_core::store::blockstore::{BlockStore, StoreResult};
+use quarxtor_core::store::decode::{BlockBody, BlockHeader};
+use quarxtor_core::block::multi::{MultiRecipe, MultiInfo};
+use quarxtor_core::codec::{ObjectPayload, ZPayload};
+use quarxtor_core::config::{QuarxConfig, ChunkerKind};
+use quarxtor_core::import::{Importer, FixedChunker, TreeBuilder};
+use quarxtor_core::reader::{ObjectReader, object_info};
+use quarxtor_core::types::{BlockId, BlockKind, BlockRef};
+
+fn tmp(name: &str) -> PathBuf {
+    let p = std::env::temp_dir().join(name);
+    let _ = fs::remove_file(&p);
+    p
+}
+
+fn test_data(len: usize) -> Vec<u8> {
+    let mut x: u32 = 0x9E37_79B9;
+    (0..len)
+        .map(|_| {
+            x ^= x << 13;
+            x ^= x >> 17;
+            x ^= x << 5;
+            x as u8
+        })
+        .collect()
+}
+
+#[test]
+fn object_reader_reads_and_seeks_imported_object() {
+    let path = tmp("quarxtor_object_reader_import.qblk");
+    let mut store = FileBlockStore::open(path.clone()).expect("open store");
+
+    let cfg = QuarxConfig {
+        chunker: ChunkerKind::FastCdc,
+        cdc_min: 512,
+        cdc_avg: 2048,
+        cdc_max: 8192,
+        ..QuarxConfig::default()
+    };
+    let data = test_data(300_000);
+    let r = Importer::new(&mut store, &cfg).import_bytes(&data).expect("import");
+
+    let mut rd = ObjectReader::open(&store, r.object_id).expect("open reader");
+    assert_eq!(rd.len().unwrap(), data.len() as u64);
+
+    let mut all = Vec::new();
+    rd.read_to_end(&mut all).expect("read_to_end");
+    assert_eq!(all, data);
+
+    // произвольные seek'и, в т.ч. через границы чанков
+    for &(pos, n) in &[(0u64, 10usize), (12_345, 5000), (299_990, 10), (150_000, 70_000)] {
+        rd.seek(SeekFrom::Start(pos)).unwrap();
+        let mut buf = vec![0u8; n];
+        rd.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, &data[pos as usize..pos as usize + n]);
+    }
+
+    assert_eq!(rd.seek(SeekFrom::End(-4)).unwrap(), data.len() as u64 - 4);
+    let mut tail = Vec::new();
+    rd.read_to_end(&mut tail).unwrap();
+    assert_eq!(tail, &data[data.len() - 4..]);
+    assert!(rd.seek(SeekFrom::Current(-(data.len() as i64) - 1)).is_err());
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn object_reader_nested_multi_and_z() {
+    let path = tmp("quarxtor_object_reader_nested.qblk");
+    let mut store = FileBlockStore::open(path.clone()).expect("open store");
+
+    let a = store.put_l0(b"aaaa").unwrap();
+    let b = store.put_l0(b"bb").unwrap();
+    let c = store.put_l0(b"cccccc").unwrap();
+    let m1 = store.put_multi(&MultiRecipe::Aggregate { blocks: smallvec![a, b] }).unwrap();
+    let m2 = store.put_multi(&MultiRecipe::Aggregate { blocks: smallvec![m1, c, m1] }).unwrap();
+    let obj = store
+        .put_object(&ObjectPayload { root: BlockRef::Multi(m2), obj_type: 1, meta: Vec::new() })
+        .unwrap();
+
+    let mut rd = ObjectReader::open(&store, obj).unwrap();
+    let mut all = String::new();
+    rd.read_to_string(&mut all).unwrap();
+    assert_eq!(all, "aaaabbccccccaaaabb");
+
+    rd.seek(SeekFrom::Start(5)).unwrap();
+    let mut buf = [0u8; 9];
+    rd.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"bccccccaa");
+
+    // Z-блок как корень: последовательность L0 first..=last
+    let z = store
+        .put_z(&ZPayload { first_l0: a, last_l0: c, z_type: 1, meta: Vec::new() })
+        .unwrap();
+    let mut zs = String::new();
+    ObjectReader::open(&store, z).unwrap().read_to_string(&mut zs).unwrap();
+    assert_eq!(zs, "aaaabbcccccc");
+
+    let _ = fs::remove_file(&path);
+}
+
+/// Обёртка, считающая чтения (frame/header) из store.
+struct CountingStore {
+    inner: FileBlockStore,
+    reads: Cell<u64>,
+}
+
+impl BlockStore for CountingStore {
+    fn put_l0(&mut self, raw: &[u8]) -> StoreResult<BlockId> {
+        self.inner.put_l0(raw)
+    }
+    fn put_multi(&mut self, recipe: &MultiRecipe) -> StoreResult<BlockId> {
+        self.inner.put_multi(recipe)
+    }
+    fn put_multi_info(&mut self, recipe: &MultiRecipe, info: &MultiInfo) -> StoreResult<BlockId> {
+        self.inner.put_multi_info(recipe, info)
+    }
+    fn put_z(&mut self, z: &ZPayload) -> StoreResult<BlockId> {
+        self.inner.put_z(z)
+    }
+    fn put_object(&mut self, o: &ObjectPayload) -> StoreResult<BlockId> {
+        self.inner.put_object(o)
+    }
+    fn get_typed(&self, id: BlockId) -> StoreResult<(BlockKind, [u8; 32], BlockBody)> {
+        self.reads.set(self.reads.get() + 1);
+        self.inner.get_typed(id)
+    }
+    fn get_frame(&self, id: BlockId) -> StoreResult<Vec<u8>> {
+        self.reads.set(self.reads.get() + 1);
+        self.inner.get_frame(id)
+    }
+    fn get_header(&self, id: BlockId) -> StoreResult<BlockHeader> {
+        self.reads.set(self.reads.get() + 1);
+        self.inner.get_header(id)
+    }
+    fn contains(&self, id: BlockId) -> bool {
+        self.inner.contains(id)
+    }
+    fn find_hash(&self, hash: &[u8; 32]) -> StoreResult<Option<BlockId>> {
+        self.inner.find_hash(hash)
+    }
+}
+
+#[test]
+fn balanced_tree_import_and_log_seek() {
+    let path = tmp("quarxtor_object_reader_tree.qblk");
+    let mut store = FileBlockStore::open(path.clone()).expect("open store");
+
+    // 1000 L0 по 64 байта, fanout 4 -> 5 уровней Indexed-узлов
+    let data = test_data(64_000);
+    let mut imp = Importer::with_chunker(&mut store, Box::new(FixedChunker::new(64)));
+    imp.set_fanout(4);
+    let r = imp.import_bytes(&data).expect("import");
+    assert_eq!(r.stats.chunks, 1000);
+
+    let BlockRef::Multi(root) = r.root else {
+        panic!("expected Multi root");
+    };
+    let BlockBody::Multi(MultiRecipe::Indexed { blocks, ends }) = store.get_typed(root).unwrap().2 else {
+        panic!("expected Indexed root");
+    };
+    assert!(blocks.len() <= 4);
+    assert_eq!(ends.last().copied(), Some(data.len() as u64));
+
+    let store = CountingStore { inner: store, reads: Cell::new(0) };
+    let mut rd = ObjectReader::open(&store, r.object_id).expect("open reader");
+    assert_eq!(rd.len().unwrap(), data.len() as u64);
+
+    for &pos in &[0u64, 63, 64, 12_345, 40_000, 63_999] {
+        let before = store.reads.get();
+        rd.seek(SeekFrom::Start(pos)).unwrap();
+        let mut b = [0u8; 1];
+        rd.read_exact(&mut b).unwrap();
+        assert_eq!(b[0], data[pos as usize]);
+        // спуск: заголовок + frame на уровень, плюс сам L0
+        assert!(store.reads.get() - before <= 2 * 5 + 1, "too many reads for seek to {}", pos);
+    }
+
+    let mut all = Vec::new();
+    rd.seek(SeekFrom::Start(0)).unwrap();
+    rd.read_to_end(&mut all).unwrap();
+    assert_eq!(all, data);
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn open_flat_roots_reads_children_lazily() {
+    let path = tmp("quarxtor_object_reader_lazy.qblk");
+    let mut store = FileBlockStore::open(path.clone()).expect("open store");
+
+    let data = test_data(64_000);
+    let ids: Vec<BlockId> = data.chunks(64).map(|c| store.put_l0(c).unwrap()).collect();
+    let info = MultiInfo { logical_len: data.len() as u64, hash: *blake3::hash(&data).as_bytes() };
+    let agg = store
+        .put_multi_info(&MultiRecipe::Aggregate { blocks: ids.iter().copied().collect() }, &info)
+        .unwrap();
+    let z = store
+        .put_z(&ZPayload { first_l0: ids[0], last_l0: ids[999], z_type: 1, meta: Vec::new() })
+        .unwrap();
+    let store = CountingStore { inner: store, reads: Cell::new(0) };
+
+    // Aggregate с MultiInfo: длина без заголовков детей, seek — до нужного ребёнка
+    let mut rd = ObjectReader::open(&store, agg).unwrap();
+    assert_eq!(rd.len().unwrap(), data.len() as u64);
+    assert!(store.reads.get() <= 3, "open read {} blocks", store.reads.get());
+    rd.seek(SeekFrom::Start(64 * 10)).unwrap();
+    let mut b = [0u8; 1];
+    rd.read_exact(&mut b).unwrap();
+    assert_eq!(b[0], data[640]);
+    assert!(store.reads.get() <= 3 + 11 + 1);
+
+    // Z-корень: open читает только сам Z-блок, len — заголовки всех L0
+    store.reads.set(0);
+    let mut rd = ObjectReader::open(&store, z).unwrap();
+    assert_eq!(store.reads.get(), 1);
+    rd.read_exact(&mut b).unwrap();
+    assert_eq!(b[0], data[0]);
+    assert!(store.reads.get() <= 3);
+    assert_eq!(rd.len().unwrap(), data.len() as u64);
+    let mut all = Vec::new();
+    rd.seek(SeekFrom::Start(0)).unwrap();
+    rd.read_to_end(&mut all).unwrap();
+    assert_eq!(all, data);
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn tree_builder_root_shapes() {
+    let path = tmp("quarxtor_tree_builder_shapes.qblk");
+    let mut store = FileBlockStore::open(path.clone()).expect("open store");
+
+    // пустой вход — пустой Indexed-корень
+    let empty = TreeBuilder::new(4).finish(&mut store).unwrap();
+    assert_eq!((empty.len, empty.depth, empty.multis), (0, 1, 1));
+
+    // ровно fanout листьев — один узел, без лишнего уровня
+    let ids: Vec<BlockId> = (0..4u8).map(|i| store.put_l0(&[i; 3]).unwrap()).collect();
+    let mut tb = TreeBuilder::new(4);
+    for (i, id) in ids.iter().enumerate() {
+        tb.push(&mut store, *id, &[i as u8; 3]).unwrap();
+    }
+    let one = tb.finish(&mut store).unwrap();
+    assert_eq!((one.len, one.depth, one.multis), (12, 1, 1));
+
+    // fanout+1 листьев — два уровня
+    let mut tb = TreeBuilder::new(4);
+    for (i, id) in ids.iter().chain(ids.iter().take(1)).enumerate() {
+        tb.push(&mut store, *id, &[(i % 4) as u8; 3]).unwrap();
+    }
+    let two = tb.finish(&mut store).unwrap();
+    assert_eq!((two.len, two.depth, two.multis), (15, 2, 3));
+    let mut out = Vec::new();
+    ObjectReader::open(&store, two.root).unwrap().read_to_end(&mut out).unwrap();
+    assert_eq!(out, [[0u8; 3], [1; 3], [2; 3], [3; 3], [0; 3]].concat());
+    assert_eq!(two.hash, *blake3::hash(&out).as_bytes());
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn multi_info_gives_size_and_content_hash() {
+    let path = tmp("quarxtor_object_reader_info.qblk");
+    let mut store = FileBlockStore::open(path.clone()).expect("open store");
+
+    let data = test_data(200_000);
+    let want = MultiInfo {
+        logical_len: data.len() as u64,
+        hash:        *blake3::hash(&data).as_bytes(),
+    };
+
+    // разная нарезка и fanout — разные деревья, но одинаковый hash данных
+    let mut imp = Importer::with_chunker(&mut store, Box::new(FixedChunker::new(1000)));
+    imp.set_fanout(8);
+    let r1 = imp.import_bytes(&data).unwrap();
+    let cfg = QuarxConfig { chunker: ChunkerKind::FastCdc, ..QuarxConfig::default() };
+    let r2 = Importer::new(&mut store, &cfg).import_bytes(&data).unwrap();
+
+    assert_eq!(object_info(&store, r1.object_id).unwrap(), Some(want));
+    assert_eq!(object_info(&store, r2.object_id).unwrap(), Some(want));
+
+    let mut rd = ObjectReader::open(&store, r1.object_id).unwrap();
+    assert_eq!(rd.content_info(), Some(want));
+    assert!(rd.verify().unwrap());
+
+    // Multi без info: размер всё равно читается, hash отсутствует
+    let a = store.put_l0(b"abc").unwrap();
+    let m = store.put_multi(&MultiRecipe::Aggregate { blocks: smallvec![a] }).unwrap();
+    assert_eq!(object_info(&store, m).unwrap(), None);
+    let mut rd = ObjectReader::open(&store, m).unwrap();
+    assert_eq!(rd.len().unwrap(), 3);
+    assert!(rd.verify().is_err());
+
+    // неверный info в frame ловится verify
+    let bad = MultiInfo { logical_len: 3, hash: [0u8; 32] };
+    let mb = store
+        .put_multi_info(&MultiRecipe::Aggregate { blocks: smallvec![a] }, &bad)
+        .unwrap();
+    assert_eq!(store.get_multi_info(mb).unwrap(), Some(bad));
+    assert!(!ObjectReader::open(&store, mb).unwrap().verify().unwrap());
+
+    let _ = fs::remove_file(&path);
+}
@@ -0,0 +1,127 @@
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use quarxtor_core::store::file_store::FileBlockStore;
+use quarxtor_core::store::blockstore::StoreError;
+use quarxtor_core::store::tiered_store::{TieredStore, TIER_SSD};
+use quarxtor_core::config::{QuarxConfig, ChunkerKind};
+use quarxtor_core::import::{Importer, ImportResult};
+use quarxtor_core::reader::ObjectReader;
+use quarxtor_core::analysis::json_spans;
+
+fn tmp(name: &str) -> PathBuf {
+    let p = std::env::temp_dir().join(name);
+    let _ = fs::remove_file(&p);
+    p
+}
+
+/// Случайные байты, нулевой участок и повтор (по границам 4K-чанков).
+fn input() -> Vec<u8> {
+    let mut state = 0x9E37_79B9_7F4A_7C15u64;
+    let mut noise = |n: usize| -> Vec<u8> {
+        (0..n)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state as u8
+            })
+            .collect()
+    };
+    let block = noise(73 * 4096);
+    let mut data = Vec::new();
+    data.extend_from_slice(&block);
+    data.extend_from_slice(&vec![0u8; 49 * 4096]);
+    data.extend_from_slice(&noise(122 * 4096));
+    data.extend_from_slice(&block);
+    data.extend_from_slice(&noise(100));
+    data
+}
+
+fn import(path: &Path, cfg: &QuarxConfig, workers: usize, data: &[u8]) -> ImportResult {
+    let mut store = FileBlockStore::open(path.to_path_buf()).expect("open store");
+    let mut imp = Importer::new(&mut store, cfg);
+    imp.set_workers(workers);
+    imp.import_bytes(data).unwrap()
+}
+
+#[test]
+fn parallel_import_matches_sequential() {
+    let log: String = (0..3000).map(|i| format!("{{\"seq\":{},\"pad\":\"{}\"}}\n", i, "x".repeat(i % 50))).collect();
+    let fixed = QuarxConfig { l0_chunk: 4096, multi_fanout: 8, analysis_z_json: true, ..QuarxConfig::default() };
+    let cdc = QuarxConfig { chunker: ChunkerKind::FastCdc, multi_fanout: 8, analysis_z_json: true, ..QuarxConfig::default() };
+
+    for (name, cfg) in [("fixed", &fixed), ("cdc", &cdc)] {
+        for data in [input(), log.clone().into_bytes()] {
+            let a = tmp(&format!("quarxtor_par_{}_1.qblk", name));
+            let b = tmp(&format!("quarxtor_par_{}_4.qblk", name));
+            let ra = import(&a, cfg, 1, &data);
+            let rb = import(&b, cfg, 4, &data);
+
+            // те же id, статистика и байт-в-байт тот же файл store
+            assert_eq!((ra.object_id, ra.root, ra.stats), (rb.object_id, rb.root, rb.stats), "{}", name);
+            assert!(fs::read(&a).unwrap() == fs::read(&b).unwrap(), "{}: store files differ", name);
+
+            let store = FileBlockStore::open(b.clone()).expect("reopen");
+            let mut out = Vec::new();
+            ObjectReader::open(&store, rb.object_id).unwrap().read_to_end(&mut out).unwrap();
+            assert!(out == data);
+
+            let _ = fs::remove_file(&a);
+            let _ = fs::remove_file(&b);
+        }
+    }
+
+    // пул не мешает dedup и нулевым чанкам
+    let p = tmp("quarxtor_par_stats.qblk");
+    let r = import(&p, &fixed, 4, &input());
+    assert_eq!((r.stats.dedup_hits, r.stats.zero_chunks), (73, 49));
+    let _ = fs::remove_file(&p);
+
+    // анализаторы видят те же данные
+    let p = tmp("quarxtor_par_json.qblk");
+    let mut store = FileBlockStore::open(p.clone()).expect("open store");
+    let mut imp = Importer::new(&mut store, &fixed);
+    imp.set_workers(4);
+    let r = imp.import_bytes(log.as_bytes()).unwrap();
+    assert_eq!(json_spans(&store, r.object_id).unwrap().len(), 3000);
+    let _ = fs::remove_file(&p);
+}
+
+#[test]
+fn parallel_import_tiered_progress_cancel() {
+    let ssd = tmp("quarxtor_par_ssd.qblk");
+    let mut ts = TieredStore::new();
+    ts.add_tier(TIER_SSD, Box::new(FileBlockStore::open(ssd.clone()).expect("open ssd")))
+        .expect("add ssd");
+    let cfg = QuarxConfig { l0_chunk: 4096, ..QuarxConfig::default() };
+    let data = input();
+
+    let seen: Arc<Mutex<Vec<u64>>> = Arc::default();
+    let log = seen.clone();
+    let mut imp = Importer::new(&mut ts, &cfg);
+    imp.set_workers(3);
+    imp.set_progress(100_000, move |s| log.lock().unwrap().push(s.bytes_in));
+    let r = imp.import_bytes(&data).unwrap();
+    let mut out = Vec::new();
+    ObjectReader::open(&ts, r.object_id).unwrap().read_to_end(&mut out).unwrap();
+    assert!(out == data);
+    let seen = seen.lock().unwrap();
+    assert!(seen.len() > 5 && seen.windows(2).all(|w| w[0] <= w[1]));
+    assert_eq!(*seen.last().unwrap(), data.len() as u64);
+
+    // отмена посреди конвейера
+    let cancel = Arc::new(AtomicBool::new(false));
+    let flag = cancel.clone();
+    let mut imp = Importer::new(&mut ts, &cfg);
+    imp.set_workers(3);
+    imp.set_cancel(cancel);
+    imp.set_progress(200_000, move |_| flag.store(true, Ordering::Relaxed));
+    let err = imp.import_bytes(&data).unwrap_err();
+    assert!(matches!(err, StoreError::Cancelled), "{:?}", err);
+
+    let _ = fs::remove_file(&ssd);
+}
@@ -0,0 +1,148 @@
+use std::path::PathBuf;
+use std::fs;
+
+use quarxtor_core::store::file_store::FileBlockStore;
+use quarxtor_core::store::blockstore::BlockStore;
+use quarxtor_core::store::decode::BlockBody;
+use quarxtor_core::store::ram_store::{RamStore, PrefetchConfig};
+use quarxtor_core::block::multi::MultiRecipe;
+use quarxtor_core::codec::ObjectPayload;
+use quarxtor_core::types::BlockRef;
+use quarxtor_core::config::QuarxConfig;
+
+#[test]
+fn ram_store_sharded_concurrent_readers() {
+    let path: PathBuf = std::env::temp_dir().join("quarxtor_ram_store_sharded.qblk");
+    let _ = fs::remove_file(&path);
+
+    let mut file = FileBlockStore::open(path.clone()).expect("open store");
+    let mut ids = Vec::new();
+    for i in 0..256u32 {
+        let raw = format!("block-{}", i).into_bytes();
+        ids.push(file.put_l0(&raw).expect("put_l0"));
+    }
+
+    let ram = RamStore::with_shards(file, u64::MAX, 16);
+    assert_eq!(ram.shards(), 16);
+
+    // 32 читателя по одним и тем же блокам
+    std::thread::scope(|s| {
+        for _ in 0..32 {
+            s.spawn(|| {
+                for (i, id) in ids.iter().enumerate() {
+                    let (_, _, body) = ram.get_typed(*id).expect("get_typed");
+                    match body {
+                        BlockBody::L0(raw) => assert_eq!(raw, format!("block-{}", i).into_bytes()),
+                        _ => panic!("expected L0 body"),
+                    }
+                }
+            });
+        }
+    });
+
+    // Статистика сведена в один snapshot по всем шардам.
+    let st = ram.stats();
+    assert_eq!(st.blocks, ids.len() as u64);
+    assert_eq!(st.hits + st.misses, 32 * ids.len() as u64);
+    assert!(st.inserts >= ids.len() as u64);
+    assert_eq!(st.evictions, 0);
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn ram_store_evicts_within_limit() {
+    let path: PathBuf = std::env::temp_dir().join("quarxtor_ram_store_evict.qblk");
+    let _ = fs::remove_file(&path);
+
+    let mut file = FileBlockStore::open(path.clone()).expect("open store");
+    let mut ids = Vec::new();
+    for i in 0..64u8 {
+        ids.push(file.put_l0(&[i; 1024]).expect("put_l0"));
+    }
+
+    // лимит меньше доли одного шарда — шард один на весь лимит
+    let ram = RamStore::with_shards(file, 8 * 1024, 4);
+    assert_eq!(ram.shards(), 1);
+    for id in &ids {
+        ram.get_frame(*id).expect("get_frame");
+    }
+
+    let st = ram.stats();
+    assert!(st.used_bytes <= st.limit_bytes);
+    assert!(st.evictions > 0);
+    assert_eq!(st.misses, ids.len() as u64);
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn ram_store_small_limit_caches_large_frames() {
+    let path: PathBuf = std::env::temp_dir().join("quarxtor_ram_store_small_limit.qblk");
+    let _ = fs::remove_file(&path);
+
+    let mut file = FileBlockStore::open(path.clone()).expect("open store");
+    let id = file.put_l0(&vec![7u8; 512 * 1024]).expect("put_l0");
+
+    // 1 MiB на 64 шарда дало бы 16K на шард: frame в 512K не влез бы
+    let ram = RamStore::new(file, 1024 * 1024);
+    assert!(ram.shards() < 64);
+    ram.get_frame(id).expect("get_frame");
+    ram.get_frame(id).expect("get_frame");
+    let st = ram.stats();
+    assert_eq!((st.misses, st.hits, st.blocks), (1, 1, 1));
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn ram_store_prefetches_children() {
+    let path: PathBuf = std::env::temp_dir().join("quarxtor_ram_store_prefetch.qblk");
+    let _ = fs::remove_file(&path);
+
+    let mut file = FileBlockStore::open(path.clone()).expect("open store");
+    let mut l0s = smallvec::SmallVec::new();
+    for i in 1..=8u8 {
+        l0s.push(file.put_l0(&[i; 100]).expect("put_l0"));
+    }
+    let id_multi = file
+        .put_multi(&MultiRecipe::Aggregate { blocks: l0s.clone() })
+        .expect("put_multi");
+    let id_obj = file
+        .put_object(&ObjectPayload {
+            root:     BlockRef::Multi(id_multi),
+            obj_type: 1,
+            meta:     Vec::new(),
+        })
+        .expect("put_object");
+
+    let mut ram = RamStore::new(file, u64::MAX);
+    let cfg = QuarxConfig { ram_prefetch_depth: 2, ram_prefetch_bytes: 1 << 20, ..QuarxConfig::default() };
+    ram.set_prefetch(PrefetchConfig::from_config(&cfg));
+
+    // обычное чтение граф не обходит
+    ram.get_typed(id_obj).expect("get obj");
+    assert_eq!(ram.stats().prefetched, 0);
+
+    // Object -> (prefetch) Multi -> L0 x 8
+    assert_eq!(ram.prefetch(id_obj).unwrap(), 1 + l0s.len() as u64);
+    let st = ram.stats();
+    assert_eq!((st.prefetched, st.misses), (1 + l0s.len() as u64, 1));
+
+    ram.get_typed(id_multi).expect("get multi");
+    for id in l0s.iter() {
+        ram.get_typed(*id).expect("get l0");
+    }
+    let st = ram.stats();
+    assert_eq!(st.prefetch_hits, 1 + l0s.len() as u64);
+    assert_eq!(st.misses, 1);
+
+    // Бюджет ограничивает объём prefetch.
+    let mut ram = RamStore::new(ram.into_inner(), u64::MAX);
+    let frame_len = ram.inner().get_frame(l0s[0]).unwrap().len() as u64;
+    ram.set_prefetch(PrefetchConfig { depth: 1, byte_budget: 3 * frame_len + 1 });
+    assert_eq!(ram.prefetch(id_multi).unwrap(), 3);
+    assert_eq!(ram.stats().prefetched, 3);
+
+    let _ = fs::remove_file(&path);
+}
@@ -0,0 +1,153 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+use quarxtor_core::store::file_store::FileBlockStore;
+use quarxtor_core::store::blockstore::StoreError;
+use quarxtor_core::store::refs::{RefStore, refs_path};
+use quarxtor_core::config::QuarxConfig;
+use quarxtor_core::import::Importer;
+
+fn tmp(name: &str) -> PathBuf {
+    let p = std::env::temp_dir().join(name);
+    let _ = fs::remove_file(&p);
+    p
+}
+
+#[test]
+fn refs_cas_list_reflog() {
+    let path = tmp("quarxtor_refs_basic.refs");
+    let mut refs = RefStore::open(path.clone()).expect("open refs");
+
+    refs.set("backups/db/2026-10-01", 10, "nightly").unwrap();
+    refs.set("backups/db/2026-10-02", 11, "nightly").unwrap();
+    refs.set("backups/web/2026-10-01", 12, "nightly").unwrap();
+    refs.set("tags/v1", 5, "release").unwrap();
+
+    assert_eq!(refs.get("backups/db/2026-10-02"), Some(11));
+    assert_eq!(refs.get("backups/db"), None);
+    let db: Vec<_> = refs.list("backups/db/").into_iter().map(|(n, _)| n).collect();
+    assert_eq!(db, ["backups/db/2026-10-01", "backups/db/2026-10-02"]);
+    assert_eq!(refs.list("backups/").len(), 3);
+    assert_eq!(refs.list("").len(), 4);
+
+    // CAS: устаревшее ожидание не проходит, журнал не растёт
+    assert!(!refs.compare_and_swap("tags/v1", Some(4), Some(6), "stale").unwrap());
+    assert!(!refs.compare_and_swap("tags/v2", Some(5), Some(6), "missing").unwrap());
+    assert!(refs.compare_and_swap("tags/v1", Some(5), Some(6), "retag").unwrap());
+    assert!(refs.compare_and_swap("tags/v2", None, Some(7), "create").unwrap());
+    assert!(!refs.delete("tags/v2", 8, "wrong").unwrap());
+    assert!(refs.delete("tags/v2", 7, "drop").unwrap());
+    assert_eq!(refs.get("tags/v2"), None);
+
+    let log = refs.reflog("tags/v1");
+    assert_eq!(log.iter().map(|e| (e.old, e.new)).collect::<Vec<_>>(), [(None, Some(5)), (Some(5), Some(6))]);
+    assert_eq!(log[1].message, "retag");
+    assert_eq!(refs.reflog("tags/v2").last().unwrap().new, None);
+
+    for bad in ["", "/a", "a//b", "a/../b", "a/.", "tab\there"] {
+        let err = refs.set(bad, 1, "bad").unwrap_err();
+        assert!(matches!(err, StoreError::Io(ref e) if e.kind() == std::io::ErrorKind::InvalidInput), "{:?}", bad);
+    }
+
+    // после переоткрытия всё на месте
+    drop(refs);
+    let refs = RefStore::open(path.clone()).unwrap();
+    assert_eq!(refs.get("tags/v1"), Some(6));
+    assert_eq!(refs.get("tags/v2"), None);
+    assert_eq!(refs.list("").len(), 4);
+    assert_eq!(refs.reflog("tags/v2").len(), 2);
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn refs_shared_between_handles() {
+    let path = tmp("quarxtor_refs_shared.refs");
+    let mut a = RefStore::open(path.clone()).unwrap();
+    let mut b = RefStore::open(path.clone()).unwrap();
+
+    a.set("head", 1, "a").unwrap();
+    // b ещё не видит изменение, но CAS сверяется с журналом
+    assert_eq!(b.get("head"), None);
+    assert!(!b.compare_and_swap("head", None, Some(2), "b").unwrap());
+    assert_eq!(b.get("head"), Some(1));
+    assert!(b.compare_and_swap("head", Some(1), Some(2), "b").unwrap());
+    assert!(!a.compare_and_swap("head", Some(1), Some(3), "a").unwrap());
+    a.refresh().unwrap();
+    assert_eq!(a.get("head"), Some(2));
+
+    // оборванная запись в конце: отбрасывается и затирается следующей
+    drop((a, b));
+    OpenOptions::new().append(true).open(&path).unwrap().write_all(&[0xB0, 0, 0, 1, 0, 0xB1]).unwrap();
+    let mut c = RefStore::open(path.clone()).unwrap();
+    assert_eq!(c.get("head"), Some(2));
+    c.set("head", 4, "after crash").unwrap();
+    let c = RefStore::open(path.clone()).unwrap();
+    assert_eq!(c.get("head"), Some(4));
+    assert_eq!(c.reflog("head").len(), 3);
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn refs_torn_tail_with_garbage() {
+    let path = tmp("quarxtor_refs_torn.refs");
+    RefStore::open(path.clone()).unwrap().set("head", 1, "init").unwrap();
+    let good = fs::read(&path).unwrap();
+    let append = |bytes: &[u8]| OpenOptions::new().append(true).open(&path).unwrap().write_all(bytes).unwrap();
+
+    // полная по длине запись с чужим тегом, затем с мусором вместо данных
+    for torn in [&[0xB1, 0, 0, 0, 2, 7, 7][..], &[0xB0, 0, 0, 0, 3, 1, 2, 3][..], &[0u8; 64][..]] {
+        append(torn);
+        let mut r = RefStore::open(path.clone()).expect("torn tail is not corruption");
+        assert_eq!(r.get("head"), Some(1));
+        r.set("head", 2, "after crash").unwrap();
+        r.set("head", 1, "back").unwrap();
+        drop(r);
+        let r = RefStore::open(path.clone()).unwrap();
+        assert_eq!(r.get("head"), Some(1));
+        assert!(!fs::read(&path).unwrap().windows(torn.len()).any(|w| w == torn));
+    }
+
+    // битая запись посреди журнала — повреждение
+    let mut bad = good.clone();
+    bad.extend_from_slice(&[0xB1, 0, 0, 0, 1, 0]);
+    bad.extend_from_slice(&good);
+    fs::write(&path, &bad).unwrap();
+    assert!(matches!(RefStore::open(path.clone()), Err(StoreError::Corrupt(_))));
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn refs_are_gc_roots() {
+    let path = tmp("quarxtor_refs_gc.qblk");
+    let _ = fs::remove_file(refs_path(&path));
+    let mut store = FileBlockStore::open(path.clone()).expect("open store");
+    let cfg = QuarxConfig { l0_chunk: 256, ..QuarxConfig::default() };
+
+    let mut import = |data: Vec<u8>| Importer::new(&mut store, &cfg).import_bytes(&data).unwrap().object_id;
+    let old = import(vec![1u8; 1000]);
+    let new = import(vec![2u8; 1000]);
+    let tag = import(vec![3u8; 1000]);
+    let orphan = import(vec![4u8; 1000]);
+
+    let mut refs = RefStore::open_for_store(&path).unwrap();
+    assert_eq!(refs.path(), refs_path(&path));
+    refs.set("backups/db/latest", old, "first").unwrap();
+    assert!(refs.compare_and_swap("backups/db/latest", Some(old), Some(new), "second").unwrap());
+    refs.set("tags/keep", tag, "tag").unwrap();
+
+    assert_eq!(refs.gc_roots(false), [new, tag]);
+    let live = refs.live_closure(&store, false).unwrap();
+    assert!(live.blocks.contains(&new) && live.blocks.contains(&tag));
+    assert!(!live.blocks.contains(&old) && !live.blocks.contains(&orphan));
+
+    // reflog держит и прошлые версии
+    let live = refs.live_closure(&store, true).unwrap();
+    assert!(live.blocks.contains(&old) && !live.blocks.contains(&orphan));
+
+    let _ = fs::remove_file(refs_path(&path));
+    let _ = fs::remove_file(&path);
+}
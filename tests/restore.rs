@@ -0,0 +1,138 @@
+use std::fs::{self, File};
+use std::path::PathBuf;
+use std::time::{Duration, UNIX_EPOCH};
+
+use smallvec::smallvec;
+
+use quarxtor_core::store::file_store::FileBlockStore;
+use quarxtor_core::store::blockstore::{BlockStore, StoreError};
+use quarxtor_core::block::{MultiInfo, MultiRecipe};
+use quarxtor_core::codec::ObjectPayload;
+use quarxtor_core::config::QuarxConfig;
+use quarxtor_core::import::FsImporter;
+use quarxtor_core::export::{Restorer, RestoreOptions, OverwritePolicy};
+use quarxtor_core::types::{BlockRef, OBJ_TYPE_FILE};
+
+fn tmp_dir(name: &str, create: bool) -> PathBuf {
+    let p = std::env::temp_dir().join(name);
+    let _ = fs::remove_dir_all(&p);
+    if create {
+        fs::create_dir_all(&p).unwrap();
+    }
+    p
+}
+
+fn sparse_data() -> Vec<u8> {
+    let mut v = vec![0u8; 200_000];
+    v[..5000].fill(0xAB);
+    v[150_000..150_100].fill(0xCD);
+    v
+}
+
+#[test]
+fn restore_tree_roundtrip_and_policies() {
+    let src = tmp_dir("quarxtor_restore_src", true);
+    let dst = tmp_dir("quarxtor_restore_dst", false);
+    let store_path = std::env::temp_dir().join("quarxtor_restore.qblk");
+    let _ = fs::remove_file(&store_path);
+
+    fs::write(src.join("plain.txt"), b"plain text").unwrap();
+    fs::write(src.join("sparse.bin"), sparse_data()).unwrap();
+    fs::create_dir(src.join("sub")).unwrap();
+    fs::write(src.join("sub").join("tool.sh"), b"#!/bin/sh\necho hi\n").unwrap();
+    let mtime = UNIX_EPOCH + Duration::new(1_600_000_000, 123_000_000);
+    File::options().write(true).open(src.join("plain.txt")).unwrap().set_modified(mtime).unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(src.join("sub").join("tool.sh"), fs::Permissions::from_mode(0o750)).unwrap();
+        std::os::unix::fs::symlink("../plain.txt", src.join("sub").join("link")).unwrap();
+    }
+
+    let mut store = FileBlockStore::open(store_path.clone()).expect("open store");
+    let cfg = QuarxConfig { import_skip_symlink: false, ..QuarxConfig::default() };
+    let root = FsImporter::new(&mut store, &cfg).import_path(&src).expect("import").root;
+
+    // dry-run: отчёт есть, на диске ничего
+    let dry = Restorer::new(&store, RestoreOptions { dry_run: true, ..RestoreOptions::default() })
+        .restore(root, &dst)
+        .unwrap();
+    assert!(!dst.exists());
+    assert_eq!((dry.files, dry.dirs), (3, 2));
+    assert_eq!(dry.bytes, 10 + 200_000 + 18);
+
+    let rep = Restorer::new(&store, RestoreOptions::default()).restore(root, &dst).unwrap();
+    assert_eq!((rep.files, rep.dirs), (dry.files, dry.dirs));
+    assert_eq!(rep.verified, rep.files + rep.symlinks);
+    assert!(rep.hole_bytes >= 100_000, "hole bytes {}", rep.hole_bytes);
+
+    assert_eq!(fs::read(dst.join("plain.txt")).unwrap(), b"plain text");
+    assert_eq!(fs::read(dst.join("sparse.bin")).unwrap(), sparse_data());
+    assert_eq!(fs::metadata(dst.join("plain.txt")).unwrap().modified().unwrap(), mtime);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(dst.join("sub").join("tool.sh")).unwrap().permissions().mode();
+        assert_eq!(mode & 0o7777, 0o750);
+        assert_eq!(rep.symlinks, 1);
+        assert_eq!(fs::read_link(dst.join("sub").join("link")).unwrap(), PathBuf::from("../plain.txt"));
+    }
+
+    // повторное восстановление: Error / Skip / Overwrite
+    let err = Restorer::new(&store, RestoreOptions::default()).restore(root, &dst);
+    assert!(matches!(err, Err(StoreError::Io(e)) if e.kind() == std::io::ErrorKind::AlreadyExists));
+
+    fs::write(dst.join("plain.txt"), b"local change").unwrap();
+    let skip = Restorer::new(&store, RestoreOptions { overwrite: OverwritePolicy::Skip, ..RestoreOptions::default() })
+        .restore(root, &dst)
+        .unwrap();
+    assert_eq!(skip.skipped.len() as u64, dry.files + dry.symlinks);
+    assert_eq!(fs::read(dst.join("plain.txt")).unwrap(), b"local change");
+
+    Restorer::new(&store, RestoreOptions { overwrite: OverwritePolicy::Overwrite, ..RestoreOptions::default() })
+        .restore(root, &dst)
+        .unwrap();
+    assert_eq!(fs::read(dst.join("plain.txt")).unwrap(), b"plain text");
+
+    let _ = fs::remove_dir_all(&src);
+    let _ = fs::remove_dir_all(&dst);
+    let _ = fs::remove_file(&store_path);
+}
+
+#[test]
+fn restore_detects_hash_mismatch() {
+    let dst = tmp_dir("quarxtor_restore_bad", true);
+    let store_path = std::env::temp_dir().join("quarxtor_restore_bad.qblk");
+    let _ = fs::remove_file(&store_path);
+    let mut store = FileBlockStore::open(store_path.clone()).expect("open store");
+
+    let a = store.put_l0(b"real data").unwrap();
+    let bad = MultiInfo { logical_len: 9, hash: *blake3::hash(b"other data").as_bytes() };
+    let m = store.put_multi_info(&MultiRecipe::Aggregate { blocks: smallvec![a] }, &bad).unwrap();
+    let obj = store
+        .put_object(&ObjectPayload { root: BlockRef::Multi(m), obj_type: OBJ_TYPE_FILE, meta: Vec::new() })
+        .unwrap();
+
+    let out = dst.join("file");
+    let res = Restorer::new(&store, RestoreOptions::default()).restore(obj, &out);
+    assert!(matches!(res, Err(StoreError::Corrupt(_))));
+    assert!(!out.exists());
+
+    // без verify файл пишется как есть
+    let rep = Restorer::new(&store, RestoreOptions { verify: false, ..RestoreOptions::default() })
+        .restore(obj, &out)
+        .unwrap();
+    assert_eq!((rep.verified, rep.unverified), (0, 0));
+    assert_eq!(fs::read(&out).unwrap(), b"real data");
+
+    // Overwrite с проверкой: старый файл не трогается, пока новый не сверен
+    fs::write(&out, b"old contents").unwrap();
+    let res = Restorer::new(&store, RestoreOptions { overwrite: OverwritePolicy::Overwrite, ..RestoreOptions::default() })
+        .restore(obj, &out);
+    assert!(matches!(res, Err(StoreError::Corrupt(_))));
+    assert_eq!(fs::read(&out).unwrap(), b"old contents");
+    assert_eq!(fs::read_dir(&dst).unwrap().count(), 1, "temp file left behind");
+
+    let _ = fs::remove_dir_all(&dst);
+    let _ = fs::remove_file(&store_path);
+}
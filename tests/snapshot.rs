@@ -0,0 +1,176 @@
+use std::fs;
+use std::io::Read;
+use std::path::PathBuf;
+
+use quarxtor_core::store::file_store::FileBlockStore;
+use quarxtor_core::store::blockstore::StoreError;
+use quarxtor_core::config::QuarxConfig;
+use quarxtor_core::import::{FsImporter, Importer};
+use quarxtor_core::codec::SnapshotMeta;
+use quarxtor_core::graph::{
+    ObjectGraph, SnapshotDiff, put_snapshot, get_snapshot, snapshot_history, common_ancestor, diff_with_parent,
+};
+use quarxtor_core::reader::{DirChange, ObjectReader};
+
+fn tmp(name: &str) -> PathBuf {
+    let p = std::env::temp_dir().join(name);
+    let _ = fs::remove_file(&p);
+    p
+}
+
+fn meta(parents: &[u64], time_sec: i64, message: &str) -> SnapshotMeta {
+    SnapshotMeta { parents: parents.to_vec(), time_sec, message: message.into() }
+}
+
+#[test]
+fn snapshot_lineage() {
+    let path = tmp("quarxtor_snapshot.qblk");
+    let mut store = FileBlockStore::open(path.clone()).expect("open store");
+    let cfg = QuarxConfig { l0_chunk: 1024, ..QuarxConfig::default() };
+
+    let v1: Vec<u8> = (0..64 * 1024u32).map(|i| (i * 7 % 251) as u8).collect();
+    let mut v2 = v1.clone();
+    v2[5000..5010].fill(0xFF);
+    let mut v3 = v1.clone();
+    v3.extend_from_slice(b"branch tail");
+
+    let mut import = |data: &[u8]| Importer::new(&mut store, &cfg).import_bytes(data).unwrap().object_id;
+    let (o1, o2, o3) = (import(&v1), import(&v2), import(&v3));
+
+    //   s1 -- s2 --- s4
+    //     \--- s3 --/
+    let s1 = put_snapshot(&mut store, o1, &meta(&[], 100, "init")).unwrap();
+    let s2 = put_snapshot(&mut store, o2, &meta(&[s1], 200, "patch")).unwrap();
+    let s3 = put_snapshot(&mut store, o3, &meta(&[s1], 250, "branch")).unwrap();
+    let s4 = put_snapshot(&mut store, o2, &meta(&[s2, s3], 300, "merge")).unwrap();
+    let other = put_snapshot(&mut store, o3, &meta(&[], 50, "unrelated")).unwrap();
+
+    let snap = get_snapshot(&store, s4).unwrap().expect("snapshot");
+    assert_eq!((snap.content, snap.meta, snap.generation), (o2, meta(&[s2, s3], 300, "merge"), 2));
+    assert!(get_snapshot(&store, o1).unwrap().is_none());
+
+    // данные читаются сквозь снапшот
+    let mut out = Vec::new();
+    ObjectReader::open(&store, s3).unwrap().read_to_end(&mut out).unwrap();
+    assert!(out == v3);
+
+    let ids = |h: Vec<quarxtor_core::graph::Snapshot>| h.iter().map(|s| s.id).collect::<Vec<_>>();
+    assert_eq!(ids(snapshot_history(&store, s4).unwrap()), [s4, s3, s2, s1]);
+    assert_eq!(ids(snapshot_history(&store, s2).unwrap()), [s2, s1]);
+
+    assert_eq!(common_ancestor(&store, s2, s3).unwrap(), Some(s1));
+    assert_eq!(common_ancestor(&store, s4, s2).unwrap(), Some(s2));
+    assert_eq!(common_ancestor(&store, s3, other).unwrap(), None);
+
+    let data = |d: Option<SnapshotDiff>| match d {
+        Some(SnapshotDiff::Data(d)) => d,
+        other => panic!("expected data diff, got {:?}", other),
+    };
+    assert_eq!(data(diff_with_parent(&store, s2).unwrap()).changed, vec![4096..5120]);
+    assert_eq!(data(diff_with_parent(&store, s3).unwrap()).changed, vec![v1.len() as u64..v3.len() as u64]);
+    assert!(diff_with_parent(&store, s1).unwrap().is_none());
+
+    // родитель обязан быть снапшотом
+    let err = put_snapshot(&mut store, o1, &meta(&[o2], 400, "bad")).unwrap_err();
+    assert!(matches!(err, StoreError::Io(_)), "{:?}", err);
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn snapshot_closure_ancestors() {
+    let path = tmp("quarxtor_snapshot_closure.qblk");
+    let mut store = FileBlockStore::open(path.clone()).expect("open store");
+    let cfg = QuarxConfig::default();
+
+    let o1 = Importer::new(&mut store, &cfg).import_bytes(b"first version").unwrap().object_id;
+    let o2 = Importer::new(&mut store, &cfg).import_bytes(b"second version").unwrap().object_id;
+    let s1 = put_snapshot(&mut store, o1, &meta(&[], 1, "one")).unwrap();
+    let s2 = put_snapshot(&mut store, o2, &meta(&[s1], 2, "two")).unwrap();
+
+    let full = ObjectGraph::new(&store).compute_closure_from_object(s2).unwrap();
+    assert!(full.blocks.contains(&s1) && full.blocks.contains(&o1));
+
+    let one = ObjectGraph::new(&store).with_ancestors(false).compute_closure_from_object(s2).unwrap();
+    assert!(one.blocks.contains(&o2));
+    assert!(!one.blocks.contains(&s1) && !one.blocks.contains(&o1));
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn snapshot_walk_ignores_clock_skew() {
+    let path = tmp("quarxtor_snapshot_skew.qblk");
+    let mut store = FileBlockStore::open(path.clone()).expect("open store");
+    let cfg = QuarxConfig::default();
+    let o = Importer::new(&mut store, &cfg).import_bytes(b"same content").unwrap().object_id;
+
+    // часы у s2 отстали: он «старше» своего родителя s1
+    let s1 = put_snapshot(&mut store, o, &meta(&[], 500, "one")).unwrap();
+    let s2 = put_snapshot(&mut store, o, &meta(&[s1], 100, "two")).unwrap();
+    let a = put_snapshot(&mut store, o, &meta(&[s2], 600, "a")).unwrap();
+    let b = put_snapshot(&mut store, o, &meta(&[s2, s1], 700, "b")).unwrap();
+
+    assert_eq!(common_ancestor(&store, a, b).unwrap(), Some(s2));
+    let ids: Vec<_> = snapshot_history(&store, b).unwrap().iter().map(|s| s.id).collect();
+    assert_eq!(ids, [b, s2, s1]);
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn snapshot_diff_of_directories() {
+    let src = std::env::temp_dir().join("quarxtor_snapshot_dir_src");
+    let _ = fs::remove_dir_all(&src);
+    fs::create_dir_all(src.join("sub")).unwrap();
+    let path = tmp("quarxtor_snapshot_dir.qblk");
+    let mut store = FileBlockStore::open(path.clone()).expect("open store");
+    let cfg = QuarxConfig { l0_chunk: 1024, ..QuarxConfig::default() };
+
+    fs::write(src.join("a.txt"), b"alpha").unwrap();
+    fs::write(src.join("b.txt"), b"bravo").unwrap();
+    fs::write(src.join("k"), b"file, later a directory").unwrap();
+    fs::write(src.join("old.txt"), b"to be removed").unwrap();
+    fs::write(src.join("sub").join("c.bin"), vec![3u8; 8192]).unwrap();
+    let d1 = FsImporter::new(&mut store, &cfg).import_path(&src).unwrap().root;
+
+    fs::write(src.join("b.txt"), b"BRAVO!").unwrap();
+    fs::remove_file(src.join("k")).unwrap();
+    fs::create_dir(src.join("k")).unwrap();
+    fs::remove_file(src.join("old.txt")).unwrap();
+    fs::write(src.join("new.txt"), b"added").unwrap();
+    let mut c = vec![3u8; 8192];
+    c[2048..2050].fill(9);
+    fs::write(src.join("sub").join("c.bin"), &c).unwrap();
+    let d2 = FsImporter::new(&mut store, &cfg).import_path(&src).unwrap().root;
+
+    let s1 = put_snapshot(&mut store, d1, &meta(&[], 1, "v1")).unwrap();
+    let s2 = put_snapshot(&mut store, d2, &meta(&[s1], 2, "v2")).unwrap();
+
+    let Some(SnapshotDiff::Dir(changes)) = diff_with_parent(&store, s2).unwrap() else {
+        panic!("expected directory diff");
+    };
+    let paths: Vec<(&str, &str)> = changes.iter().map(|c| match c {
+        DirChange::Added(p) => ("added", p.as_str()),
+        DirChange::Removed(p) => ("removed", p.as_str()),
+        DirChange::Modified(p, _) => ("modified", p.as_str()),
+        DirChange::Changed(p) => ("changed", p.as_str()),
+    }).collect();
+    assert_eq!(paths, [
+        ("modified", "b.txt"),
+        ("changed", "k"),
+        ("added", "new.txt"),
+        ("removed", "old.txt"),
+        ("modified", "sub/c.bin"),
+    ]);
+    let Some(DirChange::Modified(_, d)) = changes.last() else { unreachable!() };
+    assert_eq!(d.changed, vec![2048..3072]);
+
+    // каталог против данных не сравнивается
+    let o = Importer::new(&mut store, &cfg).import_bytes(b"plain data").unwrap().object_id;
+    let s3 = put_snapshot(&mut store, o, &meta(&[s2], 3, "data")).unwrap();
+    assert!(matches!(diff_with_parent(&store, s3), Err(StoreError::Io(_))));
+
+    let _ = fs::remove_dir_all(&src);
+    let _ = fs::remove_file(&path);
+}
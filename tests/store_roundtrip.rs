@@ -4,11 +4,12 @@ use std::fs;
 use smallvec::smallvec;
 
 use quarxtor_core::store::file_store::FileBlockStore;
-use quarxtor_core::store::blockstore::BlockStore;
+use quarxtor_core::store::blockstore::{BlockStore, StoreResult};
 use quarxtor_core::store::decode::BlockBody;
 use quarxtor_core::types::{BlockRef};
 use quarxtor_core::codec::{ZPayload, ObjectPayload};
-use quarxtor_core::types::BlockKind;
+use quarxtor_core::types::{BlockId, BlockKind};
+use quarxtor_core::block::multi::{MultiRecipe, MultiInfo};
 
 #[test]
 fn file_block_store_roundtrip() {
@@ -102,3 +103,148 @@ fn file_block_store_roundtrip() {
 
     let _ = fs::remove_file(&path);
 }
+
+#[test]
+fn file_block_store_contains_and_hash_lookup() {
+    use quarxtor_core::store::blockstore::{hash_l0, hash_object};
+
+    let path: PathBuf = std::env::temp_dir().join("quarxtor_store_contains.qblk");
+    let _ = fs::remove_file(&path);
+
+    let mut store = FileBlockStore::open(path.clone()).expect("open store");
+
+    let mut ids = Vec::new();
+    for i in 0..2000u32 {
+        ids.push(store.put_l0(&i.to_be_bytes()).expect("put_l0"));
+    }
+    let o = ObjectPayload {
+        root:     BlockRef::L0(ids[0]),
+        obj_type: 1,
+        meta:     Vec::new(),
+    };
+    let id_obj = store.put_object(&o).expect("put_object");
+
+    assert!(store.contains(ids[0]));
+    assert!(store.contains(id_obj));
+    assert!(!store.contains(id_obj + 1));
+
+    assert_eq!(store.find_hash(&hash_l0(&1234u32.to_be_bytes())).unwrap(), Some(ids[1234]));
+    assert_eq!(store.find_hash(&hash_object(&o)).unwrap(), Some(id_obj));
+    assert!(!store.contains_hash(&hash_l0(b"never-stored")).unwrap());
+
+    // индекс хэшей восстанавливается при открытии
+    drop(store);
+    let store2 = FileBlockStore::open(path.clone()).expect("re-open store");
+    assert!(store2.contains_hash(&hash_l0(&7u32.to_be_bytes())).unwrap());
+    assert_eq!(store2.find_hash(&hash_l0(&1999u32.to_be_bytes())).unwrap(), Some(ids[1999]));
+    assert!(!store2.contains_hash(&hash_l0(&5000u32.to_be_bytes())).unwrap());
+
+    // дубликаты: побеждает последняя копия, и после переоткрытия тоже
+    let mut store2 = store2;
+    let dup = store2.put_l0(&1234u32.to_be_bytes()).expect("put_l0 dup");
+    assert!(dup > id_obj);
+    assert_eq!(store2.find_hash(&hash_l0(&1234u32.to_be_bytes())).unwrap(), Some(dup));
+    drop(store2);
+    let store3 = FileBlockStore::open(path.clone()).expect("re-open store");
+    assert_eq!(store3.find_hash(&hash_l0(&1234u32.to_be_bytes())).unwrap(), Some(dup));
+
+    let _ = fs::remove_file(&path);
+}
+
+/// Внешний store, реализующий только обязательные методы.
+struct MinimalStore(FileBlockStore);
+
+impl BlockStore for MinimalStore {
+    fn put_l0(&mut self, raw: &[u8]) -> StoreResult<BlockId> {
+        self.0.put_l0(raw)
+    }
+    fn put_multi(&mut self, recipe: &MultiRecipe) -> StoreResult<BlockId> {
+        self.0.put_multi(recipe)
+    }
+    fn put_z(&mut self, z: &ZPayload) -> StoreResult<BlockId> {
+        self.0.put_z(z)
+    }
+    fn put_object(&mut self, o: &ObjectPayload) -> StoreResult<BlockId> {
+        self.0.put_object(o)
+    }
+    fn get_typed(&self, id: BlockId) -> StoreResult<(BlockKind, [u8; 32], BlockBody)> {
+        self.0.get_typed(id)
+    }
+    fn get_frame(&self, id: BlockId) -> StoreResult<Vec<u8>> {
+        self.0.get_frame(id)
+    }
+    fn find_hash(&self, hash: &[u8; 32]) -> StoreResult<Option<BlockId>> {
+        self.0.find_hash(hash)
+    }
+}
+
+#[test]
+fn minimal_store_uses_trait_defaults() {
+    use quarxtor_core::store::blockstore::hash_l0;
+
+    let path: PathBuf = std::env::temp_dir().join("quarxtor_store_minimal.qblk");
+    let _ = fs::remove_file(&path);
+
+    let mut store = MinimalStore(FileBlockStore::open(path.clone()).expect("open store"));
+    let id = store.put_l0(b"minimal").expect("put_l0");
+
+    // contains — через get_header, contains_hash — через find_hash
+    assert!(store.contains(id));
+    assert!(!store.contains(id + 1));
+    assert_eq!(store.find_hash(&hash_l0(b"minimal")).unwrap(), Some(id));
+    assert!(!store.contains_hash(&hash_l0(b"other")).unwrap());
+
+    // put_multi_info без своей реализации — Multi без info
+    let info = MultiInfo { logical_len: 7, hash: [1u8; 32] };
+    let m = store.put_multi_info(&MultiRecipe::Aggregate { blocks: smallvec![id] }, &info).unwrap();
+    assert_eq!(store.get_header(m).unwrap().kind, BlockKind::Multi);
+    assert_eq!(store.get_multi_info(m).unwrap(), None);
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn indexed_recipe_with_bad_offsets_is_rejected() {
+    let path: PathBuf = std::env::temp_dir().join("quarxtor_store_bad_indexed.qblk");
+    let _ = fs::remove_file(&path);
+    let mut store = FileBlockStore::open(path.clone()).expect("open store");
+    let a = store.put_l0(b"aaaa").unwrap();
+    let b = store.put_l0(b"bb").unwrap();
+
+    // число концов не совпадает с числом блоков
+    let short = MultiRecipe::Indexed { blocks: smallvec![a, b], ends: vec![4] };
+    assert!(store.put_multi(&short).is_err());
+    // концы убывают
+    let back = MultiRecipe::Indexed { blocks: smallvec![a, b], ends: vec![4, 2] };
+    let info = MultiInfo { logical_len: 6, hash: [0u8; 32] };
+    assert!(store.put_multi_info(&back, &info).is_err());
+
+    // отказ ничего не пишет: следующий блок получает очередной id
+    let ok = MultiRecipe::Indexed { blocks: smallvec![a, b], ends: vec![4, 6] };
+    assert_eq!(store.put_multi(&ok).unwrap(), b + 1);
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn file_store_multi_info_reads_only_tail() {
+    let path: PathBuf = std::env::temp_dir().join("quarxtor_store_multi_info.qblk");
+    let _ = fs::remove_file(&path);
+    let mut store = FileBlockStore::open(path.clone()).expect("open store");
+
+    let a = store.put_l0(b"abc").unwrap();
+    let info = MultiInfo { logical_len: 3, hash: *blake3::hash(b"abc").as_bytes() };
+    let with = store.put_multi_info(&MultiRecipe::Aggregate { blocks: smallvec![a] }, &info).unwrap();
+    let without = store.put_multi(&MultiRecipe::Aggregate { blocks: smallvec![a] }).unwrap();
+    assert_eq!(store.get_multi_info(with).unwrap(), Some(info));
+    assert_eq!(store.get_multi_info(without).unwrap(), None);
+    assert_eq!(store.get_multi_info(a).unwrap(), None);
+
+    // байты рецепта, похожие на TLV MultiInfo, за info не принимаются
+    let mut fake = vec![0x14, 0, 0, 0, 40];
+    fake.extend_from_slice(&[7u8; 40]);
+    let custom = store.put_multi(&MultiRecipe::Custom { kind_id: 1, payload: fake }).unwrap();
+    assert_eq!(store.get_multi_info(custom).unwrap(), None);
+
+    let _ = fs::remove_file(&path);
+}
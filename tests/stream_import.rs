@@ -0,0 +1,147 @@
+use std::fs;
+use std::io::{self, Read};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use quarxtor_core::store::file_store::FileBlockStore;
+use quarxtor_core::store::blockstore::StoreError;
+use quarxtor_core::config::QuarxConfig;
+use quarxtor_core::import::{Importer, ImportStats};
+use quarxtor_core::reader::ObjectReader;
+use quarxtor_core::analysis::json_spans;
+
+fn tmp(name: &str) -> PathBuf {
+    let p = std::env::temp_dir().join(name);
+    let _ = fs::remove_file(&p);
+    p
+}
+
+/// Поток псевдослучайных байт заданной длины (как pipe: отдаёт кусками).
+struct Gen {
+    left:  u64,
+    state: u64,
+}
+
+impl Read for Gen {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = buf.len().min(self.left as usize).min(3000);
+        for b in &mut buf[..n] {
+            self.state ^= self.state << 13;
+            self.state ^= self.state >> 7;
+            self.state ^= self.state << 17;
+            *b = self.state as u8;
+        }
+        self.left -= n as u64;
+        Ok(n)
+    }
+}
+
+fn digest<R: Read>(mut r: R) -> [u8; 32] {
+    let mut h = blake3::Hasher::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = r.read(&mut buf).unwrap();
+        if n == 0 {
+            return *h.finalize().as_bytes();
+        }
+        h.update(&buf[..n]);
+    }
+}
+
+#[test]
+fn stream_import_reports_progress() {
+    let path = tmp("quarxtor_stream_progress.qblk");
+    let mut store = FileBlockStore::open(path.clone()).expect("open store");
+    let cfg = QuarxConfig { l0_chunk: 4096, multi_fanout: 16, ..QuarxConfig::default() };
+    let total = 8 * 1024 * 1024 + 123;
+
+    let seen: Arc<Mutex<Vec<ImportStats>>> = Arc::default();
+    let log = seen.clone();
+    let mut imp = Importer::new(&mut store, &cfg);
+    imp.set_progress(1024 * 1024, move |s| log.lock().unwrap().push(*s));
+    let r = imp.import_reader(Gen { left: total, state: 7 }).unwrap();
+
+    // вход целиком не материализуется: сверяем потоком
+    let want = digest(Gen { left: total, state: 7 });
+    assert_eq!(digest(ObjectReader::open(&store, r.object_id).unwrap()), want);
+
+    let seen = seen.lock().unwrap();
+    assert!(seen.len() >= 8, "progress calls {}", seen.len());
+    assert!(seen.windows(2).all(|w| w[0].bytes_in <= w[1].bytes_in && w[0].chunks <= w[1].chunks));
+    assert_eq!(*seen.last().unwrap(), r.stats);
+    assert_eq!(r.stats.bytes_in, total);
+    assert_eq!(r.stats.chunks, total.div_ceil(4096));
+
+    // повторный импорт того же потока — только dedup
+    let r2 = Importer::new(&mut store, &cfg).import_reader(Gen { left: total, state: 7 }).unwrap();
+    assert_eq!(r2.stats.dedup_hits, r2.stats.chunks);
+    assert_eq!(r2.stats.bytes_written, 0);
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn stream_import_cancelled() {
+    let path = tmp("quarxtor_stream_cancel.qblk");
+    let mut store = FileBlockStore::open(path.clone()).expect("open store");
+    let cfg = QuarxConfig { l0_chunk: 4096, ..QuarxConfig::default() };
+
+    // отмена из колбэка прогресса после первого мегабайта
+    let cancel = Arc::new(AtomicBool::new(false));
+    let flag = cancel.clone();
+    let mut imp = Importer::new(&mut store, &cfg);
+    imp.set_cancel(cancel.clone());
+    imp.set_progress(1024 * 1024, move |_| flag.store(true, Ordering::Relaxed));
+    let err = imp.import_reader(Gen { left: 64 * 1024 * 1024, state: 3 }).unwrap_err();
+    assert!(matches!(err, StoreError::Cancelled), "{:?}", err);
+    let written = fs::metadata(&path).unwrap().len();
+    assert!(written < 4 * 1024 * 1024, "written {}", written);
+
+    // store остаётся рабочим
+    let r = Importer::new(&mut store, &cfg).import_bytes(b"after cancel").unwrap();
+    let mut out = Vec::new();
+    ObjectReader::open(&store, r.object_id).unwrap().read_to_end(&mut out).unwrap();
+    assert_eq!(out, b"after cancel");
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn stream_import_memory_limit() {
+    let path = tmp("quarxtor_stream_mem.qblk");
+    let mut store = FileBlockStore::open(path.clone()).expect("open store");
+    let cfg = QuarxConfig { l0_chunk: 4096, analysis_z_json: true, ..QuarxConfig::default() };
+
+    let log: String = (0..20_000).map(|i| format!("{{\"seq\":{}}}\n", i)).collect();
+
+    // окно чтения (2 чанка) не влезает в лимит
+    let tiny = QuarxConfig { import_mem_limit: 4096, ..cfg.clone() };
+    let err = Importer::new(&mut store, &tiny).import_bytes(log.as_bytes()).unwrap_err();
+    assert!(matches!(err, StoreError::Io(ref e) if e.kind() == io::ErrorKind::InvalidInput), "{:?}", err);
+
+    // анализатору NDJSON не хватает памяти на 20k записей — снят, данные целы
+    let tight = QuarxConfig { import_mem_limit: 2 * 4096 + 64 * 1024, ..cfg.clone() };
+    let r = Importer::new(&mut store, &tight).import_bytes(log.as_bytes()).unwrap();
+    assert_eq!(r.stats.analyzers_dropped, 1);
+    assert!(json_spans(&store, r.object_id).unwrap().is_empty());
+    let mut out = Vec::new();
+    ObjectReader::open(&store, r.object_id).unwrap().read_to_end(&mut out).unwrap();
+    assert!(out == log.as_bytes());
+
+    // без лимита — зоны на месте
+    let free = QuarxConfig { import_mem_limit: 0, ..cfg.clone() };
+    let r = Importer::new(&mut store, &free).import_bytes(log.as_bytes()).unwrap();
+    assert_eq!(r.stats.analyzers_dropped, 0);
+    assert_eq!(json_spans(&store, r.object_id).unwrap().len(), 20_000);
+
+    // проход уложился, а meta зон — нет: Z-блоки пишутся пачками, те же
+    let short: String = (0..2000).map(|i| format!("{{\"seq\":{}}}\n", i)).collect();
+    let full = Importer::new(&mut store, &free).import_bytes(short.as_bytes()).unwrap();
+    let r = Importer::new(&mut store, &tight).import_bytes(short.as_bytes()).unwrap();
+    assert_eq!(r.stats.analyzers_dropped, 0);
+    assert_eq!(r.stats.zblocks, 2000);
+    assert_eq!(json_spans(&store, r.object_id).unwrap(), json_spans(&store, full.object_id).unwrap());
+
+    let _ = fs::remove_file(&path);
+}
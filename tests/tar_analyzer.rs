@@ -0,0 +1,217 @@
+use std::fs;
+use std::path::PathBuf;
+
+use quarxtor_core::store::file_store::FileBlockStore;
+use quarxtor_core::store::blockstore::BlockStore;
+use quarxtor_core::store::decode::BlockBody;
+use quarxtor_core::codec::decode_dir_meta;
+use quarxtor_core::config::QuarxConfig;
+use quarxtor_core::import::{Importer, FsImporter};
+use quarxtor_core::analysis::{tar_members, tar_member, tar_extract, TarKind};
+use quarxtor_core::types::BlockRef;
+
+fn tmp_dir(name: &str) -> PathBuf {
+    let p = std::env::temp_dir().join(name);
+    let _ = fs::remove_dir_all(&p);
+    fs::create_dir_all(&p).unwrap();
+    p
+}
+
+fn octal(field: &mut [u8], v: u64) {
+    let s = format!("{:0w$o}\0", v, w = field.len() - 1);
+    field.copy_from_slice(s.as_bytes());
+}
+
+/// ustar-заголовок; `gnu` — magic "ustar  \0".
+fn header(name: &str, flag: u8, size: u64, link: &str, gnu: bool) -> Vec<u8> {
+    let mut h = vec![0u8; 512];
+    h[..name.len()].copy_from_slice(name.as_bytes());
+    octal(&mut h[100..108], 0o644);
+    octal(&mut h[108..116], 1000);
+    octal(&mut h[116..124], 1000);
+    octal(&mut h[124..136], size);
+    octal(&mut h[136..148], 1_700_000_000);
+    h[156] = flag;
+    h[157..157 + link.len()].copy_from_slice(link.as_bytes());
+    h[257..265].copy_from_slice(if gnu { b"ustar  \0" } else { b"ustar\x0000" });
+    h[148..156].fill(b' ');
+    let sum: u64 = h.iter().map(|&b| b as u64).sum();
+    let s = format!("{:06o}\0 ", sum);
+    h[148..156].copy_from_slice(s.as_bytes());
+    h
+}
+
+fn with_data(out: &mut Vec<u8>, data: &[u8]) {
+    out.extend_from_slice(data);
+    out.resize(out.len().div_ceil(512) * 512, 0);
+}
+
+fn pax_record(key: &str, val: &str) -> String {
+    // длина записи включает собственные цифры
+    let body = format!(" {}={}\n", key, val);
+    let mut n = body.len() + 1;
+    while format!("{}{}", n, body).len() != n {
+        n += 1;
+    }
+    format!("{}{}", n, body)
+}
+
+fn file_data(seed: u8, len: usize) -> Vec<u8> {
+    (0..len).map(|i| seed.wrapping_add((i % 251) as u8)).collect()
+}
+
+fn archive() -> Vec<u8> {
+    let mut t = Vec::new();
+    t.extend(header("d/", b'5', 0, "", false));
+    t.extend(header("a.txt", b'0', 1000, "", false));
+    with_data(&mut t, &file_data(1, 1000));
+    t.extend(header("d/l", b'2', 0, "a.txt", false));
+
+    let long = format!("deep/{}/file.bin", "x".repeat(140));
+    let pax = pax_record("path", &long) + &pax_record("mtime", "1234567890.5");
+    t.extend(header("PaxHeaders/file.bin", b'x', pax.len() as u64, "", false));
+    with_data(&mut t, pax.as_bytes());
+    t.extend(header("file.bin", b'0', 3000, "", false));
+    with_data(&mut t, &file_data(7, 3000));
+
+    let gnu_name = format!("gnu/{}", "y".repeat(120));
+    t.extend(header("././@LongLink", b'L', gnu_name.len() as u64 + 1, "", true));
+    with_data(&mut t, format!("{}\0", gnu_name).as_bytes());
+    t.extend(header("gnu/short", b'0', 10, "", true));
+    with_data(&mut t, b"0123456789");
+
+    t.extend(vec![0u8; 1024]);
+    t
+}
+
+#[test]
+fn tar_members_list_and_extract() {
+    let path = std::env::temp_dir().join("quarxtor_tar_analyzer.qblk");
+    let _ = fs::remove_file(&path);
+    let mut store = FileBlockStore::open(path.clone()).expect("open store");
+
+    assert!(!QuarxConfig::default().analysis_z_tar);
+    let cfg = QuarxConfig { l0_chunk: 700, analysis_z_tar: true, ..QuarxConfig::default() };
+    let tar = archive();
+    let r = Importer::new(&mut store, &cfg).import_bytes(&tar).unwrap();
+
+    let ms = tar_members(&store, r.object_id).unwrap();
+    let got: Vec<_> = ms.iter().map(|m| (m.path.as_str(), m.kind, m.size)).collect();
+    let long = format!("deep/{}/file.bin", "x".repeat(140));
+    let gnu_name = format!("gnu/{}", "y".repeat(120));
+    assert_eq!(
+        got,
+        [
+            ("d/", TarKind::Dir, 0),
+            ("a.txt", TarKind::File, 1000),
+            ("d/l", TarKind::Symlink, 0),
+            (long.as_str(), TarKind::File, 3000),
+            (gnu_name.as_str(), TarKind::File, 10),
+        ]
+    );
+    assert_eq!(ms[1].mode, 0o644);
+    assert_eq!(ms[1].mtime, 1_700_000_000);
+    assert_eq!(ms[2].link, "a.txt");
+    assert_eq!(ms[3].mtime, 1_234_567_890);
+
+    let mut out = Vec::new();
+    let m = tar_member(&store, r.object_id, &long).unwrap().expect("member");
+    assert_eq!(tar_extract(&store, &m, &mut out).unwrap(), 3000);
+    assert_eq!(out, file_data(7, 3000));
+
+    out.clear();
+    tar_extract(&store, &ms[1], &mut out).unwrap();
+    assert_eq!(out, file_data(1, 1000));
+    out.clear();
+    tar_extract(&store, &ms[4], &mut out).unwrap();
+    assert_eq!(out, b"0123456789");
+    assert!(tar_member(&store, r.object_id, "missing").unwrap().is_none());
+
+    // усечённый архив: член без полных данных не попадает в список
+    let cut = &tar[..512 + 512 + 300];
+    let r = Importer::new(&mut store, &cfg).import_bytes(cut).unwrap();
+    let ms = tar_members(&store, r.object_id).unwrap();
+    assert_eq!(ms.iter().map(|m| m.path.as_str()).collect::<Vec<_>>(), ["d/"]);
+
+    // не архив и выключенный анализатор — членов нет
+    let r = Importer::new(&mut store, &cfg).import_bytes(&vec![b'z'; 5000]).unwrap();
+    assert!(tar_members(&store, r.object_id).unwrap().is_empty());
+    let off = QuarxConfig { analysis_z_tar: false, ..cfg.clone() };
+    let r = Importer::new(&mut store, &off).import_bytes(&tar).unwrap();
+    assert!(tar_members(&store, r.object_id).unwrap().is_empty());
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn tar_oversized_size_stops_scan() {
+    let path = std::env::temp_dir().join("quarxtor_tar_oversized.qblk");
+    let _ = fs::remove_file(&path);
+    let mut store = FileBlockStore::open(path.clone()).expect("open store");
+    let cfg = QuarxConfig { analysis_z_tar: true, ..QuarxConfig::default() };
+
+    let tail = |t: &mut Vec<u8>| {
+        t.extend(header("after.txt", b'0', 10, "", false));
+        with_data(t, b"0123456789");
+        t.extend(vec![0u8; 1024]);
+    };
+
+    // pax size=u64::MAX: выравнивание до записи не помещается в u64
+    let mut t = header("d/", b'5', 0, "", false);
+    let pax = pax_record("size", &u64::MAX.to_string());
+    t.extend(header("PaxHeaders/big", b'x', pax.len() as u64, "", false));
+    with_data(&mut t, pax.as_bytes());
+    t.extend(header("big", b'0', 0, "", false));
+    tail(&mut t);
+    let r = Importer::new(&mut store, &cfg).import_bytes(&t).unwrap();
+    let ms = tar_members(&store, r.object_id).unwrap();
+    assert_eq!(ms.iter().map(|m| m.path.as_str()).collect::<Vec<_>>(), ["d/"]);
+
+    // то же через base-256 поле size в самом заголовке
+    let mut h = header("big", b'0', 0, "", false);
+    h[124..136].copy_from_slice(&[0x80, 0, 0, 0, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]);
+    h[148..156].fill(b' ');
+    let sum: u64 = h.iter().map(|&b| b as u64).sum();
+    h[148..156].copy_from_slice(format!("{:06o}\0 ", sum).as_bytes());
+    let mut t = header("d/", b'5', 0, "", false);
+    t.extend(h);
+    tail(&mut t);
+    let r = Importer::new(&mut store, &cfg).import_bytes(&t).unwrap();
+    let ms = tar_members(&store, r.object_id).unwrap();
+    assert_eq!(ms.iter().map(|m| m.path.as_str()).collect::<Vec<_>>(), ["d/"]);
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn fs_import_analyzes_tar_behind_znode() {
+    let src = tmp_dir("quarxtor_tar_fs_src");
+    let path = std::env::temp_dir().join("quarxtor_tar_fs.qblk");
+    let _ = fs::remove_file(&path);
+    fs::write(src.join("layer.tar"), archive()).unwrap();
+
+    let mut store = FileBlockStore::open(path.clone()).expect("open store");
+    let cfg = QuarxConfig {
+        l0_chunk: 1024,
+        fs_import_z_threshold: 2,
+        analysis_z_tar: true,
+        ..QuarxConfig::default()
+    };
+    let res = FsImporter::new(&mut store, &cfg).import_path(&src).unwrap();
+    assert_eq!(res.summary.znodes, 1);
+    assert_eq!(res.summary.stats.zblocks, 5);
+
+    let BlockBody::Object(o) = store.get_typed(res.root).unwrap().2 else {
+        panic!("expected Object body");
+    };
+    let BlockRef::Object(layer) = decode_dir_meta(&o.meta).unwrap()[0].child else {
+        panic!("expected Object ref");
+    };
+    let m = tar_member(&store, layer, "a.txt").unwrap().expect("member");
+    let mut out = Vec::new();
+    tar_extract(&store, &m, &mut out).unwrap();
+    assert_eq!(out, file_data(1, 1000));
+
+    let _ = fs::remove_dir_all(&src);
+    let _ = fs::remove_file(&path);
+}
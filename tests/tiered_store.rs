@@ -0,0 +1,213 @@
+use std::path::{Path, PathBuf};
+use std::fs;
+
+use smallvec::smallvec;
+
+use quarxtor_core::store::file_store::FileBlockStore;
+use quarxtor_core::store::blockstore::{BlockStore, hash_l0};
+use quarxtor_core::store::decode::{BlockBody, decode_block_frame};
+use quarxtor_core::store::tiered_store::{TieredStore, TIER_SSD, TIER_HDD};
+use quarxtor_core::store::ram_store::RamStore;
+use quarxtor_core::config::QuarxConfig;
+use quarxtor_core::import::Importer;
+use quarxtor_core::store::migrate::{Migrator, MigrationPolicy, TierRule};
+use quarxtor_core::block::multi::{MultiRecipe, MultiInfo};
+use quarxtor_core::codec::ObjectPayload;
+use quarxtor_core::types::BlockRef;
+
+fn tmp(name: &str) -> PathBuf {
+    let p = std::env::temp_dir().join(name);
+    let _ = fs::remove_file(&p);
+    p
+}
+
+fn open_tiers(ssd: &Path, hdd: &Path) -> TieredStore {
+    let mut ts = TieredStore::new();
+    ts.add_tier(TIER_SSD, Box::new(FileBlockStore::open(ssd.to_path_buf()).expect("open ssd")))
+        .expect("add ssd");
+    ts.add_tier(TIER_HDD, Box::new(FileBlockStore::open(hdd.to_path_buf()).expect("open hdd")))
+        .expect("add hdd");
+    ts
+}
+
+#[test]
+fn tiered_store_put_move_reopen() {
+    let ssd = tmp("quarxtor_tiered_ssd.qblk");
+    let hdd = tmp("quarxtor_tiered_hdd.qblk");
+    let journal = tmp("quarxtor_tiered.journal");
+
+    let mut ts = open_tiers(&ssd, &hdd);
+    ts.attach_journal(&journal).expect("journal");
+    assert_eq!(ts.write_tier(), Some(TIER_SSD));
+
+    let id_a = ts.put_l0(b"block-a").expect("put a");
+    ts.set_write_tier(TIER_HDD).expect("write tier");
+    let id_b = ts.put_l0(b"block-b").expect("put b");
+    let info = MultiInfo {
+        logical_len: 14,
+        hash:        *blake3::hash(b"block-ablock-b").as_bytes(),
+    };
+    let id_m = ts
+        .put_multi_info(&MultiRecipe::Aggregate { blocks: smallvec![id_a, id_b] }, &info)
+        .expect("put multi");
+
+    // одно пространство id поверх разных уровней
+    assert_eq!((id_a, id_b, id_m), (0, 1, 2));
+    assert_eq!(ts.tier_of(id_a), Some(TIER_SSD));
+    assert_eq!(ts.tier_of(id_b), Some(TIER_HDD));
+
+    // frame отдаётся с глобальным id
+    let (_, fid, _, _) = decode_block_frame(&ts.get_frame(id_m).unwrap()).unwrap();
+    assert_eq!(fid, id_m);
+
+    ts.move_block(id_a, TIER_HDD).expect("move a");
+    ts.move_block(id_b, TIER_SSD).expect("move b");
+    ts.move_block(id_m, TIER_SSD).expect("move multi");
+    assert_eq!(ts.get_multi_info(id_m).unwrap(), Some(info));
+    assert_eq!(ts.tier_of(id_a), Some(TIER_HDD));
+    assert_eq!(ts.tier_of(id_b), Some(TIER_SSD));
+
+    match ts.get_typed(id_a).unwrap().2 {
+        BlockBody::L0(raw) => assert_eq!(raw, b"block-a"),
+        _ => panic!("expected L0 body"),
+    }
+    assert_eq!(ts.find_hash(&hash_l0(b"block-b")).unwrap(), Some(id_b));
+
+    // размещение восстанавливается из журнала
+    drop(ts);
+    let mut ts = open_tiers(&ssd, &hdd);
+    ts.attach_journal(&journal).expect("re-attach journal");
+    assert_eq!(ts.len(), 3);
+    assert_eq!(ts.tier_of(id_a), Some(TIER_HDD));
+    assert_eq!(ts.tier_of(id_b), Some(TIER_SSD));
+    match ts.get_typed(id_b).unwrap().2 {
+        BlockBody::L0(raw) => assert_eq!(raw, b"block-b"),
+        _ => panic!("expected L0 body"),
+    }
+
+    for p in [ssd, hdd, journal] {
+        let _ = fs::remove_file(&p);
+    }
+}
+
+#[test]
+fn tiered_store_heat_migration() {
+    let ssd = tmp("quarxtor_migrate_ssd.qblk");
+    let hdd = tmp("quarxtor_migrate_hdd.qblk");
+
+    let mut ts = open_tiers(&ssd, &hdd);
+    ts.set_write_tier(TIER_HDD).unwrap();
+
+    let hot = ts.put_l0(b"hot").unwrap();
+    let warm = ts.put_l0(b"warm").unwrap();
+    let image_l0 = ts.put_l0(b"image-data").unwrap();
+    let image = ts
+        .put_object(&ObjectPayload {
+            root:     BlockRef::L0(image_l0),
+            obj_type: 9,
+            meta:     Vec::new(),
+        })
+        .unwrap();
+
+    for _ in 0..8 {
+        ts.get_typed(hot).unwrap();
+    }
+    ts.get_typed(warm).unwrap();
+    ts.get_typed(image).unwrap();
+
+    let mut policy = MigrationPolicy::new(TIER_SSD, TIER_HDD);
+    policy.promote_min_hits = 4;
+    policy.demote_idle_ticks = 16;
+    policy.rules.push(TierRule { obj_type: 9, tier: TIER_SSD });
+    let migrator = Migrator::new(policy);
+
+    let r = migrator.run_once(&mut ts).unwrap();
+    assert_eq!(r.promoted, 1);
+    assert_eq!(r.pinned, 2);
+    assert_eq!(ts.tier_of(hot), Some(TIER_SSD));
+    assert_eq!(ts.tier_of(warm), Some(TIER_HDD));
+    assert_eq!(ts.tier_of(image), Some(TIER_SSD));
+    assert_eq!(ts.tier_of(image_l0), Some(TIER_SSD));
+
+    // hot остывает, пока читают warm
+    for _ in 0..32 {
+        ts.get_typed(warm).unwrap();
+    }
+    let r = migrator.run_once(&mut ts).unwrap();
+    assert_eq!(r.demoted, 1);
+    assert_eq!(ts.tier_of(hot), Some(TIER_HDD));
+    assert_eq!(ts.tier_of(warm), Some(TIER_SSD));
+    // правило держит образ на SSD, несмотря на простой
+    assert_eq!(ts.tier_of(image), Some(TIER_SSD));
+
+    // бюджет ограничивает прогон
+    let mut tight = MigrationPolicy::new(TIER_HDD, TIER_SSD);
+    tight.promote_min_hits = 1;
+    tight.max_moves = 1;
+    for _ in 0..4 {
+        ts.get_typed(warm).unwrap();
+        ts.get_typed(image_l0).unwrap();
+    }
+    let r = Migrator::new(tight).run_once(&mut ts).unwrap();
+    assert_eq!(r.promoted, 1);
+    assert!(r.deferred >= 1);
+
+    for p in [ssd, hdd] {
+        let _ = fs::remove_file(&p);
+    }
+}
+
+#[test]
+fn tiered_store_background_migrator() {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    let ssd = tmp("quarxtor_migrate_bg_ssd.qblk");
+    let hdd = tmp("quarxtor_migrate_bg_hdd.qblk");
+
+    let mut ts = open_tiers(&ssd, &hdd);
+    ts.set_write_tier(TIER_HDD).unwrap();
+    let id = ts.put_l0(b"bg").unwrap();
+    for _ in 0..8 {
+        ts.get_typed(id).unwrap();
+    }
+
+    let store = Arc::new(Mutex::new(ts));
+    let handle = Migrator::new(MigrationPolicy::new(TIER_SSD, TIER_HDD))
+        .spawn(store.clone(), Duration::from_millis(5));
+    std::thread::sleep(Duration::from_millis(30));
+    let total = handle.stop().expect("stop migrator");
+
+    assert_eq!(total.promoted, 1);
+    assert_eq!(store.lock().unwrap().tier_of(id), Some(TIER_SSD));
+
+    for p in [ssd, hdd] {
+        let _ = fs::remove_file(&p);
+    }
+}
+
+#[test]
+fn tiered_store_dedups_across_tiers_and_wrappers() {
+    let ssd = tmp("quarxtor_tiered_dedup_ssd.qblk");
+    let hdd = tmp("quarxtor_tiered_dedup_hdd.qblk");
+    let cfg = QuarxConfig { l0_chunk: 1024, ..QuarxConfig::default() };
+    let data: Vec<u8> = (0..16 * 1024u32).map(|i| (i * 31 % 253) as u8).collect();
+
+    let mut ts = open_tiers(&ssd, &hdd);
+    let first = Importer::new(&mut ts, &cfg).import_bytes(&data).unwrap();
+    assert_eq!(first.stats.dedup_hits, 0);
+
+    // чанки на другом уровне всё равно находятся по хэшу
+    ts.set_write_tier(TIER_HDD).expect("write tier");
+    let second = Importer::new(&mut ts, &cfg).import_bytes(&data).unwrap();
+    assert_eq!(second.stats.dedup_hits, 16);
+
+    // RamStore поверх TieredStore пробрасывает find_hash
+    let mut ram = RamStore::new(ts, 1024 * 1024);
+    let third = Importer::new(&mut ram, &cfg).import_bytes(&data).unwrap();
+    assert_eq!(third.stats.dedup_hits, 16);
+
+    drop(ram);
+    let _ = fs::remove_file(&ssd);
+    let _ = fs::remove_file(&hdd);
+}
@@ -0,0 +1,191 @@
+use std::fs;
+use std::io::{Read, Seek, SeekFrom};
+use std::sync::Arc;
+
+use quarxtor_core::store::file_store::FileBlockStore;
+use quarxtor_core::store::blockstore::{StoreError, StoreResult};
+use quarxtor_core::store::typed::TypedStore;
+use quarxtor_core::codec::{ZRegion, ZSpan, decode_z_region, encode_z_region, encode_z_span, decode_z_span, ZIndexRef, encode_z_index, decode_z_index};
+use quarxtor_core::config::QuarxConfig;
+use quarxtor_core::import::Importer;
+use quarxtor_core::analysis::{ZAnalyzer, ZRegistry, ZScan, ZSource, zblocks_of, put_znode};
+use quarxtor_core::reader::ObjectReader;
+use quarxtor_core::graph::object_graph::ObjectGraph;
+use quarxtor_core::types::ZNodeMeta;
+
+/// Тестовый анализатор: зона на каждую строку, data = номер строки.
+struct Lines;
+
+struct LinesScan {
+    pos:   u64,
+    start: u64,
+    out:   Vec<ZRegion>,
+}
+
+impl ZScan for LinesScan {
+    fn feed(&mut self, data: &[u8]) {
+        for &b in data {
+            self.pos += 1;
+            if b == b'\n' {
+                let n = self.out.len() as u32;
+                self.out.push(ZRegion { offset: self.start, len: self.pos - self.start, data: n.to_be_bytes().to_vec() });
+                self.start = self.pos;
+            }
+        }
+    }
+
+    fn finish(self: Box<Self>, _src: &dyn ZSource) -> StoreResult<Vec<ZRegion>> {
+        Ok(self.out)
+    }
+}
+
+impl ZAnalyzer for Lines {
+    fn z_type(&self) -> u32 {
+        100
+    }
+    fn name(&self) -> &str {
+        "lines"
+    }
+    fn scan(&self) -> Box<dyn ZScan> {
+        Box::new(LinesScan { pos: 0, start: 0, out: Vec::new() })
+    }
+}
+
+/// Анализатор, который никогда ничего не находит.
+struct Never;
+
+struct NeverScan;
+
+impl ZScan for NeverScan {
+    fn feed(&mut self, _data: &[u8]) {}
+    fn finish(self: Box<Self>, _src: &dyn ZSource) -> StoreResult<Vec<ZRegion>> {
+        Ok(Vec::new())
+    }
+}
+
+impl ZAnalyzer for Never {
+    fn z_type(&self) -> u32 {
+        200
+    }
+    fn name(&self) -> &str {
+        "never"
+    }
+    fn scan(&self) -> Box<dyn ZScan> {
+        Box::new(NeverScan)
+    }
+}
+
+/// Анализатор с зоной за концом данных.
+struct Broken;
+
+impl ZAnalyzer for Broken {
+    fn z_type(&self) -> u32 {
+        300
+    }
+    fn name(&self) -> &str {
+        "broken"
+    }
+    fn scan(&self) -> Box<dyn ZScan> {
+        struct S;
+        impl ZScan for S {
+            fn feed(&mut self, _data: &[u8]) {}
+            fn finish(self: Box<Self>, _src: &dyn ZSource) -> StoreResult<Vec<ZRegion>> {
+                Ok(vec![ZRegion { offset: 0, len: 1 << 40, data: Vec::new() }])
+            }
+        }
+        Box::new(S)
+    }
+}
+
+#[test]
+fn analyzers_emit_zblocks_over_l0_ranges() {
+    let path = std::env::temp_dir().join("quarxtor_z_analyzer.qblk");
+    let _ = fs::remove_file(&path);
+    let mut store = FileBlockStore::open(path.clone()).expect("open store");
+
+    let mut reg = ZRegistry::new();
+    assert!(reg.register(Arc::new(Never)).is_none());
+    assert!(reg.register(Arc::new(Lines)).is_none());
+    assert!(reg.register(Arc::new(Lines)).is_some());
+    assert_eq!(reg.z_types().collect::<Vec<_>>(), [100, 200]);
+
+    let content = b"first line\nsecond\n\nthe last line is longer\n";
+    let cfg = QuarxConfig { l0_chunk: 4, ..QuarxConfig::default() };
+    let mut imp = Importer::new(&mut store, &cfg);
+    imp.set_analyzers(&reg);
+    let r = imp.import_bytes(content).unwrap();
+    assert_eq!(r.stats.zblocks, 4);
+
+    let zs = zblocks_of(&store, r.object_id, None).unwrap();
+    assert_eq!(zs.len(), 4);
+    assert!(zblocks_of(&store, r.object_id, Some(200)).unwrap().is_empty());
+    assert_eq!(zblocks_of(&store, r.object_id, Some(100)).unwrap().len(), 4);
+
+    let mut rd = ObjectReader::open(&store, r.object_id).unwrap();
+    for (i, z) in zs.iter().enumerate() {
+        assert_eq!(z.z_type, 100);
+        let reg = decode_z_region(&z.meta).expect("region meta");
+        assert_eq!(reg.data, (i as u32).to_be_bytes());
+
+        // байты зоны — ровно одна строка
+        let mut line = vec![0u8; reg.len as usize];
+        rd.seek(SeekFrom::Start(reg.offset)).unwrap();
+        rd.read_exact(&mut line).unwrap();
+        assert_eq!(line.last(), Some(&b'\n'));
+        assert!(!line[..line.len() - 1].contains(&b'\n'));
+
+        // крайние L0 — чанки, содержащие первый и последний байт зоны
+        let chunk = |off: u64| {
+            let s = (off / 4 * 4) as usize;
+            content[s..(s + 4).min(content.len())].to_vec()
+        };
+        assert_eq!(store.get_l0_data(z.first_l0).unwrap().1, chunk(reg.offset));
+        assert_eq!(store.get_l0_data(z.last_l0).unwrap().1, chunk(reg.offset + reg.len - 1));
+    }
+
+    // замыкание объекта включает его Z-блоки; через Z-node они тоже видны
+    let closure = ObjectGraph::new(&store).compute_closure_from_object(r.object_id).unwrap();
+    assert!(zs.iter().all(|z| closure.blocks.contains(&z.id)));
+    let zn = put_znode(&mut store, r.object_id, &ZNodeMeta { size_bytes: content.len() as u64, blocks: 11 }).unwrap();
+    assert_eq!(zblocks_of(&store, zn, None).unwrap().len(), 4);
+
+    // повторный импорт: Z-блоки дедуплицируются
+    let mut imp = Importer::new(&mut store, &cfg);
+    imp.set_analyzers(&reg);
+    let r2 = imp.import_bytes(content).unwrap();
+    let ids = |v: Vec<quarxtor_core::block::ZBlock>| v.into_iter().map(|z| z.id).collect::<Vec<_>>();
+    assert_eq!(ids(zblocks_of(&store, r2.object_id, None).unwrap()), ids(zs));
+
+    // без анализаторов meta пустая, Z-блоков нет
+    let plain = Importer::new(&mut store, &cfg).import_bytes(b"other\ndata\n").unwrap();
+    assert!(zblocks_of(&store, plain.object_id, None).unwrap().is_empty());
+
+    // зона за пределами данных — ошибка
+    let mut bad = ZRegistry::new();
+    bad.register(Arc::new(Broken));
+    let mut imp = Importer::new(&mut store, &cfg);
+    imp.set_analyzers(&bad);
+    assert!(matches!(imp.import_bytes(content), Err(StoreError::Corrupt(_))));
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn z_region_and_refs_codec_roundtrip() {
+    let r = ZRegion { offset: 17, len: 4096, data: b"opaque".to_vec() };
+    assert_eq!(decode_z_region(&encode_z_region(&r)), Some(r.clone()));
+    assert_eq!(decode_z_region(&[]), None);
+
+    let sp = ZSpan { base: 4096, l0s: vec![5, 9, 2] };
+    let mut meta = encode_z_region(&r);
+    meta.extend_from_slice(&encode_z_span(&sp));
+    assert_eq!(decode_z_span(&meta), Some(sp));
+    assert_eq!(decode_z_span(&encode_z_region(&r)), None);
+
+    let idx = [
+        ZIndexRef { z_type: 100, index: 7, by_key: None },
+        ZIndexRef { z_type: 200, index: 8, by_key: Some(9) },
+    ];
+    assert_eq!(decode_z_index(&encode_z_index(&idx)), Some(idx.to_vec()));
+    assert_eq!(decode_z_index(&encode_z_region(&r)), Some(Vec::new()));
+}
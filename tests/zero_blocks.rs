@@ -0,0 +1,142 @@
+use std::fs;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::PathBuf;
+
+use quarxtor_core::store::file_store::FileBlockStore;
+use quarxtor_core::store::blockstore::{
+    BlockStore, is_zero_block, zero_block_id, zero_block_len, hash_l0, ZERO_BLOCK_MAX, ZERO_BLOCK_TAG,
+};
+use quarxtor_core::store::decode::{BlockBody, decode_block_frame};
+use quarxtor_core::store::ram_store::RamStore;
+use quarxtor_core::store::tiered_store::{TieredStore, TIER_SSD};
+use quarxtor_core::store::typed::TypedStore;
+use quarxtor_core::config::QuarxConfig;
+use quarxtor_core::import::Importer;
+use quarxtor_core::graph::ObjectGraph;
+use quarxtor_core::reader::ObjectReader;
+use quarxtor_core::export::{Restorer, RestoreOptions};
+
+fn tmp(name: &str) -> PathBuf {
+    let p = std::env::temp_dir().join(name);
+    let _ = fs::remove_file(&p);
+    p
+}
+
+/// «Образ диска»: 2 MiB нулей с парой островков данных не по границе чанка.
+fn image() -> Vec<u8> {
+    let mut img = vec![0u8; 2 * 1024 * 1024];
+    for (i, b) in img[100_000..130_000].iter_mut().enumerate() {
+        *b = (i % 251) as u8 + 1;
+    }
+    img[1_500_000..1_500_100].fill(0xEE);
+    img
+}
+
+#[test]
+fn zero_chunks_imported_as_holes() {
+    let path = tmp("quarxtor_zero_import.qblk");
+    let mut store = FileBlockStore::open(path.clone()).expect("open store");
+    let cfg = QuarxConfig { l0_chunk: 4096, ..QuarxConfig::default() };
+
+    let img = image();
+    let r = Importer::new(&mut store, &cfg).import_bytes(&img).unwrap();
+    let chunks = img.len() as u64 / 4096;
+    assert_eq!(r.stats.chunks, chunks);
+    // данные задевают 9 чанков, остальные — дыры без frame'ов
+    assert_eq!(r.stats.zero_chunks, chunks - 9);
+    assert_eq!(r.stats.bytes_written, 9 * 4096);
+    assert!(fs::metadata(&path).unwrap().len() < 64 * 1024);
+
+    // чтение: нули синтезируются, seek в середину дыры и через границу
+    let mut rd = ObjectReader::open(&store, r.object_id).unwrap();
+    let mut all = Vec::new();
+    rd.read_to_end(&mut all).unwrap();
+    assert!(all == img);
+    rd.seek(SeekFrom::Start(99_000)).unwrap();
+    let mut buf = vec![0u8; 2000];
+    rd.read_exact(&mut buf).unwrap();
+    assert_eq!(buf[..], img[99_000..101_000]);
+    rd.seek(SeekFrom::Start(98_000)).unwrap();
+    assert_eq!(rd.hole_len().unwrap(), 98_304 - 98_000);
+    rd.seek(SeekFrom::Start(100_000)).unwrap();
+    assert_eq!(rd.hole_len().unwrap(), 0);
+
+    // в замыкании только записанные блоки
+    let closure = ObjectGraph::new(&store).compute_closure_from_object(r.object_id).unwrap();
+    assert!(closure.blocks.iter().all(|id| !is_zero_block(*id) && store.get_frame(*id).is_ok()));
+
+    // повторный импорт: ненулевые чанки — dedup, дыры снова без записи
+    let again = Importer::new(&mut store, &cfg).import_bytes(&img).unwrap();
+    assert_eq!(again.stats.dedup_hits, 9);
+    assert_eq!(again.stats.zero_chunks, chunks - 9);
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn zero_holes_restored_sparse() {
+    let path = tmp("quarxtor_zero_restore.qblk");
+    let dst = tmp("quarxtor_zero_restore.img");
+    let mut store = FileBlockStore::open(path.clone()).expect("open store");
+    let cfg = QuarxConfig { l0_chunk: 4096, ..QuarxConfig::default() };
+
+    let img = image();
+    let r = Importer::new(&mut store, &cfg).import_bytes(&img).unwrap();
+    let rep = Restorer::new(&store, RestoreOptions::default()).restore(r.object_id, &dst).unwrap();
+    assert_eq!(rep.verified, 1);
+    assert!(rep.hole_bytes >= r.stats.zero_chunks * 4096, "hole bytes {}", rep.hole_bytes);
+    assert!(fs::read(&dst).unwrap() == img);
+
+    // без sparse нули пишутся как есть, hash сходится так же
+    let _ = fs::remove_file(&dst);
+    let dense = Restorer::new(&store, RestoreOptions { sparse: false, ..RestoreOptions::default() })
+        .restore(r.object_id, &dst)
+        .unwrap();
+    assert_eq!((dense.verified, dense.hole_bytes), (1, 0));
+    assert!(fs::read(&dst).unwrap() == img);
+
+    let _ = fs::remove_file(&dst);
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn zero_blocks_synthesized_by_stores() {
+    let path = tmp("quarxtor_zero_ram.qblk");
+    let ssd = tmp("quarxtor_zero_ssd.qblk");
+
+    let mut ram = RamStore::new(FileBlockStore::open(path.clone()).expect("open store"), u64::MAX);
+    let mut ts = TieredStore::new();
+    ts.add_tier(TIER_SSD, Box::new(FileBlockStore::open(ssd.clone()).expect("open ssd")))
+        .expect("add ssd");
+
+    let stores: [&mut dyn BlockStore; 2] = [&mut ram, &mut ts];
+    for s in stores {
+        let id = zero_block_id(1000).unwrap();
+        assert_eq!(zero_block_len(id), Some(1000));
+        assert!(s.contains(id));
+
+        let (l0, raw) = s.get_l0_data(id).unwrap();
+        assert_eq!((l0.id, raw), (id, vec![0u8; 1000]));
+        assert_eq!(s.get_header(id).unwrap().l0_raw_len(), Some(1000));
+        assert_eq!(s.get_header(id).unwrap().hash, hash_l0(&[0u8; 1000]));
+        let (_kind, fid, hash, _payload) = decode_block_frame(&s.get_frame(id).unwrap()).unwrap();
+        assert_eq!((fid, hash), (id, hash_l0(&[0u8; 1000])));
+        assert!(matches!(s.get_typed(id).unwrap().2, BlockBody::L0(r) if r.len() == 1000));
+        assert!(s.find_hash(&hash_l0(&[0u8; 1000])).unwrap().is_none());
+
+        // put_l0 нули не распознаёт: обычный блок, обычный id
+        let stored = s.put_l0(&[0u8; 1000]).unwrap();
+        assert_eq!(stored, 0);
+        assert_eq!(s.find_hash(&hash_l0(&[0u8; 1000])).unwrap(), Some(stored));
+    }
+    assert_eq!(ram.stats().blocks, 0);
+
+    // длина дыры ограничена: длиннее — не нулевой id
+    assert!(zero_block_id(ZERO_BLOCK_MAX).is_some());
+    assert!(zero_block_id(ZERO_BLOCK_MAX + 1).is_none());
+    assert!(!is_zero_block(ZERO_BLOCK_TAG | (ZERO_BLOCK_MAX + 1)));
+    assert!(ram.get_header(ZERO_BLOCK_TAG | u32::MAX as u64).is_err());
+
+    let _ = fs::remove_file(&path);
+    let _ = fs::remove_file(&ssd);
+}